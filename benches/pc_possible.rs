@@ -7,16 +7,27 @@ use bitris_commands::pc_possible;
 use bitris_commands::prelude::*;
 
 #[inline(always)]
-fn pc_possible(data: &PcPossibleBenchmarkData) {
+fn pc_possible(data: &PcPossibleBenchmarkData, transposition_capacity: usize) {
     let move_rules = MoveRules::srs(AllowMove::Softdrop);
     let clipped_board = ClippedBoard::try_new(data.board, data.height).unwrap();
     let executor = pc_possible::PcPossibleBulkExecutor::try_new(
         &move_rules, clipped_board, &data.patterns, true,
-    ).unwrap();
+    ).unwrap().with_transposition_capacity(transposition_capacity);
     let result = executor.execute();
     assert_eq!(result.count_succeed(), data.expected);
 }
 
+#[inline(always)]
+fn pc_possible_in_parallel(data: &PcPossibleBenchmarkData, threads: usize) {
+    let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    let clipped_board = ClippedBoard::try_new(data.board, data.height).unwrap();
+    let executor = pc_possible::PcPossibleBulkExecutor::try_new(
+        &move_rules, clipped_board, &data.patterns, true,
+    ).unwrap();
+    let result = executor.execute_in_parallel(threads);
+    assert_eq!(result.count_succeed(), data.expected);
+}
+
 #[derive(Debug)]
 struct PcPossibleBenchmarkData {
     id: String,
@@ -124,7 +135,17 @@ fn bench_pc_possibles(c: &mut Criterion) {
     benchmarks.iter().for_each(|benchmark| {
         let id = format!("pc-rates-{}", benchmark.id);
         c.bench_function(id.as_str(), |b| {
-            b.iter(|| pc_possible(benchmark));
+            b.iter(|| pc_possible(benchmark, pc_possible::DEFAULT_TRANSPOSITION_CAPACITY));
+        });
+
+        let id = format!("pc-rates-{}-without-transposition", benchmark.id);
+        c.bench_function(id.as_str(), |b| {
+            b.iter(|| pc_possible(benchmark, 0));
+        });
+
+        let id = format!("pc-rates-{}-4threads", benchmark.id);
+        c.bench_function(id.as_str(), |b| {
+            b.iter(|| pc_possible_in_parallel(benchmark, 4));
         });
     });
 }
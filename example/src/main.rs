@@ -1 +1,3 @@
+mod pc_chain;
+mod pc_path;
 mod pc_possible;
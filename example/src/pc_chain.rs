@@ -0,0 +1,43 @@
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use bitris_commands::pc_chain::*;
+    use bitris_commands::prelude::*;
+
+    // Finds how many PCs can be taken in a row with SRS.
+    #[test]
+    fn srs() {
+        use Shape::*;
+
+        // Makes a binder with SRS. Default values are already set in Binder.
+        // Please check the documentation of `PcChainBulkExecutorBinder::default()` for details.
+        let mut binder = PcChainBulkExecutorBinder::srs();
+
+        // The first PC is taken on this board, and the following PCs are taken on the blank board of `chain_height`.
+        binder.clipped_board = ClippedBoard::try_new(Board64::blank(), 2).expect("Failed to clip");
+        binder.chain_height = 2;
+
+        // Sets sequences long enough for some PCs. `OOOOO OOOOO OOOO*`
+        binder.pattern = Rc::from(Pattern::try_from(vec![
+            PatternElement::Fixed(BitShapes::try_from(vec![O, O, O, O, O, O, O, O, O, O, O, O, O, O]).unwrap()),
+            PatternElement::Wildcard,
+        ]).expect("Failed to create a pattern"));
+
+        // Finds the maximum count of PCs of each sequence.
+        // The hold and the unused shapes at each PC are carried over to the next.
+        let results = binder.try_execute().expect("Failed to execute");
+        assert_eq!(results.count_keys(), 7);
+        assert_eq!(results.max_chain(), Some(3));
+
+        // All sequences can take two PCs, and only one can take three.
+        assert_eq!(results.count_at_least(2), 7);
+        assert_eq!(results.count_at_least(3), 1);
+        assert_eq!(results.distribution().get(&2), Some(&6));
+
+        // If only a few PCs matter, the search can be limited.
+        binder.max_chain = 2;
+        let results = binder.try_execute().expect("Failed to execute");
+        assert_eq!(results.max_chain(), Some(2));
+    }
+}
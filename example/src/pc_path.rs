@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use std::str::FromStr;
+
+    use bitris_commands::pc_path::*;
+    use bitris_commands::prelude::*;
+
+    // Enumerates all PC solutions with SRS.
+    #[test]
+    fn srs() {
+        // Makes a binder with SRS. Default values are already set in Binder.
+        // Please check the documentation of `PcPathExecutorBinder::default()` for details.
+        let mut binder = PcPathExecutorBinder::srs();
+
+        // Sets a board and goal for 4 lines PC.
+        let board = Board64::from_str("
+            ####....##
+            #####..###
+            ##########
+            #####..###
+        ").expect("Failed to create a board");
+        let height = 4;
+        binder.clipped_board = ClippedBoard::try_new(board, height).expect("Failed to clip");
+
+        // Sets sequences in which you want to find solutions.
+        binder.pattern = Rc::from(Pattern::try_from(vec![
+            PatternElement::Permutation(ShapeCounter::one_of_each(), 3),
+        ]).expect("Failed to create a pattern"));
+
+        // Finds all solutions. If it contains an invalid configuration, an error is returned.
+        let results = binder.try_execute().expect("Failed to execute");
+
+        // Solutions with the same layout are merged, even if the build orders differ.
+        assert!(0 < results.count_solutions());
+
+        // Each solution knows the sequences it can be built from.
+        for (index, solution) in results.solutions().iter().enumerate() {
+            // The pieces are located on the board before lines are cleared.
+            assert_eq!(solution.pieces().len(), 2);
+
+            // The operations are one of the valid build orders.
+            assert_eq!(solution.operations().len(), 2);
+
+            assert!(0 < results.count_covered(index));
+        }
+
+        // The count of sequences that have at least one solution is equal to the PC possible count.
+        assert_eq!(results.count_succeed(), 90);
+
+        // You can also look up solutions from a sequence.
+        use Shape::*;
+        let solutions = results.solutions_for(&ShapeSequence::new(vec![O, I, T]));
+        assert_eq!(solutions.len(), 1);
+    }
+
+    // Finds the smallest set of solutions to memorize.
+    #[test]
+    fn minimal_cover() {
+        let mut binder = PcPathExecutorBinder::srs();
+
+        let board = Board64::from_str("
+            ####....##
+            #####..###
+            ##########
+            #####..###
+        ").expect("Failed to create a board");
+        binder.clipped_board = ClippedBoard::try_new(board, 4).expect("Failed to clip");
+        binder.pattern = Rc::from(Pattern::try_from(vec![
+            PatternElement::Permutation(ShapeCounter::one_of_each(), 3),
+        ]).expect("Failed to create a pattern"));
+
+        let results = binder.try_execute().expect("Failed to execute");
+
+        // `Greedy` is fast but may not be minimal. `Exact` is minimal but may be slow for large cases.
+        let cover = results.minimal_cover(CoverStrategy::Exact);
+
+        // The selected solutions cover all sequences for which a PC is possible.
+        assert_eq!(cover.count_covered(), results.count_succeed());
+
+        // Each entry has the coverage of the solution.
+        let attributed: u64 = cover.entries().iter()
+            .map(|entry| entry.count_attributed)
+            .sum();
+        assert_eq!(attributed, cover.count_covered());
+    }
+}
@@ -106,6 +106,79 @@ mod tests {
         );
     }
 
+    // Breaks down the success rate by the head shapes.
+    #[test]
+    fn bulk_success_tree() {
+        let mut binder = PcPossibleBulkExecutorBinder::srs();
+        let board = Board64::from_str("
+            ###.....##
+            ###....###
+            ###...####
+            ###....###
+        ").expect("Failed to create a board");
+        binder.clipped_board = ClippedBoard::try_new(board, 4).expect("Failed to clip");
+        binder.pattern = Rc::from(Pattern::try_from(vec![
+            PatternElement::One(Shape::I),
+            PatternElement::Permutation(ShapeCounter::one_of_each(), 4),
+        ]).expect("Failed to create a pattern"));
+        let results = binder.try_execute().expect("Failed to execute");
+
+        // Aggregates by the first two shapes, and sorts by the success rate.
+        let builder = SuccessTreeBuilder {
+            depth: 2,
+            order: SuccessTreeOrder::RateDescending,
+        };
+        let tree = builder.build(&results);
+
+        // As structured data.
+        let node = tree.find(&[Shape::I, Shape::S]).expect("Not found");
+        assert_eq!(node.count_succeed, 98);
+        assert_eq!(node.count_accepted, 120);
+        assert_eq!(tree.root().children.len(), 1); // Only `I`
+        assert_eq!(tree.root().children[0].children.len(), 7);
+
+        // As text like `percent -t` in solution-finder.
+        let text = tree.to_string();
+        assert!(text.starts_with("* -> 84.64 % [711/840]\n  I -> 84.64 % [711/840]\n"));
+        assert!(text.contains("    IS -> 81.67 % [98/120]\n"));
+    }
+
+    // Lists the failed sequences.
+    #[test]
+    fn bulk_failures() {
+        let mut binder = PcPossibleBulkExecutorBinder::srs();
+        let board = Board64::from_str("
+            ####....##
+            ####...###
+            ####..####
+            ####...###
+        ").expect("Failed to create a board");
+        binder.clipped_board = ClippedBoard::try_new(board, 4).expect("Failed to clip");
+        binder.pattern = Rc::from(Pattern::try_from(vec![
+            PatternElement::Permutation(ShapeCounter::one_of_each(), 4),
+        ]).expect("Failed to create a pattern"));
+        binder.allows_hold = true;
+        let results = binder.try_execute().expect("Failed to execute");
+
+        // In lexicographic order.
+        let failed = results.failed_sequences();
+        assert_eq!(failed.len() as u64, results.count_failed());
+
+        // Grouped by the first shape or by the shapes used.
+        let by_prefix = results.failed_by_prefix(1);
+        assert_eq!(by_prefix.values().map(|sequences| sequences.len()).sum::<usize>(), failed.len());
+        let by_counter = results.failed_by_shape_counter();
+        assert_eq!(by_counter.values().map(|sequences| sequences.len()).sum::<usize>(), failed.len());
+
+        // Collapsed into wildcard orders like `[SZ]**`, merged by the hold the results were searched with.
+        let collapsed = results.collapse_failed();
+        assert!(collapsed.len() < failed.len());
+        assert_eq!(collapsed.iter().map(|order| order.count_sequences).sum::<u64>(), results.count_failed());
+        for order in &collapsed {
+            println!("{}", order);
+        }
+    }
+
     // Use early stopping.
     #[test]
     fn bulk_using_early_stopping() {
@@ -143,6 +216,121 @@ mod tests {
         assert!(0 < result.count_pending()); // There are still sequences to be explored.
     }
 
+    // Use multiple threads.
+    #[test]
+    fn bulk_in_parallel() {
+        let mut binder = PcPossibleBulkExecutorBinder::srs();
+
+        let board = Board64::from_str("
+            ###.....##
+            ###....###
+            ###...####
+            ###....###
+        ").expect("Failed to create a board");
+        let height = 4;
+        binder.clipped_board = ClippedBoard::try_new(board, height).expect("Failed to clip");
+        binder.pattern = Rc::from(Pattern::try_from(vec![
+            PatternElement::One(Shape::I),
+            PatternElement::Permutation(ShapeCounter::one_of_each(), 4),
+        ]).expect("Failed to create a pattern"));
+
+        // Sets the count of threads. The sequences are distributed to the threads.
+        // The rotation system is shared among the threads, so it must be `Sync`.
+        // The results are the same as the single thread.
+        let results = binder.try_execute_in_parallel(4).expect("Failed to execute");
+        assert_eq!(results.count_succeed(), 711);
+        assert_eq!(results.count_accepted(), 840);
+    }
+
+    // Reports the progress and stops by a time budget or cancellation.
+    #[test]
+    fn bulk_with_progress() {
+        let mut binder = PcPossibleBulkExecutorBinder::srs();
+
+        let board = Board64::from_str("
+            ###.....##
+            ###....###
+            ###...####
+            ###....###
+        ").expect("Failed to create a board");
+        let height = 4;
+        binder.clipped_board = ClippedBoard::try_new(board, height).expect("Failed to clip");
+        binder.pattern = Rc::from(Pattern::try_from(vec![
+            PatternElement::One(Shape::I),
+            PatternElement::Permutation(ShapeCounter::one_of_each(), 4),
+        ]).expect("Failed to create a pattern"));
+
+        // Stops after 60 seconds, or when `cancel()` is called on a clone of the token (e.g. from another thread).
+        let token = CancellationToken::new();
+        let stop_conditions = StopConditions {
+            deadline: Some(std::time::Instant::now() + std::time::Duration::from_secs(60)),
+            cancellation: Some(token.clone()),
+        };
+
+        // The observer is called each time a sequence is accepted. It's cheap because the counts are kept up to date.
+        let mut last_progress = None;
+        let results = binder.try_execute_with_progress(&stop_conditions, |progress| {
+            // `progress.sequences_per_second()` and `progress.estimated_remaining()` are also available.
+            last_progress = Some(*progress);
+            ExecuteInstruction::Continue
+        }).expect("Failed to execute");
+        assert_eq!(results.count_accepted(), 840);
+
+        let last_progress = last_progress.unwrap();
+        assert_eq!(last_progress.count_succeed, 711);
+        assert_eq!(last_progress.count_pending, 0);
+
+        // The sequences not searched remain pending after cancellation.
+        token.cancel();
+        let results = binder.try_execute_with_progress(&stop_conditions, |_| ExecuteInstruction::Continue)
+            .expect("Failed to execute");
+        assert_eq!(results.count_pending(), 840);
+    }
+
+    // Saves a checkpoint and resumes from it.
+    #[test]
+    fn bulk_with_checkpoint() {
+        let mut binder = PcPossibleBulkExecutorBinder::srs();
+
+        let board = Board64::from_str("
+            ###.....##
+            ###....###
+            ###...####
+            ###....###
+        ").expect("Failed to create a board");
+        let height = 4;
+        binder.clipped_board = ClippedBoard::try_new(board, height).expect("Failed to clip");
+        binder.pattern = Rc::from(Pattern::try_from(vec![
+            PatternElement::One(Shape::I),
+            PatternElement::Permutation(ShapeCounter::one_of_each(), 4),
+        ]).expect("Failed to create a pattern"));
+
+        // Suppose the execution is interrupted halfway.
+        let interrupted = binder.try_execute_with_early_stopping(|results| {
+            if results.count_accepted() < 400 {
+                ExecuteInstruction::Continue
+            } else {
+                ExecuteInstruction::Stop
+            }
+        }).expect("Failed to execute");
+
+        // Saves the results. Usually, it's written to a file.
+        let mut checkpoint = Vec::<u8>::new();
+        binder.try_save_checkpoint(&interrupted, &mut checkpoint).expect("Failed to save");
+
+        // Only the pending sequences are searched.
+        let results = binder.try_resume(checkpoint.as_slice()).expect("Failed to resume");
+        assert_eq!(results.count_succeed(), 711);
+        assert_eq!(results.count_accepted(), 840);
+
+        // The checkpoint belongs to the board, pattern, move rules and hold. It cannot be resumed with others.
+        binder.allows_hold = false;
+        assert!(matches!(
+            binder.try_resume(checkpoint.as_slice()),
+            Err(PcCheckpointError::ConfigurationMismatch),
+        ));
+    }
+
     // Use with customized kicks
     #[test]
     fn customized_kick() {
@@ -162,6 +350,9 @@ mod tests {
             }
         }
 
+        // No 180° rotation by default.
+        impl RotationSystem180 for MyKickTable {}
+
         // Makes a binder with customized kicks.
         let rotation_system = Rc::from(MyKickTable);
         let mut binder = PcPossibleBulkExecutorBinder::default(rotation_system);
@@ -2,7 +2,7 @@ use bitris::pieces::Shape;
 use derive_more::Constructor;
 
 use crate::internal_macros::forward_impl_try_from;
-use crate::ShapeSequence;
+use crate::{Mirror, ShapeSequence};
 
 /// Preserves the order of shapes.
 /// Internally, it's represented in bits, making copying and comparing lightweight.
@@ -112,6 +112,13 @@ impl TryFrom<&ShapeSequence> for BitShapes {
 
 forward_impl_try_from!(BitShapes, BitShapesCreationError, from ShapeSequence);
 
+impl Mirror for BitShapes {
+    fn mirror(&self) -> Self {
+        let shapes: Vec<Shape> = self.to_vec().into_iter().map(|shape| shape.mirror()).collect();
+        BitShapes::try_from(shapes).unwrap()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use bitris::pieces::Shape;
@@ -1,6 +1,8 @@
 use bitris::boards::{Board64, BoardOp};
 use thiserror::Error;
 
+use crate::Mirror;
+
 /// Holds a board and height.
 #[derive(Copy, Clone, PartialEq, PartialOrd, Hash, Debug)]
 pub struct ClippedBoard {
@@ -101,3 +103,29 @@ impl ClippedBoard {
         self.height
     }
 }
+
+impl Mirror for ClippedBoard {
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris_commands::prelude::*;
+    ///
+    /// let board = Board64::from_str("
+    ///     XX........
+    ///     XXX.......
+    /// ").unwrap();
+    /// let clipped = ClippedBoard::try_new(board, 4).unwrap();
+    ///
+    /// let expected = Board64::from_str("
+    ///     ........XX
+    ///     .......XXX
+    /// ").unwrap();
+    /// assert_eq!(clipped.mirror(), ClippedBoard::try_new(expected, 4).unwrap());
+    /// ```
+    fn mirror(&self) -> Self {
+        let mut board = Board64::blank();
+        for x in 0..10 {
+            board.cols[x] = self.board.cols[9 - x];
+        }
+        Self { board, height: self.height }
+    }
+}
@@ -0,0 +1,189 @@
+use bitris::prelude::*;
+use thiserror::Error;
+
+use crate::input_moves::{blocks_of, drop_to_bottom};
+use crate::{ClippedBoard, Input, InputLimitedMoves, RotationSystem180, SpawnRule};
+
+/// A collection of errors that occur when replaying inputs.
+#[derive(Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum FinesseReplayError {
+    #[error("The stack blocks the spawn.")]
+    SpawnIsBlocked,
+    #[error("The input at {0} is not available under the settings.")]
+    UnavailableInput(usize),
+    #[error("The input at {0} follows the harddrop.")]
+    InputAfterHarddrop(usize),
+    #[error("The inputs do not end with the harddrop.")]
+    MissingHarddrop,
+}
+
+/// The calculator of the shortest inputs to perform placements, and the verifier to replay them.
+///
+/// The inputs end with `Input::Harddrop`, which is counted like the others.
+/// The pieces spawn by the spawn rule, so the inputs differ depending on the spawn. The placements with the same blocks are regarded as the same.
+/// The 180° rotation is used if the rotation system defines it.
+/// ```
+/// use std::str::FromStr;
+/// use bitris::prelude::*;
+/// use bitris_commands::prelude::*;
+/// use Input::*;
+///
+/// let kick_table = KickTable::srs_plus();
+/// let move_rules = MoveRules::new(&kick_table, AllowMove::Softdrop);
+/// let calculator = FinesseCalculator::new(&move_rules);
+///
+/// let board = Board64::from_str("
+///     ..........
+///     ..........
+///     ..........
+///     ..........
+/// ").unwrap();
+/// let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+///
+/// // Flat T on the left wall
+/// let target = Shape::T.with(Orientation::South).with(bl(0, 0));
+/// let inputs = calculator.calculate(clipped_board, Shape::T, target).unwrap();
+/// assert_eq!(inputs, vec![DasLeft, Rotate180, Harddrop]);
+///
+/// assert_eq!(calculator.replay(clipped_board, Shape::T, &inputs), Ok(target));
+/// assert!(calculator.verify(clipped_board, Shape::T, &[Rotate180, MoveLeft, MoveLeft, MoveLeft, MoveLeft, MoveLeft, Harddrop], target));
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Hash, Debug)]
+pub struct FinesseCalculator<'a, T: RotationSystem180> {
+    moves: InputLimitedMoves<'a, T>,
+    spawn_rule: SpawnRule,
+}
+
+impl<'a, T: RotationSystem180> FinesseCalculator<'a, T> {
+    /// Makes the calculator with the default spawn rule.
+    #[inline]
+    pub fn new(move_rules: &'a MoveRules<'a, T>) -> Self {
+        Self {
+            moves: InputLimitedMoves::new(move_rules, u32::MAX),
+            spawn_rule: SpawnRule::default(),
+        }
+    }
+
+    /// Sets the placements where pieces spawn. See `SpawnRule` for more details.
+    /// In 20G, the piece drops at spawn and after every input.
+    #[inline]
+    pub fn with_spawn_rule(mut self, spawn_rule: SpawnRule) -> Self {
+        self.spawn_rule = spawn_rule;
+        self.moves = self.moves.with_20g(spawn_rule.is_20g());
+        self
+    }
+
+    /// Returns the shortest inputs to place the shape on the target, ending with the harddrop.
+    /// Returns `None` if the target is not of the shape or cannot be reached.
+    pub fn calculate(&self, clipped_board: ClippedBoard, shape: Shape, target: BlPlacement) -> Option<Vec<Input>> {
+        if target.piece.shape != shape {
+            return None;
+        }
+
+        let spawn = self.spawn_rule.placement(shape, clipped_board.height());
+        let mut inputs = self.moves.find_inputs(clipped_board.board(), spawn, target)?;
+        inputs.push(Input::Harddrop);
+        Some(inputs)
+    }
+
+    /// Replays the inputs from the spawn of the shape, and returns the placement locked by the harddrop.
+    /// Like in games, the inputs blocked by the stack do nothing.
+    ///
+    /// Returns `Err()` if the inputs cannot be replayed. See `FinesseReplayError` for error patterns.
+    pub fn replay(&self, clipped_board: ClippedBoard, shape: Shape, inputs: &[Input]) -> Result<BlPlacement, FinesseReplayError> {
+        use FinesseReplayError::*;
+
+        let board = clipped_board.board();
+        let spawn = self.spawn_rule.placement(shape, clipped_board.height()).to_cc_placement();
+        if !spawn.is_in_free_space(&board) {
+            return Err(SpawnIsBlocked);
+        }
+
+        let mut current = self.moves.fall(&board, spawn);
+        for (index, &input) in inputs.iter().enumerate() {
+            if !self.moves.is_available(input) {
+                return Err(UnavailableInput(index));
+            }
+
+            if input == Input::Harddrop {
+                if index + 1 < inputs.len() {
+                    return Err(InputAfterHarddrop(index + 1));
+                }
+                return Ok(drop_to_bottom(&board, current).to_bl_placement());
+            }
+
+            if let Some(next) = self.moves.step(&board, current, input) {
+                current = next;
+            }
+        }
+
+        Err(MissingHarddrop)
+    }
+
+    /// Returns `true` if the inputs lock the shape on the placement with the same blocks as the target.
+    pub fn verify(&self, clipped_board: ClippedBoard, shape: Shape, inputs: &[Input], target: BlPlacement) -> bool {
+        self.replay(clipped_board, shape, inputs)
+            .is_ok_and(|placement| blocks_of(placement.to_cc_placement()) == blocks_of(target.to_cc_placement()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::{ClippedBoard, FinesseCalculator, FinesseReplayError, Input, KickTable};
+
+    #[test]
+    fn calculate_and_replay_all() {
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let kick_table = KickTable::srs_x();
+        let move_rules_180 = MoveRules::new(&kick_table, AllowMove::Softdrop);
+        let calculator_180 = FinesseCalculator::new(&move_rules_180);
+        let board = Board64::from_str("
+            ..........
+            XX....XX..
+            XXX..XXX.X
+            XXXX.XXXXX
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let calculator = FinesseCalculator::new(&move_rules);
+
+        for shape in Shape::all_iter() {
+            let spawn = shape.with(Orientation::North).with(bl(5, 8));
+            for target in move_rules.generate_minimized_moves(board, spawn) {
+                let inputs = calculator.calculate(clipped_board, shape, target).unwrap();
+                assert_eq!(inputs.last(), Some(&Input::Harddrop));
+                assert!(calculator.verify(clipped_board, shape, &inputs, target));
+
+                // The 180° rotation never makes it longer.
+                let inputs_180 = calculator_180.calculate(clipped_board, shape, target).unwrap();
+                assert!(inputs_180.len() <= inputs.len());
+            }
+        }
+    }
+
+    #[test]
+    fn replay_errors() {
+        use FinesseReplayError::*;
+        use Input::*;
+
+        let clipped_board = ClippedBoard::try_new(Board64::blank(), 4).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Harddrop);
+        let calculator = FinesseCalculator::new(&move_rules);
+
+        assert_eq!(calculator.replay(clipped_board, Shape::T, &[MoveLeft]), Err(MissingHarddrop));
+        assert_eq!(calculator.replay(clipped_board, Shape::T, &[Harddrop, MoveLeft]), Err(InputAfterHarddrop(1)));
+        assert_eq!(calculator.replay(clipped_board, Shape::T, &[Softdrop, Harddrop]), Err(UnavailableInput(0)));
+        assert_eq!(calculator.replay(clipped_board, Shape::T, &[RotateCw, Rotate180, Harddrop]), Err(UnavailableInput(1)));
+
+        // Blocked by the wall
+        let placement = calculator.replay(clipped_board, Shape::O, &[DasRight, MoveRight, Harddrop]);
+        assert_eq!(placement, Ok(Shape::O.with(Orientation::North).with(bl(8, 0))));
+
+        // Not reachable
+        assert_eq!(calculator.calculate(clipped_board, Shape::T, Shape::T.with(Orientation::North).with(bl(0, 1))), None);
+        assert_eq!(calculator.calculate(clipped_board, Shape::T, Shape::O.with(Orientation::North).with(bl(0, 0))), None);
+    }
+}
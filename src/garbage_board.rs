@@ -0,0 +1,221 @@
+use std::str::FromStr;
+
+use bitris::prelude::*;
+use thiserror::Error;
+
+/// A collection of errors that occur when parsing a garbage board.
+/// The line numbers start at 1.
+#[derive(Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum GarbageBoardParseError {
+    #[error("Line {0}: The row must have 10 cells.")]
+    InvalidRow(usize),
+    #[error("Line {0}: Unknown cell `{1}`.")]
+    UnknownCell(usize, char),
+    #[error("The board exceeds the upper limit. Up to 64 rows are supported.")]
+    BoardIsTooHigh,
+}
+
+/// A board in which the hole positions of some garbage rows are unknown.
+///
+/// Each unknown row has one hole in one of its candidate columns; the other candidates are filled.
+/// The holes of the rows are independent and equally likely, so the board has the product of the candidate counts as its variants.
+///
+/// In the text, `?` marks the candidates. The other cells are the same as `Board64`.
+/// ```
+/// use std::str::FromStr;
+/// use bitris::prelude::*;
+/// use bitris_commands::GarbageBoard;
+///
+/// let garbage_board = GarbageBoard::from_str("
+///     XXXXXX....
+///     ??????????
+///     XXXX?????X
+/// ").unwrap();
+/// assert_eq!(garbage_board.count_variants(), 50);
+///
+/// // The same board by the builder
+/// let board = Board64::from_str("
+///     XXXXXX....
+///     ..........
+///     XXXX.....X
+/// ").unwrap();
+/// let built = GarbageBoard::new(board)
+///     .with_unknown_hole(1, &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9])
+///     .with_unknown_hole(0, &[4, 5, 6, 7, 8]);
+/// assert_eq!(built, garbage_board);
+///
+/// let variants = garbage_board.variants();
+/// assert_eq!(variants[0], Board64::from_str("
+///     XXXXXX....
+///     .XXXXXXXXX
+///     XXXX.XXXXX
+/// ").unwrap());
+/// ```
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct GarbageBoard {
+    // The known blocks. The candidates are empty.
+    board: Board64,
+
+    // The rows and the bitmasks of their candidate columns, in ascending order of rows.
+    unknown_holes: Vec<(u32, u16)>,
+}
+
+impl GarbageBoard {
+    /// Makes the board without unknown rows.
+    #[inline]
+    pub fn new(board: Board64) -> Self {
+        Self { board, unknown_holes: Vec::new() }
+    }
+
+    /// Sets the row to have one hole in one of the columns. The cells of the columns are cleared on the known board.
+    /// If the row is already unknown, it's replaced.
+    /// Panics if the row is out of the board or a column is out of range.
+    pub fn with_unknown_hole(mut self, row: u32, columns: &[u32]) -> Self {
+        assert!(row < 64, "The row is out of the board: {row}");
+
+        let mut candidates = 0u16;
+        for &column in columns {
+            assert!(column < 10, "The column is out of range: {column}");
+            candidates |= 1 << column;
+            self.board.unset_at(xy(column as i32, row as i32));
+        }
+
+        self.unknown_holes.retain(|&(unknown_row, _)| unknown_row != row);
+        if candidates != 0 {
+            self.unknown_holes.push((row, candidates));
+            self.unknown_holes.sort();
+        }
+        self
+    }
+
+    /// Returns the known blocks. The candidates of the holes are empty.
+    #[inline]
+    pub fn known_board(&self) -> Board64 {
+        self.board
+    }
+
+    /// Returns the rows whose holes are unknown, in ascending order.
+    #[inline]
+    pub fn unknown_rows(&self) -> Vec<u32> {
+        self.unknown_holes.iter().map(|&(row, _)| row).collect()
+    }
+
+    /// Returns the count of the possible boards.
+    #[inline]
+    pub fn count_variants(&self) -> usize {
+        self.unknown_holes.iter()
+            .map(|&(_, candidates)| candidates.count_ones() as usize)
+            .product()
+    }
+
+    /// Returns all possible boards. Each of them is equally likely.
+    /// The order is deterministic: the holes of the lower rows change slower, and the holes are from the left.
+    pub fn variants(&self) -> Vec<Board64> {
+        let mut variants = vec![self.board];
+        for &(row, candidates) in &self.unknown_holes {
+            variants = variants.into_iter()
+                .flat_map(|board| {
+                    (0..10).filter(move |column| candidates & (1 << column) != 0)
+                        .map(move |hole| {
+                            let mut board = board;
+                            for column in (0..10).filter(|&column| column != hole && candidates & (1 << column) != 0) {
+                                board.set_at(xy(column, row as i32));
+                            }
+                            board
+                        })
+                })
+                .collect();
+        }
+        variants
+    }
+}
+
+impl From<Board64> for GarbageBoard {
+    #[inline]
+    fn from(board: Board64) -> Self {
+        Self::new(board)
+    }
+}
+
+impl FromStr for GarbageBoard {
+    type Err = GarbageBoardParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use GarbageBoardParseError::*;
+
+        let rows = s.lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty())
+            .collect::<Vec<_>>();
+        if 64 < rows.len() {
+            return Err(BoardIsTooHigh);
+        }
+
+        let mut board = Board64::blank();
+        let mut unknown_holes = Vec::new();
+        for (y, &(line_number, line)) in rows.iter().rev().enumerate() {
+            if line.chars().count() != 10 {
+                return Err(InvalidRow(line_number));
+            }
+
+            let mut candidates = 0u16;
+            for (x, cell) in line.chars().enumerate() {
+                match cell {
+                    'X' | '#' => board.set_at(xy(x as i32, y as i32)),
+                    '.' | '_' => {}
+                    '?' => candidates |= 1 << x,
+                    _ => return Err(UnknownCell(line_number, cell)),
+                }
+            }
+            if candidates != 0 {
+                unknown_holes.push((y as u32, candidates));
+            }
+        }
+
+        Ok(Self { board, unknown_holes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::{GarbageBoard, GarbageBoardParseError};
+
+    #[test]
+    fn variants() {
+        let garbage_board = GarbageBoard::from_str("
+            ??.???????
+            ???.......
+        ").unwrap();
+        assert_eq!(garbage_board.unknown_rows(), vec![0, 1]);
+        assert_eq!(garbage_board.count_variants(), 27);
+
+        let variants = garbage_board.variants();
+        assert_eq!(variants.len(), 27);
+        for variant in &variants {
+            assert_eq!(variant.count_blocks(), 2 + 8);
+            assert!(variant.is_free_at(xy(2, 1)));
+        }
+
+        let mut sorted = variants.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted.len(), 27);
+
+        // Without unknown rows
+        let garbage_board = GarbageBoard::from(Board64::blank());
+        assert_eq!(garbage_board.variants(), vec![Board64::blank()]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        use GarbageBoardParseError::*;
+
+        assert_eq!(GarbageBoard::from_str("\n??????????\n?????????"), Err(InvalidRow(3)));
+        assert_eq!(GarbageBoard::from_str("XXXXXXXXX!"), Err(UnknownCell(1, '!')));
+    }
+}
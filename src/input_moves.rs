@@ -0,0 +1,415 @@
+use std::collections::hash_map::Entry;
+use std::collections::VecDeque;
+
+use bitris::prelude::*;
+use fxhash::{FxHashMap, FxHashSet};
+
+use crate::{RotationSystem180, SpawnRule};
+
+/// A collection of inputs to move a piece.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Input {
+    MoveLeft,
+    MoveRight,
+
+    /// Moves to the left until blocked.
+    DasLeft,
+
+    /// Moves to the right until blocked.
+    DasRight,
+
+    RotateCw,
+    RotateCcw,
+
+    /// Available only if the rotation system defines the 180° kicks. See `RotationSystem180`.
+    Rotate180,
+
+    /// Drops to the bottom without locking. Available only if the move rules allow softdrop.
+    Softdrop,
+
+    /// Drops to the bottom and locks. `InputLimitedMoves` leaves it implicit at the end and does not count it.
+    Harddrop,
+}
+
+/// The generator of placements reachable within a limited count of inputs.
+///
+/// `AllowMove::Softdrop` accepts any reachable placement, however deep the path is.
+/// This limits each placement to `max_inputs` inputs before the harddrop, which is closer to what humans can do under speed.
+/// The positions are searched in breadth-first order, so the inputs found for each placement are the shortest.
+/// The 180° rotation is used if the rotation system defines it.
+///
+/// With `with_20g(true)`, the piece drops to the bottom at spawn and after every input, so it never moves in the air.
+/// ```
+/// use std::str::FromStr;
+/// use bitris::prelude::*;
+/// use bitris_commands::{Input, InputLimitedMoves};
+///
+/// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+/// let board = Board64::from_str("
+///     XXXXXXX...
+///     XXXXXXXX..
+/// ").unwrap();
+/// let spawn = Shape::O.with(Orientation::North).with(bl(4, 20));
+///
+/// // No inputs: harddrop at the spawn column only.
+/// let moves = InputLimitedMoves::new(&move_rules, 0).generate_minimized_moves(board, spawn);
+/// assert_eq!(moves.len(), 1);
+///
+/// // DAS to the right wall.
+/// let moves = InputLimitedMoves::new(&move_rules, 1).generate_with_inputs(board, spawn);
+/// let (_, inputs) = moves.iter()
+///     .find(|(placement, _)| *placement == Shape::O.with(Orientation::North).with(bl(8, 0)))
+///     .unwrap();
+/// assert_eq!(inputs, &vec![Input::DasRight]);
+///
+/// // In 20G, the piece drops at spawn, so it cannot get over the wall.
+/// let board = Board64::from_str("
+///     ..X.......
+///     ..X.......
+///     ..X.......
+/// ").unwrap();
+/// let moves = InputLimitedMoves::new(&move_rules, 1).generate_minimized_moves(board, spawn);
+/// assert!(moves.contains(&Shape::O.with(Orientation::North).with(bl(0, 0))));
+///
+/// let moves = InputLimitedMoves::new(&move_rules, 1).with_20g(true).generate_minimized_moves(board, spawn);
+/// assert!(moves.contains(&Shape::O.with(Orientation::North).with(bl(3, 0))));
+/// assert!(!moves.contains(&Shape::O.with(Orientation::North).with(bl(0, 0))));
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Hash, Debug)]
+pub struct InputLimitedMoves<'a, T: RotationSystem180> {
+    move_rules: &'a MoveRules<'a, T>,
+    max_inputs: u32,
+    is_20g: bool,
+}
+
+impl<'a, T: RotationSystem180> InputLimitedMoves<'a, T> {
+    #[inline]
+    pub fn new(move_rules: &'a MoveRules<'a, T>, max_inputs: u32) -> Self {
+        Self { move_rules, max_inputs, is_20g: false }
+    }
+
+    /// Sets whether the piece drops to the bottom at spawn and after every input. The default is `false`.
+    #[inline]
+    pub fn with_20g(mut self, enabled: bool) -> Self {
+        self.is_20g = enabled;
+        self
+    }
+
+    #[inline]
+    pub fn max_inputs(&self) -> u32 {
+        self.max_inputs
+    }
+
+    #[inline]
+    pub fn is_20g(&self) -> bool {
+        self.is_20g
+    }
+
+    /// Returns the position after the gravity, that is, dropped to the bottom in 20G and unchanged otherwise.
+    #[inline]
+    pub(crate) fn fall(&self, board: &Board64, placement: CcPlacement) -> CcPlacement {
+        if self.is_20g {
+            drop_to_bottom(board, placement)
+        } else {
+            placement
+        }
+    }
+
+    /// Returns the placements reachable within the limit. Placements with the same blocks are merged into one.
+    /// It can be used in place of `MoveRules::generate_minimized_moves()`.
+    pub fn generate_minimized_moves(&self, board: Board64, spawn: BlPlacement) -> Vec<BlPlacement> {
+        self.search(board, spawn).into_iter()
+            .map(|(placement, _)| placement)
+            .collect()
+    }
+
+    /// Returns the placements reachable within the limit with the shortest inputs for each.
+    /// Placements with the same blocks are merged into one.
+    pub fn generate_with_inputs(&self, board: Board64, spawn: BlPlacement) -> Vec<(BlPlacement, Vec<Input>)> {
+        self.search(board, spawn)
+    }
+
+    /// Returns the shortest inputs to the placement with the same blocks as the target, excluding the harddrop.
+    pub(crate) fn find_inputs(&self, board: Board64, spawn: BlPlacement, target: BlPlacement) -> Option<Vec<Input>> {
+        let target = blocks_of(target.to_cc_placement());
+        self.search_until(board, spawn, |blocks| *blocks == target).pop()
+            .filter(|(placement, _)| blocks_of(placement.to_cc_placement()) == target)
+            .map(|(_, inputs)| inputs)
+    }
+
+    #[inline]
+    fn search(&self, board: Board64, spawn: BlPlacement) -> Vec<(BlPlacement, Vec<Input>)> {
+        self.search_until(board, spawn, |_| false)
+    }
+
+    /// Searches the placements until one of them satisfies `stops`.
+    fn search_until(&self, board: Board64, spawn: BlPlacement, stops: impl Fn(&Board64) -> bool) -> Vec<(BlPlacement, Vec<Input>)> {
+        let spawn = spawn.to_cc_placement();
+        if !spawn.is_in_free_space(&board) {
+            return Vec::new();
+        }
+        let spawn = self.fall(&board, spawn);
+
+        // The previous position and the input for each visited position.
+        let mut parents = FxHashMap::<CcPlacement, Option<(CcPlacement, Input)>>::default();
+        let mut queue = VecDeque::new();
+        parents.insert(spawn, None);
+        queue.push_back((spawn, 0));
+
+        let mut landed_blocks = FxHashSet::<Board64>::default();
+        let mut results = Vec::new();
+
+        while let Some((current, depth)) = queue.pop_front() {
+            let landed = drop_to_bottom(&board, current);
+            let blocks = blocks_of(landed);
+            if landed_blocks.insert(blocks) {
+                results.push((landed.to_bl_placement(), trace_inputs(&parents, current)));
+                if stops(&blocks) {
+                    break;
+                }
+            }
+
+            if self.max_inputs <= depth {
+                continue;
+            }
+
+            for (input, next) in self.nexts(&board, current) {
+                if let Entry::Vacant(entry) = parents.entry(next) {
+                    entry.insert(Some((current, input)));
+                    queue.push_back((next, depth + 1));
+                }
+            }
+        }
+
+        results
+    }
+
+    /// Returns the positions after each input. Inputs that do not move the piece are excluded.
+    fn nexts(&self, board: &Board64, current: CcPlacement) -> Vec<(Input, CcPlacement)> {
+        use Input::*;
+
+        let mut nexts = Vec::with_capacity(8);
+        for input in [MoveLeft, DasLeft, MoveRight, DasRight, RotateCw, RotateCcw, Rotate180, Softdrop] {
+            let Some(next) = self.step(board, current, input) else {
+                continue;
+            };
+
+            // DAS is the same as the tap if blocked after one column.
+            if matches!(input, DasLeft | DasRight) && nexts.last().is_some_and(|&(_, tapped)| tapped == next) {
+                continue;
+            }
+            nexts.push((input, next));
+        }
+        nexts
+    }
+
+    /// Returns the position after the input.
+    /// Returns `None` if the input is not available, or the piece does not move.
+    pub(crate) fn step(&self, board: &Board64, current: CcPlacement, input: Input) -> Option<CcPlacement> {
+        let shift = |placement: CcPlacement, dx: i32| CcPlacement::new(placement.piece, cc(placement.position.cx + dx, placement.position.cy));
+        let das = |dx: i32| {
+            let mut end = current;
+            while shift(end, dx).is_in_free_space(board) {
+                end = self.fall(board, shift(end, dx));
+            }
+            end
+        };
+        let kick = |piece: Piece, kicks: &[Kick]| {
+            kicks.iter()
+                .map(|kick| CcPlacement::new(piece, cc(
+                    current.position.cx + kick.offset.dx,
+                    current.position.cy + kick.offset.dy,
+                )))
+                .find(|placement| placement.is_in_free_space(board))
+        };
+        let rotates = self.move_rules.rotation_system.is_moving_in_rotation(current.piece.shape);
+
+        let next = match input {
+            Input::MoveLeft => Some(shift(current, -1)).filter(|next| next.is_in_free_space(board)),
+            Input::MoveRight => Some(shift(current, 1)).filter(|next| next.is_in_free_space(board)),
+            Input::DasLeft => Some(das(-1)),
+            Input::DasRight => Some(das(1)),
+            Input::RotateCw | Input::RotateCcw if rotates => {
+                let rotation = if input == Input::RotateCw { Rotation::Cw } else { Rotation::Ccw };
+                let kicks = self.move_rules.rotation_system.iter_kicks(current.piece, rotation).as_slice();
+                kick(current.piece.rotate(rotation), kicks)
+            }
+            Input::Rotate180 if rotates => {
+                let kicks = self.move_rules.rotation_system.iter_kicks_180(current.piece).as_slice();
+                kick(current.piece.rotate(Rotation::Cw).rotate(Rotation::Cw), kicks)
+            }
+            Input::Softdrop if self.move_rules.allow_move == AllowMove::Softdrop => Some(drop_to_bottom(board, current)),
+            Input::Harddrop => Some(drop_to_bottom(board, current)),
+            _ => None,
+        };
+        next.map(|next| self.fall(board, next))
+            .filter(|&next| next != current)
+    }
+
+    /// Returns `true` if the input can be used under the settings.
+    pub(crate) fn is_available(&self, input: Input) -> bool {
+        match input {
+            Input::Rotate180 => self.move_rules.rotation_system.has_kicks_180(),
+            Input::Softdrop => self.move_rules.allow_move == AllowMove::Softdrop,
+            _ => true,
+        }
+    }
+}
+
+/// Returns the placements for the executors, in place of `MoveRules::generate_minimized_moves()`.
+/// The moves are limited if `max_inputs` is set, the 180° rotation is used if the rotation system defines it,
+/// and the piece drops after every input if the spawn rule is 20G.
+pub(crate) fn generate_moves<T: RotationSystem180>(move_rules: &MoveRules<T>, max_inputs: Option<u32>, spawn_rule: &SpawnRule, board: Board64, spawn: BlPlacement) -> Vec<BlPlacement> {
+    let is_20g = spawn_rule.is_20g();
+    match max_inputs {
+        Some(max_inputs) => InputLimitedMoves::new(move_rules, max_inputs).with_20g(is_20g).generate_minimized_moves(board, spawn),
+        // The moves of bitris neither rotate 180° nor drop in 20G, so searches by inputs without the limit.
+        None if is_20g || move_rules.rotation_system.has_kicks_180() => {
+            InputLimitedMoves::new(move_rules, u32::MAX).with_20g(is_20g).generate_minimized_moves(board, spawn)
+        }
+        None => move_rules.generate_minimized_moves(board, spawn),
+    }
+}
+
+#[inline]
+pub(crate) fn drop_to_bottom(board: &Board64, placement: CcPlacement) -> CcPlacement {
+    let mut current = placement;
+    loop {
+        let below = CcPlacement::new(current.piece, cc(current.position.cx, current.position.cy - 1));
+        if !below.is_in_free_space(board) {
+            return current;
+        }
+        current = below;
+    }
+}
+
+/// Returns the blocks of the placement as a board, to identify the placements with the same blocks.
+#[inline]
+pub(crate) fn blocks_of(placement: CcPlacement) -> Board64 {
+    let mut board = Board64::blank();
+    placement.to_bl_placement().place_on_and_clear_lines(&mut board).unwrap();
+    board
+}
+
+fn trace_inputs(parents: &FxHashMap<CcPlacement, Option<(CcPlacement, Input)>>, last: CcPlacement) -> Vec<Input> {
+    let mut inputs = Vec::new();
+    let mut current = last;
+    while let Some(&Some((parent, input))) = parents.get(&current) {
+        inputs.push(input);
+        current = parent;
+    }
+    inputs.reverse();
+    inputs
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::input_moves::generate_moves;
+    use crate::{Input, InputLimitedMoves, KickTable, SpawnRule};
+
+    #[test]
+    fn converges_to_all_moves() {
+        let board = Board64::from_str("
+            ....X.....
+            XX..XX...X
+            XXX.XXX.XX
+            XXX.XXXXXX
+        ").unwrap();
+
+        for allow_move in [AllowMove::Softdrop, AllowMove::Harddrop] {
+            let move_rules = MoveRules::srs(allow_move);
+            for shape in Shape::all_iter() {
+                let spawn = shape.with(Orientation::North).with(bl(4, 20));
+                let expected = move_rules.generate_minimized_moves(board, spawn).len();
+
+                let sizes = (0..12)
+                    .map(|max_inputs| InputLimitedMoves::new(&move_rules, max_inputs).generate_minimized_moves(board, spawn).len())
+                    .collect::<Vec<_>>();
+                assert!(sizes.windows(2).all(|pair| pair[0] <= pair[1]));
+                assert_eq!(*sizes.last().unwrap(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn shortest_inputs() {
+        use Input::*;
+
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let board = Board64::from_str("
+            XXXXXXXX..
+            XXXXXX....
+            XXXXXXXX.X
+        ").unwrap();
+        let spawn = Shape::T.with(Orientation::North).with(bl(4, 20));
+
+        let moves = InputLimitedMoves::new(&move_rules, 5).generate_with_inputs(board, spawn);
+        let tss = Shape::T.with(Orientation::South).with(cc(8, 1)).to_bl_placement();
+        let (_, inputs) = moves.iter().find(|(placement, _)| *placement == tss).unwrap();
+        assert_eq!(inputs.len(), 4);
+        assert!(inputs.contains(&Softdrop));
+        assert!(matches!(inputs.last(), Some(RotateCw | RotateCcw)));
+
+        // The spin needs four inputs.
+        let moves = InputLimitedMoves::new(&move_rules, 3).generate_minimized_moves(board, spawn);
+        assert!(!moves.contains(&tss));
+
+        // Harddrop only.
+        let moves = InputLimitedMoves::new(&MoveRules::srs(AllowMove::Harddrop), 5).generate_minimized_moves(board, spawn);
+        assert!(!moves.contains(&tss));
+    }
+
+    #[test]
+    fn rotate_180() {
+        let board = Board64::from_str("
+            XXX..X.XXX
+            .X......XX
+            .XXXXX..XX
+        ").unwrap();
+        let spawn = Shape::T.with(Orientation::North).with(bl(4, 20));
+        let target = Shape::T.with(Orientation::West).with(cc(6, 1)).to_bl_placement();
+
+        // Reachable only by the 180° rotation.
+        let srs_x = KickTable::srs_x();
+        let move_rules = MoveRules::new(&srs_x, AllowMove::Softdrop);
+        assert!(generate_moves(&move_rules, None, &SpawnRule::default(), board, spawn).contains(&target));
+        assert!(!generate_moves(&MoveRules::srs(AllowMove::Softdrop), None, &SpawnRule::default(), board, spawn).contains(&target));
+
+        let moves = InputLimitedMoves::new(&move_rules, u32::MAX).generate_with_inputs(board, spawn);
+        let (_, inputs) = moves.iter().find(|(placement, _)| *placement == target).unwrap();
+        assert!(inputs.contains(&Input::Rotate180));
+    }
+
+    #[test]
+    fn moves_in_20g() {
+        use Input::*;
+
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let board = Board64::from_str("
+            XXXX......
+            XXXX.....X
+            XXXX.XXXXX
+            XXXX.XXXXX
+        ").unwrap();
+        let spawn = Shape::I.with(Orientation::North).with(bl(4, 20));
+        let spawn_rule = SpawnRule::default().with_20g(true);
+        let moves = generate_moves(&move_rules, None, &spawn_rule, board, spawn);
+
+        // Never in the air.
+        assert!(moves.iter().all(|placement| {
+            let below = placement.piece.with(bl(placement.position.lx, placement.position.by - 1));
+            !below.is_in_free_space(&board)
+        }));
+
+        // The vertical I rotates on the stack, and falls into the well when it slides over it.
+        assert!(moves.contains(&Shape::I.with(Orientation::East).with(bl(4, 0))));
+
+        // The softdrop does nothing since the piece is always on the bottom.
+        let moves = InputLimitedMoves::new(&move_rules, u32::MAX).with_20g(true).generate_with_inputs(board, spawn);
+        assert!(moves.iter().all(|(_, inputs)| !inputs.contains(&Softdrop)));
+    }
+}
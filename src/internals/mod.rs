@@ -1,5 +1,7 @@
 pub(crate) use fuzzy_shape::*;
 pub(crate) use fuzzy_shape_order::*;
+pub(crate) use sequence_table::*;
 
 mod fuzzy_shape;
 mod fuzzy_shape_order;
+mod sequence_table;
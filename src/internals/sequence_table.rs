@@ -0,0 +1,174 @@
+use fxhash::FxHashMap;
+
+use crate::ShapeSequence;
+
+const NONE: usize = usize::MAX;
+
+/// Holds a value for each distinct sequence, in the order they are added.
+///
+/// Each sequence is owned only once. The map holds the first index for each hash of the sequences,
+/// and the indices with the same hash are chained, so the collisions are resolved by comparing the owned sequences.
+///
+/// The equality compares the values by sequence, so it does not depend on the order added.
+#[derive(Clone, Debug)]
+pub(crate) struct SequenceTable<V> {
+    sequences: Vec<ShapeSequence>,
+    values: Vec<V>,
+    heads: FxHashMap<u64, usize>,
+    next_same_hash: Vec<usize>,
+}
+
+impl<V> Default for SequenceTable<V> {
+    fn default() -> Self {
+        Self { sequences: Vec::new(), values: Vec::new(), heads: FxHashMap::default(), next_same_hash: Vec::new() }
+    }
+}
+
+impl<V: PartialEq> PartialEq for SequenceTable<V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self.iter().all(|(sequence, value)| other.get(sequence) == Some(value))
+    }
+}
+
+impl<V: Eq> Eq for SequenceTable<V> {}
+
+impl<V> SequenceTable<V> {
+    /// Makes the table of the sequences without duplicates, whose values are made by the function.
+    pub fn new(sequences: impl IntoIterator<Item=ShapeSequence>, mut value: impl FnMut() -> V) -> Self {
+        let sequences = sequences.into_iter();
+        let mut table = Self::default();
+        table.reserve(sequences.size_hint().0);
+        for sequence in sequences {
+            table.insert_if_absent(sequence, &mut value);
+        }
+        table
+    }
+
+    #[inline]
+    fn reserve(&mut self, additional: usize) {
+        self.sequences.reserve(additional);
+        self.values.reserve(additional);
+        self.heads.reserve(additional);
+        self.next_same_hash.reserve(additional);
+    }
+
+    /// Adds the sequence if it's not present. Returns the index of the sequence.
+    pub fn insert_if_absent(&mut self, sequence: ShapeSequence, value: impl FnOnce() -> V) -> usize {
+        let hash = fxhash::hash64(&sequence);
+        if let Some(index) = self.find(hash, &sequence) {
+            return index;
+        }
+
+        let index = self.sequences.len();
+        let head = self.heads.insert(hash, index).unwrap_or(NONE);
+        self.next_same_hash.push(head);
+        self.sequences.push(sequence);
+        self.values.push(value());
+        index
+    }
+
+    fn find(&self, hash: u64, sequence: &ShapeSequence) -> Option<usize> {
+        let mut index = *self.heads.get(&hash)?;
+        while index != NONE {
+            if &self.sequences[index] == sequence {
+                return Some(index);
+            }
+            index = self.next_same_hash[index];
+        }
+        None
+    }
+
+    /// Returns the index of the sequence in the order added.
+    #[inline]
+    pub fn index_of(&self, sequence: &ShapeSequence) -> Option<usize> {
+        self.find(fxhash::hash64(sequence), sequence)
+    }
+
+    #[inline]
+    pub fn contains_key(&self, sequence: &ShapeSequence) -> bool {
+        self.index_of(sequence).is_some()
+    }
+
+    #[inline]
+    pub fn get(&self, sequence: &ShapeSequence) -> Option<&V> {
+        self.index_of(sequence).map(|index| &self.values[index])
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, sequence: &ShapeSequence) -> Option<&mut V> {
+        self.index_of(sequence).map(|index| &mut self.values[index])
+    }
+
+    #[inline]
+    pub fn sequences(&self) -> &[ShapeSequence] {
+        self.sequences.as_slice()
+    }
+
+    #[inline]
+    pub fn values(&self) -> &[V] {
+        self.values.as_slice()
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item=(&ShapeSequence, &V)> + '_ {
+        self.sequences.iter().zip(self.values.iter())
+    }
+
+    #[inline]
+    pub fn into_iter(self) -> impl Iterator<Item=(ShapeSequence, V)> {
+        self.sequences.into_iter().zip(self.values)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.sequences.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitris::prelude::Shape;
+
+    use crate::internals::SequenceTable;
+    use crate::ShapeSequence;
+
+    #[test]
+    fn dedup_in_order() {
+        use Shape::*;
+
+        let sequences = vec![
+            ShapeSequence::new(vec![T, I]),
+            ShapeSequence::new(vec![O, S]),
+            ShapeSequence::new(vec![T, I]),
+        ];
+        let mut table = SequenceTable::new(sequences, || 0);
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.sequences(), &[ShapeSequence::new(vec![T, I]), ShapeSequence::new(vec![O, S])]);
+
+        *table.get_mut(&ShapeSequence::new(vec![O, S])).unwrap() = 1;
+        assert_eq!(table.get(&ShapeSequence::new(vec![O, S])), Some(&1));
+        assert_eq!(table.index_of(&ShapeSequence::new(vec![T, I])), Some(0));
+        assert!(!table.contains_key(&ShapeSequence::new(vec![I, T])));
+
+        assert_eq!(table.insert_if_absent(ShapeSequence::new(vec![I, T]), || 2), 2);
+        assert_eq!(table.insert_if_absent(ShapeSequence::new(vec![I, T]), || 3), 2);
+        assert_eq!(table.values(), &[0, 1, 2]);
+    }
+
+    #[test]
+    fn eq_in_any_order() {
+        use Shape::*;
+
+        let table = SequenceTable::new(vec![ShapeSequence::new(vec![T, I]), ShapeSequence::new(vec![O, S])], || 0);
+        let mut reversed = SequenceTable::new(vec![ShapeSequence::new(vec![O, S]), ShapeSequence::new(vec![T, I])], || 0);
+        assert_eq!(table, reversed);
+
+        *reversed.get_mut(&ShapeSequence::new(vec![O, S])).unwrap() = 1;
+        assert_ne!(table, reversed);
+
+        let longer = SequenceTable::new(vec![ShapeSequence::new(vec![T, I]), ShapeSequence::new(vec![O, S]), ShapeSequence::new(vec![I, T])], || 0);
+        assert_ne!(table, longer);
+        assert_ne!(longer, table);
+    }
+}
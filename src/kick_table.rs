@@ -0,0 +1,785 @@
+use std::slice::Iter;
+use std::str::FromStr;
+
+use bitris::prelude::*;
+use bitris::srs::SrsKickTable;
+use thiserror::Error;
+
+use crate::Mirror;
+
+/// A collection of errors that occur when parsing a kick table.
+/// The line numbers start at 1.
+#[derive(Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum KickTableParseError {
+    #[error("Line {0}: The syntax is invalid.")]
+    InvalidSyntax(usize),
+    #[error("Line {0}: Unknown shape `{1}`.")]
+    UnknownShape(usize, String),
+    #[error("Line {0}: Invalid orientation transition `{1}`.")]
+    InvalidTransition(usize, String),
+    #[error("Line {0}: Invalid kick `{1}`.")]
+    InvalidKick(usize, String),
+    #[error("Line {0}: The kicks are defined before a section.")]
+    NoSection(usize),
+    #[error("Line {0}: The kicks are already defined.")]
+    DuplicateKicks(usize),
+    #[error("The kicks of {0:?} from {1:?} by {2:?} are not defined.")]
+    MissingKicks(Shape, Orientation, Rotation),
+}
+
+/// A rotation system built from a kick table.
+///
+/// The kick table is described in a TOML-like text:
+///   + A section `[...]` lists the shapes that the following kicks apply to.
+///   + `A->B = (x, y), ...` lists the kicks in order of priority, for the rotation from orientation `A` to `B` (`N`, `E`, `S` or `W`).
+///     The offsets are in the coordinates of bitris, rotating around the center of pieces, so the first kick may not be `(0, 0)`.
+///   + `rotates = false` marks the shapes that do not move in rotation, like O.
+///   + Lines starting with `#` are comments.
+///
+/// The kicks for clockwise and counterclockwise rotations are required for all shapes.
+/// The kicks for 180° rotations (e.g. `N->S`) are optional.
+/// If they are defined, the executors generate the moves with the 180° rotation. See `RotationSystem180` for more details.
+/// ```
+/// use std::rc::Rc;
+/// use bitris::prelude::*;
+/// use bitris_commands::prelude::*;
+/// use bitris_commands::pc_possible::PcPossibleExecutorBinder;
+///
+/// let srs_plus = KickTable::srs_plus();
+/// assert!(srs_plus.has_kicks_180());
+///
+/// // I piece kicks are different from SRS.
+/// let srs = KickTable::srs();
+/// let piece = Shape::I.with(Orientation::North);
+/// assert_ne!(srs.iter_kicks(piece, Rotation::Cw).as_slice(), srs_plus.iter_kicks(piece, Rotation::Cw).as_slice());
+///
+/// let mut binder = PcPossibleExecutorBinder::default(Rc::new(srs_plus));
+/// binder.shape_order = Rc::new(ShapeOrder::new(vec![Shape::I, Shape::I, Shape::I, Shape::I, Shape::I, Shape::I, Shape::I, Shape::I, Shape::I, Shape::I]));
+/// assert_eq!(binder.try_execute(), Ok(true));
+/// ```
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct KickTable {
+    // Indexed by `shape * 8 + orientation * 2 + rotation`.
+    kicks: Vec<Vec<Kick>>,
+
+    // Indexed by `shape * 4 + orientation`. Empty if not defined.
+    kicks_180: Vec<Vec<Kick>>,
+
+    rotates: [bool; 7],
+}
+
+impl KickTable {
+    /// The kick table of SRS. It's the same as `SrsKickTable`.
+    pub fn srs() -> Self {
+        SRS_KICK_TABLE.parse().expect("The built-in kick table is broken")
+    }
+
+    /// The kick table of SRS+ in TETR.IO.
+    /// Only the I piece kicks differ from SRS, and the 180° kicks are defined.
+    pub fn srs_plus() -> Self {
+        SRS_PLUS_KICK_TABLE.parse().expect("The built-in kick table is broken")
+    }
+
+    /// The kick table of SRS-X in Heboris U.E., as described in Tetris Wiki.
+    /// It's SRS with the 180° kicks, which try the horizontal shifts first.
+    pub fn srs_x() -> Self {
+        SRS_X_KICK_TABLE.parse().expect("The built-in kick table is broken")
+    }
+
+    /// The kick table of the standard rule in NullpoMino.
+    /// It's SRS with the 180° kicks of `StandardWallkick`, which are defined with the y-axis pointing down.
+    pub fn nullpomino() -> Self {
+        NULLPOMINO_KICK_TABLE.parse().expect("The built-in kick table is broken")
+    }
+
+}
+
+/// A rotation system that may define the 180° rotation, which the moves of bitris do not support.
+///
+/// The executors generate the moves with the 180° rotation if the kicks are defined.
+/// Such moves are searched by inputs (see `InputLimitedMoves`), so it's slower than the moves of bitris.
+///
+/// All methods have defaults without the 180° rotation, so a custom `RotationSystem` needs only an empty impl:
+/// ```
+/// use std::slice::Iter;
+/// use bitris::prelude::*;
+/// use bitris_commands::prelude::*;
+///
+/// struct MyKickTable;
+///
+/// impl RotationSystem for MyKickTable {
+///     fn iter_kicks(&self, _: Piece, _: Rotation) -> Iter<'_, Kick> {
+///         [].iter()
+///     }
+///
+///     fn is_moving_in_rotation(&self, _: Shape) -> bool {
+///         false
+///     }
+/// }
+///
+/// impl RotationSystem180 for MyKickTable {}
+///
+/// assert!(!MyKickTable.has_kicks_180());
+/// ```
+/// If the impl cannot be added (e.g. the type is defined in another crate), wrap it in `No180Kicks`.
+pub trait RotationSystem180: RotationSystem {
+    /// Returns the kicks for the 180° rotation. Returns an empty iterator if not defined.
+    #[inline]
+    fn iter_kicks_180(&self, _piece: Piece) -> Iter<'_, Kick> {
+        [].iter()
+    }
+
+    /// Returns `true` if the 180° kicks are defined for any shape.
+    fn has_kicks_180(&self) -> bool {
+        Shape::all_iter().any(|shape| {
+            Orientation::all_iter().any(|orientation| self.iter_kicks_180(shape.with(orientation)).len() != 0)
+        })
+    }
+}
+
+impl RotationSystem180 for SrsKickTable {
+    #[inline]
+    fn has_kicks_180(&self) -> bool {
+        false
+    }
+}
+
+/// Wraps a rotation system to use it without the 180° rotation.
+/// ```
+/// use bitris::prelude::*;
+/// use bitris::srs::SrsKickTable;
+/// use bitris_commands::prelude::*;
+///
+/// let rotation_system = No180Kicks(SrsKickTable);
+/// let piece = Shape::T.with(Orientation::North);
+/// assert_eq!(rotation_system.iter_kicks(piece, Rotation::Cw).as_slice(), SrsKickTable.iter_kicks(piece, Rotation::Cw).as_slice());
+/// assert!(!rotation_system.has_kicks_180());
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct No180Kicks<T: RotationSystem>(pub T);
+
+impl<T: RotationSystem> RotationSystem for No180Kicks<T> {
+    #[inline]
+    fn iter_kicks(&self, piece: Piece, rotation: Rotation) -> Iter<'_, Kick> {
+        self.0.iter_kicks(piece, rotation)
+    }
+
+    #[inline]
+    fn is_moving_in_rotation(&self, shape: Shape) -> bool {
+        self.0.is_moving_in_rotation(shape)
+    }
+}
+
+impl<T: RotationSystem> RotationSystem180 for No180Kicks<T> {
+    #[inline]
+    fn has_kicks_180(&self) -> bool {
+        false
+    }
+}
+
+impl RotationSystem180 for KickTable {
+    #[inline]
+    fn iter_kicks_180(&self, piece: Piece) -> Iter<'_, Kick> {
+        self.kicks_180[piece.shape as usize * 4 + piece.orientation as usize].iter()
+    }
+
+    #[inline]
+    fn has_kicks_180(&self) -> bool {
+        self.kicks_180.iter().any(|kicks| !kicks.is_empty())
+    }
+}
+
+/// Returns `true` if the rotation system moves pieces mirror-symmetrically. That is, on the mirrored board,
+/// the mirrored piece (L↔J, S↔Z and East↔West) rotated the other way tries the mirrored kicks in the same order.
+///
+/// SRS is not symmetric because of the order of I kicks, and SRS+ is not because of the 180° kicks.
+pub(crate) fn is_mirror_symmetric(rotation_system: &impl RotationSystem180) -> bool {
+    let mirror_kicks = |from: Piece, to: Piece, kicks: &[Kick]| {
+        let (from_dx, from_dy) = mirror_shift(from);
+        let (to_dx, to_dy) = mirror_shift(to);
+        kicks.iter()
+            .map(|kick| Kick::new(dd(to_dx - from_dx - kick.offset.dx, to_dy - from_dy + kick.offset.dy)))
+            .collect::<Vec<_>>()
+    };
+
+    Shape::all_iter().all(|shape| {
+        rotation_system.is_moving_in_rotation(shape) == rotation_system.is_moving_in_rotation(shape.mirror())
+            && Orientation::all_iter().all(|orientation| {
+                let piece = shape.with(orientation);
+                let mirrored = mirror_piece(piece);
+                let cw = mirror_kicks(piece, piece.rotate(Rotation::Cw), rotation_system.iter_kicks(piece, Rotation::Cw).as_slice());
+                let ccw = mirror_kicks(piece, piece.rotate(Rotation::Ccw), rotation_system.iter_kicks(piece, Rotation::Ccw).as_slice());
+                let half = mirror_kicks(piece, piece.rotate(Rotation::Cw).rotate(Rotation::Cw), rotation_system.iter_kicks_180(piece).as_slice());
+                rotation_system.iter_kicks(mirrored, Rotation::Ccw).as_slice() == cw.as_slice()
+                    && rotation_system.iter_kicks(mirrored, Rotation::Cw).as_slice() == ccw.as_slice()
+                    && rotation_system.iter_kicks_180(mirrored).as_slice() == half.as_slice()
+            })
+    })
+}
+
+#[inline]
+fn mirror_piece(piece: Piece) -> Piece {
+    let orientation = match piece.orientation {
+        Orientation::East => Orientation::West,
+        Orientation::West => Orientation::East,
+        orientation => orientation,
+    };
+    piece.shape.mirror().with(orientation)
+}
+
+/// Returns the offset from the mirrored center of the piece to the center of the mirrored piece.
+/// The rotation centers of bitris are not always on the axis of the mirror.
+fn mirror_shift(piece: Piece) -> (i32, i32) {
+    let offsets = |piece: Piece| {
+        CcPlacement::new(piece, cc(4, 4)).to_bl_placement().locations()
+            .map(|location| (location.x - 4, location.y - 4))
+    };
+    let min = |offsets: [(i32, i32); 4]| {
+        (offsets.iter().map(|&(x, _)| x).min().unwrap(), offsets.iter().map(|&(_, y)| y).min().unwrap())
+    };
+
+    let (x, y) = min(offsets(piece).map(|(x, y)| (-x, y)));
+    let (mirrored_x, mirrored_y) = min(offsets(mirror_piece(piece)));
+    (x - mirrored_x, y - mirrored_y)
+}
+
+impl RotationSystem for KickTable {
+    #[inline]
+    fn iter_kicks(&self, piece: Piece, rotation: Rotation) -> Iter<'_, Kick> {
+        self.kicks[piece.shape as usize * 8 + piece.orientation as usize * 2 + rotation as usize].iter()
+    }
+
+    #[inline]
+    fn is_moving_in_rotation(&self, shape: Shape) -> bool {
+        self.rotates[shape as usize]
+    }
+}
+
+impl FromStr for KickTable {
+    type Err = KickTableParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use KickTableParseError::*;
+
+        let mut kicks: Vec<Option<Vec<Kick>>> = vec![None; 7 * 8];
+        let mut kicks_180: Vec<Option<Vec<Kick>>> = vec![None; 7 * 4];
+        let mut rotates = [true; 7];
+        let mut shapes: Option<Vec<Shape>> = None;
+
+        for (index, line) in s.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(section) = line.strip_prefix('[') {
+                let section = section.strip_suffix(']').ok_or(InvalidSyntax(line_number))?;
+                let parsed = section.split([',', ' '])
+                    .filter(|name| !name.is_empty())
+                    .map(|name| parse_shape(name).ok_or_else(|| UnknownShape(line_number, name.to_string())))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if parsed.is_empty() {
+                    return Err(InvalidSyntax(line_number));
+                }
+                shapes = Some(parsed);
+                continue;
+            }
+
+            let (key, value) = line.split_once('=').ok_or(InvalidSyntax(line_number))?;
+            let (key, value) = (key.trim(), value.trim());
+            let shapes = shapes.as_ref().ok_or(NoSection(line_number))?;
+
+            if key == "rotates" {
+                let value = match value {
+                    "true" => true,
+                    "false" => false,
+                    _ => return Err(InvalidSyntax(line_number)),
+                };
+                for &shape in shapes {
+                    rotates[shape as usize] = value;
+                }
+                continue;
+            }
+
+            let (from, to) = key.split_once("->")
+                .and_then(|(from, to)| Some((parse_orientation(from.trim())?, parse_orientation(to.trim())?)))
+                .ok_or_else(|| InvalidTransition(line_number, key.to_string()))?;
+            let parsed = parse_kicks(value).ok_or_else(|| InvalidKick(line_number, value.to_string()))?;
+
+            for &shape in shapes {
+                let slot = if to == from.rotate(Rotation::Cw) {
+                    &mut kicks[shape as usize * 8 + from as usize * 2 + Rotation::Cw as usize]
+                } else if to == from.rotate(Rotation::Ccw) {
+                    &mut kicks[shape as usize * 8 + from as usize * 2 + Rotation::Ccw as usize]
+                } else if to != from {
+                    &mut kicks_180[shape as usize * 4 + from as usize]
+                } else {
+                    return Err(InvalidTransition(line_number, key.to_string()));
+                };
+
+                if slot.is_some() {
+                    return Err(DuplicateKicks(line_number));
+                }
+                *slot = Some(parsed.clone());
+            }
+        }
+
+        let mut table = Vec::with_capacity(kicks.len());
+        for shape in Shape::all_iter() {
+            for orientation in Orientation::all_iter() {
+                for rotation in [Rotation::Cw, Rotation::Ccw] {
+                    let index = shape as usize * 8 + orientation as usize * 2 + rotation as usize;
+                    table.push(kicks[index].take().ok_or(MissingKicks(shape, orientation, rotation))?);
+                }
+            }
+        }
+
+        Ok(Self {
+            kicks: table,
+            kicks_180: kicks_180.into_iter().map(Option::unwrap_or_default).collect(),
+            rotates,
+        })
+    }
+}
+
+fn parse_shape(name: &str) -> Option<Shape> {
+    Shape::all_iter().find(|shape| format!("{shape:?}") == name)
+}
+
+fn parse_orientation(name: &str) -> Option<Orientation> {
+    match name {
+        "N" => Some(Orientation::North),
+        "E" => Some(Orientation::East),
+        "S" => Some(Orientation::South),
+        "W" => Some(Orientation::West),
+        _ => None,
+    }
+}
+
+/// Parses `(x, y), (x, y), ...`.
+fn parse_kicks(value: &str) -> Option<Vec<Kick>> {
+    let mut kicks = Vec::new();
+    let mut rest = value.trim();
+    while !rest.is_empty() {
+        let (pair, next) = rest.strip_prefix('(')?.split_once(')')?;
+        let (dx, dy) = pair.split_once(',')?;
+        kicks.push(Kick::new(dd(dx.trim().parse().ok()?, dy.trim().parse().ok()?)));
+
+        rest = next.trim_start();
+        if let Some(next) = rest.strip_prefix(',') {
+            rest = next.trim_start();
+            if rest.is_empty() {
+                return None;
+            }
+        }
+    }
+
+    if kicks.is_empty() { None } else { Some(kicks) }
+}
+
+/// The description of SRS. See `KickTable` for the format.
+pub const SRS_KICK_TABLE: &str = "
+[T J L S Z]
+N->E = (0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)
+E->N = (0, 0), (1, 0), (1, -1), (0, 2), (1, 2)
+E->S = (0, 0), (1, 0), (1, -1), (0, 2), (1, 2)
+S->E = (0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)
+S->W = (0, 0), (1, 0), (1, 1), (0, -2), (1, -2)
+W->S = (0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)
+W->N = (0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)
+N->W = (0, 0), (1, 0), (1, 1), (0, -2), (1, -2)
+
+[I]
+N->E = (1, 0), (-1, 0), (2, 0), (-1, -1), (2, 2)
+E->N = (-1, 0), (1, 0), (-2, 0), (1, 1), (-2, -2)
+E->S = (0, -1), (-1, -1), (2, -1), (-1, 1), (2, -2)
+S->E = (0, 1), (1, 1), (-2, 1), (1, -1), (-2, 2)
+S->W = (-1, 0), (1, 0), (-2, 0), (1, 1), (-2, -2)
+W->S = (1, 0), (-1, 0), (2, 0), (-1, -1), (2, 2)
+W->N = (0, 1), (1, 1), (-2, 1), (1, -1), (-2, 2)
+N->W = (0, -1), (-1, -1), (2, -1), (-1, 1), (2, -2)
+
+# The kicks only keep the same blocks.
+[O]
+rotates = false
+N->E = (0, 1)
+E->N = (0, -1)
+E->S = (1, 0)
+S->E = (-1, 0)
+S->W = (0, -1)
+W->S = (0, 1)
+W->N = (-1, 0)
+N->W = (1, 0)
+";
+
+/// The description of SRS+ in TETR.IO. See `KickTable` for the format.
+pub const SRS_PLUS_KICK_TABLE: &str = "
+[T J L S Z]
+N->E = (0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)
+E->N = (0, 0), (1, 0), (1, -1), (0, 2), (1, 2)
+E->S = (0, 0), (1, 0), (1, -1), (0, 2), (1, 2)
+S->E = (0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)
+S->W = (0, 0), (1, 0), (1, 1), (0, -2), (1, -2)
+W->S = (0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)
+W->N = (0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)
+N->W = (0, 0), (1, 0), (1, 1), (0, -2), (1, -2)
+N->S = (0, 0), (0, 1), (1, 1), (-1, 1), (1, 0), (-1, 0)
+S->N = (0, 0), (0, -1), (-1, -1), (1, -1), (-1, 0), (1, 0)
+E->W = (0, 0), (1, 0), (1, 2), (1, 1), (0, 2), (0, 1)
+W->E = (0, 0), (-1, 0), (-1, 2), (-1, 1), (0, 2), (0, 1)
+
+# The kicks are symmetric, unlike SRS. The 180° kicks are the same as the others.
+[I]
+N->E = (1, 0), (2, 0), (-1, 0), (-1, -1), (2, 2)
+E->N = (-1, 0), (-2, 0), (1, 0), (-2, -2), (1, 1)
+E->S = (0, -1), (-1, -1), (2, -1), (-1, 1), (2, -2)
+S->E = (0, 1), (-2, 1), (1, 1), (-2, 2), (1, -1)
+S->W = (-1, 0), (1, 0), (-2, 0), (1, 1), (-2, -2)
+W->S = (1, 0), (2, 0), (-1, 0), (2, -2), (-1, 1)
+W->N = (0, 1), (1, 1), (-2, 1), (1, 3), (-2, 0)
+N->W = (0, -1), (-1, -1), (2, -1), (2, -2), (-1, 1)
+N->S = (1, -1), (1, 0), (2, 0), (0, 0), (2, -1), (0, -1)
+S->N = (-1, 1), (-1, 0), (-2, 0), (0, 0), (-2, 1), (0, 1)
+E->W = (-1, -1), (0, -1), (0, 1), (0, 0), (-1, 1), (-1, 0)
+W->E = (1, 1), (0, 1), (0, 3), (0, 2), (1, 3), (1, 2)
+
+[O]
+rotates = false
+N->E = (0, 1)
+E->N = (0, -1)
+E->S = (1, 0)
+S->E = (-1, 0)
+S->W = (0, -1)
+W->S = (0, 1)
+W->N = (-1, 0)
+N->W = (1, 0)
+N->S = (1, 1)
+S->N = (-1, -1)
+E->W = (1, -1)
+W->E = (-1, 1)
+";
+
+/// SRS with the I kicks only at the rotation center, so that it is mirror-symmetric.
+#[cfg(test)]
+pub(crate) fn symmetric_kick_table() -> KickTable {
+    let (others, rest) = SRS_KICK_TABLE.split_once("[I]").unwrap();
+    let (_, o) = rest.split_once("[O]").unwrap();
+    let i = "
+        N->E = (1, 0)
+        E->N = (-1, 0)
+        E->S = (0, -1)
+        S->E = (0, 1)
+        S->W = (-1, 0)
+        W->S = (1, 0)
+        W->N = (0, 1)
+        N->W = (0, -1)
+    ";
+    format!("{others}[I]{i}[O]{o}").parse().unwrap()
+}
+
+/// The description of SRS-X in Heboris U.E. See `KickTable` for the format.
+pub const SRS_X_KICK_TABLE: &str = "
+[T J L S Z]
+N->E = (0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)
+E->N = (0, 0), (1, 0), (1, -1), (0, 2), (1, 2)
+E->S = (0, 0), (1, 0), (1, -1), (0, 2), (1, 2)
+S->E = (0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)
+S->W = (0, 0), (1, 0), (1, 1), (0, -2), (1, -2)
+W->S = (0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)
+W->N = (0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)
+N->W = (0, 0), (1, 0), (1, 1), (0, -2), (1, -2)
+N->S = (0, 0), (1, 0), (2, 0), (1, 1), (2, 1), (-1, 0), (-2, 0), (-1, 1), (-2, 1), (0, -1), (3, 0), (-3, 0)
+E->W = (0, 0), (0, 1), (0, 2), (-1, 1), (-1, 2), (0, -1), (0, -2), (-1, -1), (-1, -2), (1, 0), (0, 3), (0, -3)
+S->N = (0, 0), (-1, 0), (-2, 0), (-1, -1), (-2, -1), (1, 0), (2, 0), (1, -1), (2, -1), (0, 1), (-3, 0), (3, 0)
+W->E = (0, 0), (0, 1), (0, 2), (1, 1), (1, 2), (0, -1), (0, -2), (1, -1), (1, -2), (-1, 0), (0, 3), (0, -3)
+
+# The 180° kicks are shifted by the rotation center of bitris.
+[I]
+N->E = (1, 0), (-1, 0), (2, 0), (-1, -1), (2, 2)
+E->N = (-1, 0), (1, 0), (-2, 0), (1, 1), (-2, -2)
+E->S = (0, -1), (-1, -1), (2, -1), (-1, 1), (2, -2)
+S->E = (0, 1), (1, 1), (-2, 1), (1, -1), (-2, 2)
+S->W = (-1, 0), (1, 0), (-2, 0), (1, 1), (-2, -2)
+W->S = (1, 0), (-1, 0), (2, 0), (-1, -1), (2, 2)
+W->N = (0, 1), (1, 1), (-2, 1), (1, -1), (-2, 2)
+N->W = (0, -1), (-1, -1), (2, -1), (-1, 1), (2, -2)
+N->S = (1, -1), (0, -1), (-1, -1), (2, -1), (3, -1), (1, 0)
+S->N = (-1, 1), (0, 1), (1, 1), (-2, 1), (-3, 1), (-1, 0)
+E->W = (-1, -1), (-1, 0), (-1, 1), (-1, -2), (-1, -3), (-2, -1)
+W->E = (1, 1), (1, 2), (1, 3), (1, 0), (1, -1), (2, 1)
+
+[O]
+rotates = false
+N->E = (0, 1)
+E->N = (0, -1)
+E->S = (1, 0)
+S->E = (-1, 0)
+S->W = (0, -1)
+W->S = (0, 1)
+W->N = (-1, 0)
+N->W = (1, 0)
+N->S = (1, 1)
+S->N = (-1, -1)
+E->W = (1, -1)
+W->E = (-1, 1)
+";
+
+/// The description of the standard rule in NullpoMino. See `KickTable` for the format.
+pub const NULLPOMINO_KICK_TABLE: &str = "
+[T J L S Z]
+N->E = (0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)
+E->N = (0, 0), (1, 0), (1, -1), (0, 2), (1, 2)
+E->S = (0, 0), (1, 0), (1, -1), (0, 2), (1, 2)
+S->E = (0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)
+S->W = (0, 0), (1, 0), (1, 1), (0, -2), (1, -2)
+W->S = (0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)
+W->N = (0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)
+N->W = (0, 0), (1, 0), (1, 1), (0, -2), (1, -2)
+N->S = (0, 0), (1, 0), (2, 0), (1, -1), (2, -1), (-1, 0), (-2, 0), (-1, -1), (-2, -1), (0, 1), (3, 0), (-3, 0)
+E->W = (0, 0), (0, -1), (0, -2), (-1, -1), (-1, -2), (0, 1), (0, 2), (-1, 1), (-1, 2), (1, 0), (0, -3), (0, 3)
+S->N = (0, 0), (-1, 0), (-2, 0), (-1, 1), (-2, 1), (1, 0), (2, 0), (1, 1), (2, 1), (0, -1), (-3, 0), (3, 0)
+W->E = (0, 0), (0, -1), (0, -2), (1, -1), (1, -2), (0, 1), (0, 2), (1, 1), (1, 2), (-1, 0), (0, -3), (0, 3)
+
+# The 180° kicks are shifted by the rotation center of bitris.
+[I]
+N->E = (1, 0), (-1, 0), (2, 0), (-1, -1), (2, 2)
+E->N = (-1, 0), (1, 0), (-2, 0), (1, 1), (-2, -2)
+E->S = (0, -1), (-1, -1), (2, -1), (-1, 1), (2, -2)
+S->E = (0, 1), (1, 1), (-2, 1), (1, -1), (-2, 2)
+S->W = (-1, 0), (1, 0), (-2, 0), (1, 1), (-2, -2)
+W->S = (1, 0), (-1, 0), (2, 0), (-1, -1), (2, 2)
+W->N = (0, 1), (1, 1), (-2, 1), (1, -1), (-2, 2)
+N->W = (0, -1), (-1, -1), (2, -1), (-1, 1), (2, -2)
+N->S = (1, -1), (0, -1), (-1, -1), (2, -1), (3, -1), (1, -2)
+S->N = (-1, 1), (0, 1), (1, 1), (-2, 1), (-3, 1), (-1, 2)
+E->W = (-1, -1), (-1, -2), (-1, -3), (-1, 0), (-1, 1), (-2, -1)
+W->E = (1, 1), (1, 0), (1, -1), (1, 2), (1, 3), (2, 1)
+
+[O]
+rotates = false
+N->E = (0, 1)
+E->N = (0, -1)
+E->S = (1, 0)
+S->E = (-1, 0)
+S->W = (0, -1)
+W->S = (0, 1)
+W->N = (-1, 0)
+N->W = (1, 0)
+N->S = (1, 1)
+S->N = (-1, -1)
+E->W = (1, -1)
+W->E = (-1, 1)
+";
+
+#[cfg(test)]
+mod tests {
+    use bitris::prelude::*;
+    use bitris::srs::SrsKickTable;
+
+    use crate::kick_table::{is_mirror_symmetric, symmetric_kick_table};
+    use crate::{KickTable, KickTableParseError, No180Kicks, RotationSystem180};
+
+    /// Checks the 180° kicks against the reference, listed from North, East, South and West in the coordinates of the source.
+    /// The rotation without kicks is tried first, and not listed as in the source.
+    fn assert_kicks_180(table: &KickTable, shapes: &[Shape], reference: [&[(i32, i32)]; 4], y_down: bool) {
+        let srs = KickTable::srs();
+        for &shape in shapes {
+            for (orientation, offsets) in Orientation::all_iter().zip(reference) {
+                let piece = shape.with(orientation);
+
+                // The rotation center of bitris is the sum of two rotations without kicks.
+                let cw = srs.iter_kicks(piece, Rotation::Cw).as_slice()[0].offset;
+                let cw_cw = srs.iter_kicks(piece.cw(), Rotation::Cw).as_slice()[0].offset;
+                let expected = [(0, 0)].iter().chain(offsets)
+                    .map(|&(dx, dy)| {
+                        let dy = if y_down { -dy } else { dy };
+                        Kick::new(dd(cw.dx + cw_cw.dx + dx, cw.dy + cw_cw.dy + dy))
+                    })
+                    .collect::<Vec<_>>();
+                assert_eq!(table.iter_kicks_180(piece).as_slice(), expected.as_slice(), "{piece:?}");
+            }
+        }
+    }
+
+    /// Checks the kicks other than the 180° rotation are the same as SRS, and O is the same as SRS+.
+    fn assert_srs_based(table: &KickTable) {
+        let srs = KickTable::srs();
+        let srs_plus = KickTable::srs_plus();
+        for shape in Shape::all_iter() {
+            assert_eq!(table.is_moving_in_rotation(shape), srs.is_moving_in_rotation(shape));
+            for orientation in Orientation::all_iter() {
+                let piece = shape.with(orientation);
+                for rotation in [Rotation::Cw, Rotation::Ccw] {
+                    assert_eq!(table.iter_kicks(piece, rotation).as_slice(), srs.iter_kicks(piece, rotation).as_slice());
+                }
+            }
+        }
+        for orientation in Orientation::all_iter() {
+            let piece = Shape::O.with(orientation);
+            assert_eq!(table.iter_kicks_180(piece).as_slice(), srs_plus.iter_kicks_180(piece).as_slice());
+        }
+    }
+
+    #[test]
+    fn srs_is_same_as_bitris() {
+        let table = KickTable::srs();
+        for shape in Shape::all_iter() {
+            assert_eq!(table.is_moving_in_rotation(shape), SrsKickTable.is_moving_in_rotation(shape));
+            for orientation in Orientation::all_iter() {
+                let piece = shape.with(orientation);
+                for rotation in [Rotation::Cw, Rotation::Ccw] {
+                    assert_eq!(
+                        table.iter_kicks(piece, rotation).as_slice(),
+                        SrsKickTable.iter_kicks(piece, rotation).as_slice(),
+                    );
+                }
+                assert_eq!(table.iter_kicks_180(piece).len(), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn srs_plus() {
+        let srs = KickTable::srs();
+        let srs_plus = KickTable::srs_plus();
+        for shape in Shape::all_iter() {
+            assert_eq!(srs_plus.is_moving_in_rotation(shape), srs.is_moving_in_rotation(shape));
+            for orientation in Orientation::all_iter() {
+                let piece = shape.with(orientation);
+                for rotation in [Rotation::Cw, Rotation::Ccw] {
+                    let kicks = srs_plus.iter_kicks(piece, rotation).as_slice();
+                    let srs_kicks = srs.iter_kicks(piece, rotation).as_slice();
+                    if shape == Shape::I {
+                        // The same rotation center, and the same kicks in different priorities.
+                        assert_eq!(kicks[0], srs_kicks[0]);
+                        assert_eq!(kicks.len(), srs_kicks.len());
+                    } else {
+                        assert_eq!(kicks, srs_kicks);
+                    }
+                }
+
+                // The first 180° kick keeps the rotation center: it's the sum of two rotations without kicks.
+                let kicks_180 = srs_plus.iter_kicks_180(piece).as_slice();
+                let cw = srs.iter_kicks(piece, Rotation::Cw).as_slice()[0].offset;
+                let cw_cw = srs.iter_kicks(piece.cw(), Rotation::Cw).as_slice()[0].offset;
+                assert_eq!(kicks_180[0].offset, dd(cw.dx + cw_cw.dx, cw.dy + cw_cw.dy));
+            }
+        }
+    }
+
+    #[test]
+    fn srs_plus_i_kicks() {
+        use Orientation::*;
+
+        // Tetris Wiki, "TETR.IO" (the I kicks of SRS+), with the y-axis pointing up.
+        // The first kick of bitris SRS moves the rotation center of the guideline to that of bitris.
+        let srs_plus = KickTable::srs_plus();
+        let reference: [(Orientation, Rotation, [(i32, i32); 5]); 8] = [
+            (North, Rotation::Cw, [(0, 0), (1, 0), (-2, 0), (-2, -1), (1, 2)]),
+            (East, Rotation::Ccw, [(0, 0), (-1, 0), (2, 0), (-1, -2), (2, 1)]),
+            (East, Rotation::Cw, [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)]),
+            (South, Rotation::Ccw, [(0, 0), (-2, 0), (1, 0), (-2, 1), (1, -2)]),
+            (South, Rotation::Cw, [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)]),
+            (West, Rotation::Ccw, [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)]),
+            (West, Rotation::Cw, [(0, 0), (1, 0), (-2, 0), (1, 2), (-2, -1)]),
+            (North, Rotation::Ccw, [(0, 0), (-1, 0), (2, 0), (2, -1), (-1, 2)]),
+        ];
+        for (orientation, rotation, offsets) in reference {
+            let piece = Shape::I.with(orientation);
+            let center = SrsKickTable.iter_kicks(piece, rotation).as_slice()[0].offset;
+            let expected = offsets.iter()
+                .map(|&(dx, dy)| Kick::new(dd(center.dx + dx, center.dy + dy)))
+                .collect::<Vec<_>>();
+            assert_eq!(srs_plus.iter_kicks(piece, rotation).as_slice(), expected.as_slice(), "{piece:?} {rotation:?}");
+        }
+    }
+
+    #[test]
+    fn no_180_kicks() {
+        let table = No180Kicks(KickTable::srs_plus());
+        let srs_plus = KickTable::srs_plus();
+        assert!(srs_plus.has_kicks_180());
+        assert!(!table.has_kicks_180());
+        for shape in Shape::all_iter() {
+            assert_eq!(table.is_moving_in_rotation(shape), srs_plus.is_moving_in_rotation(shape));
+            for orientation in Orientation::all_iter() {
+                let piece = shape.with(orientation);
+                for rotation in [Rotation::Cw, Rotation::Ccw] {
+                    assert_eq!(table.iter_kicks(piece, rotation).as_slice(), srs_plus.iter_kicks(piece, rotation).as_slice());
+                }
+                assert_eq!(table.iter_kicks_180(piece).len(), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn srs_x() {
+        use Shape::*;
+
+        // Tetris Wiki, "SRS" (the 180° kicks of SRS-X)
+        let table = KickTable::srs_x();
+        assert_srs_based(&table);
+        assert_kicks_180(&table, &[T, J, L, S, Z], [
+            &[(1, 0), (2, 0), (1, 1), (2, 1), (-1, 0), (-2, 0), (-1, 1), (-2, 1), (0, -1), (3, 0), (-3, 0)],
+            &[(0, 1), (0, 2), (-1, 1), (-1, 2), (0, -1), (0, -2), (-1, -1), (-1, -2), (1, 0), (0, 3), (0, -3)],
+            &[(-1, 0), (-2, 0), (-1, -1), (-2, -1), (1, 0), (2, 0), (1, -1), (2, -1), (0, 1), (-3, 0), (3, 0)],
+            &[(0, 1), (0, 2), (1, 1), (1, 2), (0, -1), (0, -2), (1, -1), (1, -2), (-1, 0), (0, 3), (0, -3)],
+        ], false);
+        assert_kicks_180(&table, &[I], [
+            &[(-1, 0), (-2, 0), (1, 0), (2, 0), (0, 1)],
+            &[(0, 1), (0, 2), (0, -1), (0, -2), (-1, 0)],
+            &[(1, 0), (2, 0), (-1, 0), (-2, 0), (0, -1)],
+            &[(0, 1), (0, 2), (0, -1), (0, -2), (1, 0)],
+        ], false);
+    }
+
+    #[test]
+    fn nullpomino() {
+        use Shape::*;
+
+        // NullpoMino, `StandardWallkick.WALLKICK_NORMAL_180` and `WALLKICK_I_180`
+        let table = KickTable::nullpomino();
+        assert_srs_based(&table);
+        assert_kicks_180(&table, &[T, J, L, S, Z], [
+            &[(1, 0), (2, 0), (1, 1), (2, 1), (-1, 0), (-2, 0), (-1, 1), (-2, 1), (0, -1), (3, 0), (-3, 0)],
+            &[(0, 1), (0, 2), (-1, 1), (-1, 2), (0, -1), (0, -2), (-1, -1), (-1, -2), (1, 0), (0, 3), (0, -3)],
+            &[(-1, 0), (-2, 0), (-1, -1), (-2, -1), (1, 0), (2, 0), (1, -1), (2, -1), (0, 1), (-3, 0), (3, 0)],
+            &[(0, 1), (0, 2), (1, 1), (1, 2), (0, -1), (0, -2), (1, -1), (1, -2), (-1, 0), (0, 3), (0, -3)],
+        ], true);
+        assert_kicks_180(&table, &[I], [
+            &[(-1, 0), (-2, 0), (1, 0), (2, 0), (0, 1)],
+            &[(0, 1), (0, 2), (0, -1), (0, -2), (-1, 0)],
+            &[(1, 0), (2, 0), (-1, 0), (-2, 0), (0, -1)],
+            &[(0, 1), (0, 2), (0, -1), (0, -2), (1, 0)],
+        ], true);
+    }
+
+    #[test]
+    fn mirror_symmetry() {
+        assert!(!is_mirror_symmetric(&SrsKickTable));
+        assert!(!is_mirror_symmetric(&KickTable::srs()));
+        assert!(!is_mirror_symmetric(&KickTable::srs_plus()));
+        assert!(!is_mirror_symmetric(&KickTable::srs_x()));
+        assert!(!is_mirror_symmetric(&KickTable::nullpomino()));
+        assert!(is_mirror_symmetric(&symmetric_kick_table()));
+    }
+
+    #[test]
+    fn parse_errors() {
+        use KickTableParseError::*;
+
+        assert_eq!("N->E = (0, 0)".parse::<KickTable>(), Err(NoSection(1)));
+        assert_eq!("[T X]".parse::<KickTable>(), Err(UnknownShape(1, "X".to_string())));
+        assert_eq!("[T]\nN->N = (0, 0)".parse::<KickTable>(), Err(InvalidTransition(2, "N->N".to_string())));
+        assert_eq!("[T]\nN->E = (0, 0), (1)".parse::<KickTable>(), Err(InvalidKick(2, "(0, 0), (1)".to_string())));
+        assert_eq!("[T]\nN->E = (0, 0)\nN->E = (0, 0)".parse::<KickTable>(), Err(DuplicateKicks(3)));
+        assert_eq!("[T]\nN->E (0, 0)".parse::<KickTable>(), Err(InvalidSyntax(2)));
+        assert_eq!("[T]\nN->E = (0, 0)".parse::<KickTable>(), Err(MissingKicks(Shape::T, Orientation::North, Rotation::Ccw)));
+
+        // Sections can list the shapes in any order, and comments are ignored.
+        let reordered = KickTable::srs_plus();
+        let text = crate::kick_table::SRS_PLUS_KICK_TABLE.replace("[T J L S Z]", "[Z, S, L, J, T] # comment");
+        assert_eq!(text.parse::<KickTable>(), Ok(reordered));
+    }
+}
@@ -6,6 +6,12 @@ pub use shape_sequence::*;
 pub use patterns::*;
 pub use shape_order::*;
 pub use shape_counter::*;
+pub use finesse::*;
+pub use garbage_board::*;
+pub use input_moves::*;
+pub use kick_table::*;
+pub use spawn_rule::*;
+pub use t_spin::*;
 pub use traits::*;
 
 #[doc(hidden)]
@@ -19,10 +25,18 @@ pub mod prelude {
         patterns::*,
         shape_order::*,
         shape_counter::*,
+        finesse::*,
+        garbage_board::*,
+        input_moves::*,
+        kick_table::*,
+        spawn_rule::*,
+        t_spin::*,
         traits::*,
     };
 }
 
+pub mod pc_chain;
+pub mod pc_path;
 pub mod pc_possible;
 
 mod bit_shapes;
@@ -31,6 +45,12 @@ mod shape_sequence;
 mod patterns;
 mod shape_order;
 mod shape_counter;
+mod finesse;
+mod garbage_board;
+mod input_moves;
+mod kick_table;
+mod spawn_rule;
+mod t_spin;
 mod traits;
 
 mod internal_macros;
@@ -3,7 +3,7 @@ use itertools::{repeat_n, Itertools};
 use thiserror::Error;
 
 use crate::bit_shapes::BitShapes;
-use crate::{ForEachVisitor, ShapeCounter, ShapeOrder, ShapeSequence};
+use crate::{ForEachVisitor, Mirror, ShapeCounter, ShapeOrder, ShapeSequence};
 
 /// Calculate the number of permutations.
 fn calculate_permutation_size(len: usize, pop: usize) -> usize {
@@ -12,6 +12,45 @@ fn calculate_permutation_size(len: usize, pop: usize) -> usize {
     ((len - pop + 1)..=len).fold(1, |sum, it| sum * it)
 }
 
+/// Returns the shapes of `ShapeCounter` in the order they are permuted.
+fn expand_counter(counter: ShapeCounter) -> Vec<Shape> {
+    counter
+        .to_pairs()
+        .into_iter()
+        .flat_map(|(shape, count)| repeat_n(shape, count as usize))
+        .collect()
+}
+
+/// Returns the rank of `shapes` in the permutations by taking `shapes.len()` from `items` in lexicographic order of the indices.
+/// If the items have duplicates, it's the rank of the first permutation that makes `shapes`.
+fn rank_permutation(items: &[Shape], shapes: &[Shape]) -> Option<usize> {
+    let mut used = vec![false; items.len()];
+    let mut rank = 0;
+    for (depth, &shape) in shapes.iter().enumerate() {
+        let index = (0..items.len()).find(|&index| !used[index] && items[index] == shape)?;
+        let smaller = (0..index).filter(|&index| !used[index]).count();
+        let len_rest = shapes.len() - depth - 1;
+        if 0 < len_rest {
+            rank += smaller * calculate_permutation_size(items.len() - depth - 1, len_rest);
+        } else {
+            rank += smaller;
+        }
+        used[index] = true;
+    }
+    Some(rank)
+}
+
+/// Returns the permutation at `rank` by taking `pop` from `items`. The inverse of `rank_permutation()`.
+fn unrank_permutation(items: &[Shape], pop: usize, mut rank: usize, out: &mut Vec<Shape>) {
+    let mut unused = items.to_vec();
+    for depth in 0..pop {
+        let len_rest = pop - depth - 1;
+        let block = if 0 < len_rest { calculate_permutation_size(unused.len() - 1, len_rest) } else { 1 };
+        out.push(unused.remove(rank / block));
+        rank %= block;
+    }
+}
+
 /// A collection of elements to define the order/sequence of the shapes.
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub enum PatternElement {
@@ -42,19 +81,11 @@ impl PatternElement {
             PatternElement::Wildcard => Shape::all_iter().map(|it| vec![it]).collect(),
             PatternElement::Permutation(counter, pop) => {
                 assert!(0 < pop && pop <= counter.len());
-                counter
-                    .to_pairs()
-                    .into_iter()
-                    .flat_map(|(shape, count)| repeat_n(shape, count as usize).into_iter())
-                    .permutations(pop)
-                    .collect_vec()
+                expand_counter(counter).into_iter().permutations(pop).collect_vec()
+            }
+            PatternElement::Factorial(counter) => {
+                expand_counter(counter).into_iter().permutations(counter.len()).collect_vec()
             }
-            PatternElement::Factorial(counter) => counter
-                .to_pairs()
-                .into_iter()
-                .flat_map(|(shape, count)| repeat_n(shape, count as usize).into_iter())
-                .permutations(counter.len())
-                .collect_vec(),
         }
     }
 
@@ -74,6 +105,43 @@ impl PatternElement {
         }
     }
 
+    /// Returns the index of `shapes` in `to_shapes_vec()`. Returns `None` if the element does not represent it.
+    /// If it appears more than once because of duplicates, returns the first index.
+    pub fn rank_of(&self, shapes: &[Shape]) -> Option<usize> {
+        if shapes.len() != self.dim_shapes() {
+            return None;
+        }
+        match *self {
+            PatternElement::One(shape) => (shapes[0] == shape).then_some(0),
+            PatternElement::Fixed(fixed) => (shapes == fixed.to_vec()).then_some(0),
+            PatternElement::Wildcard => Shape::all_iter().position(|shape| shape == shapes[0]),
+            PatternElement::Permutation(counter, _) | PatternElement::Factorial(counter) => {
+                rank_permutation(&expand_counter(counter), shapes)
+            }
+        }
+    }
+
+    /// Appends the shapes at `rank` in `to_shapes_vec()` to `out`. `rank` must be less than `len_shapes_vec()`.
+    fn push_shapes_at(&self, rank: usize, out: &mut Vec<Shape>) {
+        match *self {
+            PatternElement::One(shape) => out.push(shape),
+            PatternElement::Fixed(shapes) => out.extend(shapes.to_vec()),
+            PatternElement::Wildcard => out.extend(Shape::all_iter().nth(rank)),
+            PatternElement::Permutation(counter, pop) => unrank_permutation(&expand_counter(counter), pop, rank, out),
+            PatternElement::Factorial(counter) => unrank_permutation(&expand_counter(counter), counter.len(), rank, out),
+        }
+    }
+
+    /// Returns `true` if the same shapes appear more than once in `to_shapes_vec()`.
+    fn has_duplicates(&self) -> bool {
+        match *self {
+            PatternElement::One(_) | PatternElement::Fixed(_) | PatternElement::Wildcard => false,
+            PatternElement::Permutation(counter, _) | PatternElement::Factorial(counter) => {
+                counter.to_pairs().into_iter().any(|(_, count)| 1 < count)
+            }
+        }
+    }
+
     /// The number of elements in one shapes.
     pub fn dim_shapes(&self) -> usize {
         match *self {
@@ -89,6 +157,18 @@ impl PatternElement {
     }
 }
 
+impl Mirror for PatternElement {
+    fn mirror(&self) -> Self {
+        match *self {
+            PatternElement::One(shape) => PatternElement::One(shape.mirror()),
+            PatternElement::Fixed(shapes) => PatternElement::Fixed(shapes.mirror()),
+            PatternElement::Wildcard => PatternElement::Wildcard,
+            PatternElement::Permutation(counter, pop) => PatternElement::Permutation(counter.mirror(), pop),
+            PatternElement::Factorial(counter) => PatternElement::Factorial(counter.mirror()),
+        }
+    }
+}
+
 /// Define the order/sequence of the shapes.
 /// ```
 /// use bitris_commands::prelude::*;
@@ -154,6 +234,11 @@ impl Pattern {
         Ok(Self { elements })
     }
 
+    #[inline]
+    pub(crate) fn elements(&self) -> &[PatternElement] {
+        self.elements.as_slice()
+    }
+
     #[allow(dead_code)]
     fn walk_shapes(&self, visitor: &mut impl ForEachVisitor<Vec<Shape>>) {
         let all_shapes_vec: Vec<Vec<Vec<Shape>>> = self
@@ -244,6 +329,66 @@ impl Pattern {
             .fold(1, |sum, it| sum * it)
     }
 
+    /// Returns the index of `shapes` in `to_sequences()`, called the rank.
+    /// Returns `None` if the pattern does not represent it.
+    /// If it appears more than once because of duplicates, returns the first index.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use PatternElement::*;
+    /// use Shape::*;
+    ///
+    /// // `T[TIO]p2`
+    /// let pattern = Pattern::try_from(vec![One(T), Permutation(ShapeCounter::from(vec![T, I, O]), 2)]).unwrap();
+    /// assert_eq!(pattern.rank_of(&[T, T, I]), Some(0));
+    /// assert_eq!(pattern.rank_of(&[T, O, I]), Some(5));
+    /// assert_eq!(pattern.rank_of(&[I, T, O]), None);
+    ///
+    /// assert_eq!(pattern.sequence_at(5), Some(ShapeSequence::new(vec![T, O, I])));
+    /// assert_eq!(pattern.sequence_at(6), None);
+    /// ```
+    pub fn rank_of(&self, shapes: &[Shape]) -> Option<usize> {
+        let mut rest = shapes;
+        let mut rank = 0;
+        for element in &self.elements {
+            let dim = element.dim_shapes();
+            if rest.len() < dim {
+                return None;
+            }
+            let (head, tail) = rest.split_at(dim);
+            rank = rank * element.len_shapes_vec() + element.rank_of(head)?;
+            rest = tail;
+        }
+        rest.is_empty().then_some(rank)
+    }
+
+    /// Returns the sequence at `rank` in `to_sequences()` without enumerating them.
+    /// Returns `None` if `rank` is out of range. The inverse of `rank_of()`.
+    pub fn sequence_at(&self, rank: usize) -> Option<ShapeSequence> {
+        if self.len_shapes_vec() <= rank {
+            return None;
+        }
+
+        // The rank is a mixed radix number whose most significant digit is the first element.
+        let mut digits = Vec::with_capacity(self.elements.len());
+        let mut rest = rank;
+        for element in self.elements.iter().rev() {
+            let len = element.len_shapes_vec();
+            digits.push(rest % len);
+            rest /= len;
+        }
+
+        let mut shapes = Vec::with_capacity(self.dim_shapes());
+        for (element, digit) in self.elements.iter().zip(digits.into_iter().rev()) {
+            element.push_shapes_at(digit, &mut shapes);
+        }
+        Some(ShapeSequence::new(shapes))
+    }
+
+    /// Returns `true` if the same sequences appear more than once in `to_sequences()`.
+    pub(crate) fn has_duplicates(&self) -> bool {
+        self.elements.iter().any(|element| element.has_duplicates())
+    }
+
     /// The number of elements in one shapes.
     pub fn dim_shapes(&self) -> usize {
         assert!(!self.elements.is_empty(), "The pattern do not have shapes.");
@@ -254,9 +399,28 @@ impl Pattern {
     }
 }
 
+impl Mirror for Pattern {
+    /// Returns the pattern of the mirrored sequences. The enumeration order may differ.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use PatternElement::*;
+    /// use Shape::*;
+    ///
+    /// // `L[TSZ]p2` -> `J[TSZ]p2`
+    /// let pattern = Pattern::try_from(vec![One(L), Permutation(ShapeCounter::from(vec![T, S, Z]), 2)]).unwrap();
+    /// let mirrored = pattern.mirror();
+    /// assert_eq!(mirrored, Pattern::try_from(vec![One(J), Permutation(ShapeCounter::from(vec![T, Z, S]), 2)]).unwrap());
+    /// assert!(mirrored.to_sequences().contains(&ShapeSequence::new(vec![J, Z, T])));
+    /// ```
+    fn mirror(&self) -> Self {
+        Self { elements: self.elements.iter().map(|element| element.mirror()).collect() }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use bitris::pieces::Shape;
+    use itertools::Itertools;
 
     use crate::bit_shapes::BitShapes;
     use crate::{Pattern, PatternCreationError, PatternElement, ShapeCounter};
@@ -342,6 +506,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn rank_and_unrank() {
+        use PatternElement::*;
+        use Shape::*;
+
+        let patterns = [
+            Pattern::try_from(vec![Permutation(ShapeCounter::one_of_each(), 3)]).unwrap(),
+            Pattern::try_from(vec![
+                One(S),
+                Wildcard,
+                Factorial(ShapeCounter::from(vec![T, I, O])),
+                Fixed(BitShapes::try_from(vec![L, J]).unwrap()),
+            ]).unwrap(),
+            Pattern::try_from(vec![
+                Permutation(ShapeCounter::from(vec![T, T, I, O, O]), 3),
+                Wildcard,
+            ]).unwrap(),
+        ];
+
+        for pattern in patterns {
+            let sequences = pattern.to_sequences();
+            for (index, sequence) in sequences.iter().enumerate() {
+                assert_eq!(pattern.sequence_at(index).as_ref(), Some(sequence));
+
+                // With duplicates, the rank is the first index.
+                let rank = pattern.rank_of(sequence.shapes()).unwrap();
+                assert!(rank <= index);
+                assert_eq!(&sequences[rank], sequence);
+            }
+            assert_eq!(pattern.sequence_at(sequences.len()), None);
+            assert_eq!(pattern.has_duplicates(), sequences.iter().unique().count() < sequences.len());
+        }
+
+        let pattern = Pattern::try_from(vec![One(T), Wildcard]).unwrap();
+        assert_eq!(pattern.rank_of(&[T]), None);
+        assert_eq!(pattern.rank_of(&[T, I, O]), None);
+        assert_eq!(pattern.rank_of(&[I, T]), None);
+    }
+
     #[test]
     fn large() {
         let patterns = Pattern::try_from(vec![
@@ -0,0 +1,110 @@
+use std::rc::Rc;
+
+use bitris::prelude::*;
+use bitris::srs::SrsKickTable;
+
+use crate::{ClippedBoard, Pattern, PatternElement, RotationSystem180, ShapeCounter, SpawnRule};
+use crate::pc_chain::{PcChainBulkExecutor, PcChainExecutorCreationError, PcChainResults};
+
+/// The binder to hold and tie settings for `PcChainBulkExecutor`.
+#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub struct PcChainBulkExecutorBinder<T: RotationSystem180> {
+    pub rotation_system: Rc<T>,
+    pub allow_move: AllowMove,
+    pub clipped_board: ClippedBoard,
+    pub pattern: Rc<Pattern>,
+    pub allows_hold: bool,
+    pub chain_height: u32,
+    pub max_chain: usize,
+    pub spawn_rule: SpawnRule,
+}
+
+impl PcChainBulkExecutorBinder<SrsKickTable> {
+    /// Making the executor with SRS. See `PcChainBulkExecutorBinder::default()` for more details.
+    pub fn srs() -> Self {
+        PcChainBulkExecutorBinder::default(Rc::from(SrsKickTable))
+    }
+}
+
+impl<T: RotationSystem180> PcChainBulkExecutorBinder<T> {
+    /// Making the executor with default.
+    ///
+    /// The default values are as follows:
+    ///   + [required] rotation_system: set an argument (wrapped by Rc)
+    ///   + allow move: softdrop
+    ///   + board: blank
+    ///   + height: 4 lines
+    ///   + pattern: two factorials of all shapes (like `*p7,*p7`)
+    ///   + allows hold: yes
+    ///   + chain height: 4 lines
+    ///   + max chain: unlimited
+    ///   + spawn rule: above the height (see `SpawnRule::default()`)
+    pub fn default(rotation_system: Rc<T>) -> Self {
+        Self {
+            rotation_system,
+            allow_move: AllowMove::Softdrop,
+            clipped_board: ClippedBoard::try_new(Board64::blank(), 4).unwrap(),
+            pattern: Rc::from(Pattern::try_from(vec![
+                PatternElement::Factorial(ShapeCounter::one_of_each()),
+                PatternElement::Factorial(ShapeCounter::one_of_each()),
+            ]).unwrap()),
+            allows_hold: true,
+            chain_height: 4,
+            max_chain: usize::MAX,
+            spawn_rule: SpawnRule::default(),
+        }
+    }
+
+    // See `PcChainBulkExecutor::{try_new, execute}` for more details.
+    pub fn try_execute(&self) -> Result<PcChainResults, PcChainExecutorCreationError> {
+        let move_rules = MoveRules::new(self.rotation_system.as_ref(), self.allow_move);
+        let executor = self.try_bind(&move_rules)?;
+        Ok(executor.execute())
+    }
+
+    fn try_bind<'a>(&'a self, move_rules: &'a MoveRules<T>) -> Result<PcChainBulkExecutor<'a, T>, PcChainExecutorCreationError> {
+        PcChainBulkExecutor::try_new(
+            move_rules,
+            self.clipped_board,
+            self.pattern.as_ref(),
+            self.allows_hold,
+            self.chain_height,
+        ).and_then(|executor| {
+            executor
+                .with_max_chain(self.max_chain)
+                .try_with_spawn_rule(self.spawn_rule)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use bitris::prelude::*;
+
+    use crate::{BitShapes, ClippedBoard, Pattern, PatternElement};
+    use crate::pc_chain::PcChainBulkExecutorBinder;
+
+    #[test]
+    fn reuse() {
+        use PatternElement::*;
+        use Shape::*;
+
+        let mut binder = PcChainBulkExecutorBinder::srs();
+        binder.clipped_board = ClippedBoard::try_new(Board64::blank(), 2).unwrap();
+        binder.chain_height = 2;
+        binder.pattern = Rc::from(Pattern::try_from(vec![
+            Fixed(BitShapes::try_from(vec![O, O, O, O, O, O, O, O, O, O, O, O, O, O]).unwrap()),
+            Wildcard,
+        ]).unwrap());
+
+        let results = binder.try_execute().unwrap();
+        assert_eq!(results.max_chain(), Some(3));
+
+        binder.max_chain = 1;
+        let results = binder.try_execute().unwrap();
+        assert_eq!(results.count_at_least(1), 7);
+        assert_eq!(results.max_chain(), Some(1));
+    }
+}
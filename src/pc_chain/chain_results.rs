@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+
+use crate::internals::SequenceTable;
+use crate::ShapeSequence;
+
+/// Holds the maximum count of consecutive Perfect Clears for each sequence.
+///
+/// The result of a sequence is `None` while pending. `Some(0)` means that even the first PC is impossible.
+/// The iteration is in the order of the sequences passed to `new()`.
+#[derive(Clone, Eq, PartialEq, Default, Debug)]
+pub struct PcChainResults {
+    chains: SequenceTable<Option<usize>>,
+}
+
+impl PcChainResults {
+    #[inline]
+    pub fn new(sequences: &Vec<ShapeSequence>) -> Self {
+        Self { chains: SequenceTable::new(sequences.iter().cloned(), || None) }
+    }
+
+    /// Records the maximum count of consecutive PCs. Returns `false` if the sequence is not a key.
+    #[inline]
+    pub fn accept_if_present(&mut self, sequence: &ShapeSequence, chain: usize) -> bool {
+        if let Some(recorded) = self.chains.get_mut(sequence) {
+            *recorded = Some(chain);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the maximum count of consecutive PCs of the sequence. Returns `None` if it's pending or not a key.
+    #[inline]
+    pub fn get(&self, sequence: &ShapeSequence) -> Option<usize> {
+        self.chains.get(sequence).copied().flatten()
+    }
+
+    /// Returns the pair of shape sequence and result in the order of the sequences passed to `new()`.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item=(&ShapeSequence, &Option<usize>)> + '_ {
+        self.chains.iter()
+    }
+
+    /// Returns the count of sequences that can take `chain` or more consecutive PCs.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::pc_chain::PcChainResults;
+    /// use Shape::*;
+    ///
+    /// let mut results = PcChainResults::new(&vec![
+    ///     ShapeSequence::new(vec![O, I]),
+    ///     ShapeSequence::new(vec![I, O]),
+    ///     ShapeSequence::new(vec![I, I]),
+    /// ]);
+    /// results.accept_if_present(&ShapeSequence::new(vec![O, I]), 2);
+    /// results.accept_if_present(&ShapeSequence::new(vec![I, O]), 1);
+    ///
+    /// assert_eq!(results.count_at_least(1), 2);
+    /// assert_eq!(results.count_at_least(2), 1);
+    /// assert_eq!(results.max_chain(), Some(2));
+    /// assert_eq!(results.count_pending(), 1);
+    /// ```
+    pub fn count_at_least(&self, chain: usize) -> u64 {
+        self.chains.values().iter()
+            .filter(|result| matches!(result, Some(found) if chain <= *found))
+            .count() as u64
+    }
+
+    /// Returns the largest count of consecutive PCs among the accepted sequences.
+    #[inline]
+    pub fn max_chain(&self) -> Option<usize> {
+        self.chains.values().iter().flatten().max().copied()
+    }
+
+    /// Returns the count of accepted sequences for each count of consecutive PCs.
+    pub fn distribution(&self) -> BTreeMap<usize, u64> {
+        let mut distribution = BTreeMap::new();
+        for &chain in self.chains.values().iter().flatten() {
+            *distribution.entry(chain).or_default() += 1;
+        }
+        distribution
+    }
+
+    /// Returns the count of shape sequences for which results are found.
+    #[inline]
+    pub fn count_accepted(&self) -> u64 {
+        self.chains.values().iter().flatten().count() as u64
+    }
+
+    /// Returns the count of shape sequences for which results are not yet found.
+    #[inline]
+    pub fn count_pending(&self) -> u64 {
+        self.count_keys() as u64 - self.count_accepted()
+    }
+
+    /// Return the count of all shape sequences independent of the result.
+    #[inline]
+    pub fn count_keys(&self) -> usize {
+        self.chains.len()
+    }
+}
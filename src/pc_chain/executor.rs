@@ -0,0 +1,450 @@
+use bitris::prelude::*;
+use fxhash::{FxHashMap, FxHashSet};
+use thiserror::Error;
+
+use crate::input_moves::generate_moves;
+use crate::pc_chain::PcChainResults;
+use crate::pc_possible::{validate_board, VerticalParity};
+use crate::{ClippedBoard, OrderCursor, Pattern, PopOp, RotationSystem180, ShapeOrder, SpawnRule};
+
+/// Dataset for detecting the same state during a PC search in a chain.
+/// See `SearchingState` in `PcPossibleBulkExecutor` for more details.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default, Debug)]
+struct SearchingState {
+    // The board does not include filled rows.
+    board: Board64,
+
+    height: u32,
+
+    first: Option<Shape>,
+}
+
+/// The search for one PC in a chain.
+struct Stage {
+    // The height of the board where the PC starts. Pieces spawn by it.
+    height: u32,
+
+    visited_states: FxHashSet<SearchingState>,
+
+    // The count of PCs, including this one, that is allowed to search.
+    limit: usize,
+
+    // The count of PCs that cannot be exceeded by the remaining shapes. The search stops when it's reached.
+    upper: usize,
+
+    best: usize,
+}
+
+/// Memorizes the counts of PCs from the blank board across sequences.
+/// The count from the blank board depends only on the remaining order.
+#[derive(Default)]
+struct ChainMemo {
+    // The order -> (the count of PCs found, the limit used to search)
+    blank_stages: FxHashMap<ShapeOrder, (usize, usize)>,
+}
+
+/// A collection of errors that occur when making the executor.
+#[derive(Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum PcChainExecutorCreationError {
+    #[error("Unexpected the count of board spaces.")]
+    UnexpectedBoardSpaces,
+    #[error("The pattern is too short to take a PC.")]
+    ShortPatternDimension,
+    #[error("Board height exceeds the upper limit. Up to 56 are supported.")]
+    BoardIsTooHigh,
+    #[error("The height of the chained PCs must be an even number from 2 to 56.")]
+    InvalidChainHeight,
+    #[error("The stack blocks the spawn.")]
+    SpawnIsBlocked,
+}
+
+/// The executor to find how many Perfect Clears can be taken in a row.
+///
+/// The first PC is taken on the clipped board, and the following PCs are taken on the blank board of `chain_height`.
+/// The hold and the unused shapes at each PC are carried over to the next PC.
+#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub struct PcChainBulkExecutor<'a, T: RotationSystem180> {
+    move_rules: &'a MoveRules<'a, T>,
+    clipped_board: ClippedBoard,
+    pattern: &'a Pattern,
+    allows_hold: bool,
+    chain_height: u32,
+    max_chain: usize,
+    spawn_rule: SpawnRule,
+}
+
+impl<'a, T: RotationSystem180> PcChainBulkExecutor<'a, T> {
+    /// Make PcChainBulkExecutor.
+    ///
+    /// Returns `Err()` if the setting is incorrect or restricted.
+    /// See `PcChainExecutorCreationError` for error patterns.
+    /// ```
+    /// use bitris::prelude::*;
+    /// use bitris_commands::{BitShapes, ClippedBoard, Pattern, PatternElement, ShapeCounter};
+    /// use bitris_commands::pc_chain::PcChainBulkExecutor;
+    /// use Shape::*;
+    ///
+    /// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    ///
+    /// // The first PC is 2 lines, and the following PCs are also 2 lines.
+    /// let clipped_board = ClippedBoard::try_new(Board64::blank(), 2).expect("Failed to clip");
+    /// let chain_height = 2;
+    ///
+    /// // `OOOOO OOOOO OOOO*`
+    /// let pattern = Pattern::try_from(vec![
+    ///     PatternElement::Fixed(BitShapes::try_from(vec![O, O, O, O, O, O, O, O, O, O, O, O, O, O]).unwrap()),
+    ///     PatternElement::Wildcard,
+    /// ]).expect("Failed to create a pattern");
+    ///
+    /// let executor = PcChainBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true, chain_height)
+    ///     .expect("Failed to create an executor");
+    ///
+    /// let results = executor.execute();
+    /// assert_eq!(results.count_keys(), 7);
+    /// assert_eq!(results.count_at_least(2), 7);
+    /// assert_eq!(results.count_at_least(3), 1); // Only if the last is O
+    /// ```
+    pub fn try_new(
+        move_rules: &'a MoveRules<T>,
+        clipped_board: ClippedBoard,
+        pattern: &'a Pattern,
+        allows_hold: bool,
+        chain_height: u32,
+    ) -> Result<Self, PcChainExecutorCreationError> {
+        use PcChainExecutorCreationError::*;
+
+        if 56 < clipped_board.height() {
+            return Err(BoardIsTooHigh);
+        }
+
+        if clipped_board.spaces() % 4 != 0 {
+            return Err(UnexpectedBoardSpaces);
+        }
+
+        if chain_height == 0 || 56 < chain_height || chain_height % 2 != 0 {
+            return Err(InvalidChainHeight);
+        }
+
+        let dimension = pattern.dim_shapes() as u32;
+        if dimension < clipped_board.spaces() / 4 {
+            return Err(ShortPatternDimension);
+        }
+
+        debug_assert!(0 < clipped_board.spaces());
+
+        Ok(Self {
+            move_rules,
+            clipped_board,
+            pattern,
+            allows_hold,
+            chain_height,
+            max_chain: usize::MAX,
+            spawn_rule: SpawnRule::default(),
+        })
+    }
+
+    /// Stops searching a sequence when `max_chain` PCs are found. It's useful when only a few PCs matter.
+    /// The default is unlimited.
+    #[inline]
+    pub fn with_max_chain(mut self, max_chain: usize) -> Self {
+        self.max_chain = max_chain;
+        self
+    }
+
+    /// Sets the placements where pieces spawn. See `PcPossibleBulkExecutor::try_with_spawn_rule()` for more details.
+    /// `SpawnRow::AboveHeight` spawns above the height of each PC, that is, the clipped height first and `chain_height` after.
+    ///
+    /// Returns `Err(PcChainExecutorCreationError::SpawnIsBlocked)` if the spawn of any shape is blocked on the initial board or the blank board.
+    pub fn try_with_spawn_rule(mut self, spawn_rule: SpawnRule) -> Result<Self, PcChainExecutorCreationError> {
+        let height = self.clipped_board.height();
+        if Shape::all_iter().any(|shape| {
+            spawn_rule.is_blocked(shape, self.clipped_board.board_ref(), height)
+                || spawn_rule.is_blocked(shape, &Board64::blank(), self.chain_height)
+        }) {
+            return Err(PcChainExecutorCreationError::SpawnIsBlocked);
+        }
+        self.spawn_rule = spawn_rule;
+        Ok(self)
+    }
+
+    /// Start the search for the maximum count of consecutive PCs of each sequence.
+    pub fn execute(&self) -> PcChainResults {
+        let sequences = self.pattern.to_sequences();
+        let mut results = PcChainResults::new(&sequences);
+
+        let mut memo = ChainMemo::default();
+        for sequence in &sequences {
+            let chain = self.search_chain(self.clipped_board, sequence.to_shape_order(), self.max_chain, &mut memo);
+            results.accept_if_present(sequence, chain);
+        }
+
+        results
+    }
+
+    /// Returns the maximum count of consecutive PCs of the order, independent of the pattern.
+    /// ```
+    /// use bitris::prelude::*;
+    /// use bitris_commands::{ClippedBoard, Pattern, PatternElement, ShapeOrder};
+    /// use bitris_commands::pc_chain::PcChainBulkExecutor;
+    /// use Shape::*;
+    ///
+    /// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    /// let clipped_board = ClippedBoard::try_new(Board64::blank(), 2).unwrap();
+    /// let pattern = Pattern::try_from(vec![PatternElement::Wildcard; 5]).unwrap();
+    /// let executor = PcChainBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true, 2).unwrap();
+    ///
+    /// // The hold keeps the first I until the second PC.
+    /// let order = ShapeOrder::new(vec![I, O, O, O, O, O, I, O, O, O]);
+    /// assert_eq!(executor.max_chain_of(&order), 2);
+    /// ```
+    pub fn max_chain_of(&self, order: &ShapeOrder) -> usize {
+        let mut memo = ChainMemo::default();
+        self.search_chain(self.clipped_board, order.clone(), self.max_chain, &mut memo)
+    }
+
+    #[inline]
+    fn shapes_per_blank(&self) -> usize {
+        (self.chain_height * 10 / 4) as usize
+    }
+
+    /// Returns the maximum count of PCs, up to `limit`, starting from the board.
+    fn search_chain(
+        &self,
+        clipped_board: ClippedBoard,
+        order: ShapeOrder,
+        limit: usize,
+        memo: &mut ChainMemo,
+    ) -> usize {
+        let shapes_per_pc = (clipped_board.spaces() / 4) as usize;
+        let len = order.shapes().len();
+        if limit == 0 || len < shapes_per_pc {
+            return 0;
+        }
+
+        let upper = limit.min(1 + (len - shapes_per_pc) / self.shapes_per_blank());
+        let mut stage = Stage {
+            height: clipped_board.height(),
+            visited_states: FxHashSet::default(),
+            limit,
+            upper,
+            best: 0,
+        };
+
+        let parity = VerticalParity::new(clipped_board);
+        self.pop_shape(order.new_cursor(), clipped_board, &parity, &mut stage, memo);
+
+        stage.best
+    }
+
+    /// Returns the maximum count of PCs, up to `limit`, starting from the blank board.
+    fn search_blank(&self, order: ShapeOrder, limit: usize, memo: &mut ChainMemo) -> usize {
+        if let Some(&(found, searched_limit)) = memo.blank_stages.get(&order) {
+            // If the count reached the limit, more may be found with a larger limit.
+            if found < searched_limit || limit <= searched_limit {
+                return found.min(limit);
+            }
+        }
+
+        let clipped_board = ClippedBoard::new_unsafe(Board64::blank(), self.chain_height);
+        let found = self.search_chain(clipped_board, order.clone(), limit, memo);
+        memo.blank_stages.insert(order, (found, limit));
+        found
+    }
+
+    /// Returns `true` if the upper is reached, so that the search can stop.
+    fn pop_shape(
+        &self,
+        cursor: OrderCursor,
+        clipped_board: ClippedBoard,
+        parity: &VerticalParity,
+        stage: &mut Stage,
+        memo: &mut ChainMemo,
+    ) -> bool {
+        let (popped, next_cursor) = cursor.pop(PopOp::First);
+        let Some(shape) = popped else {
+            return false;
+        };
+        if self.increment(shape, clipped_board, next_cursor, parity, stage, memo) {
+            return true;
+        }
+
+        if self.allows_hold {
+            let (popped, next_cursor) = cursor.pop(PopOp::Second);
+            // If the second is the same as the first, the result is the same as popping the first.
+            if let Some(shape) = popped.filter(|&shape| Some(shape) != cursor.first()) {
+                if self.increment(shape, clipped_board, next_cursor, parity, stage, memo) {
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    fn increment(
+        &self,
+        shape: Shape,
+        clipped_board: ClippedBoard,
+        next_cursor: OrderCursor,
+        parity: &VerticalParity,
+        stage: &mut Stage,
+        memo: &mut ChainMemo,
+    ) -> bool {
+        let spawn = self.spawn_rule.placement(shape, stage.height);
+        let moves = if !spawn.is_in_free_space(clipped_board.board_ref()) {
+            Vec::new()
+        } else {
+            generate_moves(self.move_rules, None, &self.spawn_rule, clipped_board.board(), spawn)
+        };
+
+        for placement in moves {
+            if clipped_board.height() as i32 <= placement.to_tr_placement().position.ty {
+                continue;
+            }
+
+            let mut board = clipped_board.board();
+            let lines_cleared = placement.place_on_and_clear_lines(&mut board).unwrap();
+            let rest_order = if next_cursor.has_next() { next_cursor.unused_shapes() } else { ShapeOrder::default() };
+
+            if board.is_empty() {
+                // The hold and the unused shapes are carried over to the next PC.
+                let chain = 1 + self.search_blank(rest_order, stage.limit - 1, memo);
+                stage.best = stage.best.max(chain);
+                if stage.upper <= stage.best {
+                    return true;
+                }
+                continue;
+            }
+
+            let height = clipped_board.height() - lines_cleared.count();
+            if !stage.visited_states.insert(SearchingState {
+                board,
+                height,
+                first: next_cursor.first(),
+            }) {
+                continue;
+            }
+
+            let next_clipped_board = ClippedBoard::new_unsafe(board, height);
+            if !validate_board(&next_clipped_board) {
+                continue;
+            }
+
+            let rest_shapes = rest_order.shapes();
+            let has_extra_shapes = (next_clipped_board.spaces() / 4) < rest_shapes.len() as u32;
+            let next_parity = parity.place(placement);
+            if !next_parity.validates(rest_shapes, 0, self.allows_hold && has_extra_shapes) {
+                continue;
+            }
+
+            if self.pop_shape(next_cursor, next_clipped_board, &next_parity, stage, memo) {
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::pc_chain::{PcChainBulkExecutor, PcChainExecutorCreationError};
+    use crate::{BitShapes, ClippedBoard, Pattern, PatternElement, ShapeCounter, ShapeOrder, ShapeSequence, SpawnRow, SpawnRule};
+
+    #[test]
+    fn carry_over_hold() {
+        use Shape::*;
+
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let clipped_board = ClippedBoard::try_new(Board64::blank(), 2).unwrap();
+        let pattern = Pattern::try_from(vec![PatternElement::Wildcard; 5]).unwrap();
+        let executor = PcChainBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true, 2).unwrap();
+
+        let order = ShapeOrder::new(vec![O, O, O, O, O, O, O, O, O, O, O, O, O, O, O]);
+        assert_eq!(executor.max_chain_of(&order), 3);
+        assert_eq!(executor.clone().with_max_chain(2).max_chain_of(&order), 2);
+
+        // Not enough shapes for the third.
+        let order = ShapeOrder::new(vec![O, O, O, O, O, O, O, O, O, O, O, O, O, O]);
+        assert_eq!(executor.max_chain_of(&order), 2);
+
+        // Without the hold, the I blocks the second.
+        let order = ShapeOrder::new(vec![I, O, O, O, O, O, I, O, O, O]);
+        let executor_without_hold = PcChainBulkExecutor::try_new(&move_rules, clipped_board, &pattern, false, 2).unwrap();
+        assert_eq!(executor_without_hold.max_chain_of(&order), 0);
+        assert_eq!(executor.max_chain_of(&order), 2);
+    }
+
+    #[test]
+    fn first_pc_on_board() {
+        use PatternElement::*;
+        use Shape::*;
+
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let board = Board64::from_str("
+            ######....
+            ######....
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 2).unwrap();
+
+        // `O*OOOOOO`
+        let pattern = Pattern::try_from(vec![
+            One(O),
+            Wildcard,
+            Fixed(BitShapes::try_from(vec![O, O, O, O, O, O]).unwrap()),
+        ]).unwrap();
+
+        // The hold keeps the wildcard until the end.
+        let executor = PcChainBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true, 2).unwrap();
+        let results = executor.execute();
+        assert_eq!(results.count_keys(), 7);
+        assert_eq!(results.count_pending(), 0);
+        assert_eq!(results.count_at_least(2), 7);
+
+        let executor = PcChainBulkExecutor::try_new(&move_rules, clipped_board, &pattern, false, 2).unwrap();
+        let results = executor.execute();
+        assert_eq!(results.get(&ShapeSequence::new(vec![O, O, O, O, O, O, O, O])), Some(2));
+        assert_eq!(results.distribution(), [(0, 6), (2, 1)].into_iter().collect());
+    }
+
+    #[test]
+    fn spawn_rule() {
+        use Shape::*;
+
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let board = Board64::from_str("
+            ######....
+            ######....
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 2).unwrap();
+        let pattern = Pattern::try_from(vec![PatternElement::Wildcard; 5]).unwrap();
+        let executor = PcChainBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true, 2).unwrap();
+
+        // Spawning on the floor on the right, the O can still reach everywhere.
+        let order = ShapeOrder::new(vec![O, O, O, O, O, O, O]);
+        let spawn_rule = SpawnRule::default().with_row(SpawnRow::Fixed(0)).with_columns(6);
+        let executor_on_floor = executor.clone().try_with_spawn_rule(spawn_rule).unwrap();
+        assert_eq!(executor_on_floor.max_chain_of(&order), executor.max_chain_of(&order));
+
+        // Blocked by the initial board
+        let spawn_rule = SpawnRule::default().with_row(SpawnRow::Fixed(0));
+        assert_eq!(executor.try_with_spawn_rule(spawn_rule).unwrap_err(), PcChainExecutorCreationError::SpawnIsBlocked);
+    }
+
+    #[test]
+    fn invalid_chain_height() {
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let clipped_board = ClippedBoard::try_new(Board64::blank(), 4).unwrap();
+        let pattern = Pattern::try_from(vec![PatternElement::Factorial(ShapeCounter::one_of_each())]).unwrap();
+        for chain_height in [0, 3, 58] {
+            assert_eq!(
+                PcChainBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true, chain_height).unwrap_err(),
+                PcChainExecutorCreationError::InvalidChainHeight,
+            );
+        }
+    }
+}
@@ -0,0 +1,7 @@
+pub use binder::*;
+pub use chain_results::*;
+pub use executor::*;
+
+mod binder;
+mod chain_results;
+mod executor;
@@ -0,0 +1,103 @@
+use std::rc::Rc;
+
+use bitris::prelude::*;
+use bitris::srs::SrsKickTable;
+
+use crate::{ClippedBoard, Pattern, PatternElement, RotationSystem180, ShapeCounter, SpawnRule};
+use crate::pc_path::{PcPathExecutor, PcPathExecutorCreationError, PcPathResults};
+
+/// The binder to hold and tie settings for `PcPathExecutor`.
+#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub struct PcPathExecutorBinder<T: RotationSystem180> {
+    pub rotation_system: Rc<T>,
+    pub allow_move: AllowMove,
+    pub clipped_board: ClippedBoard,
+    pub pattern: Rc<Pattern>,
+    pub allows_hold: bool,
+    pub spawn_rule: SpawnRule,
+}
+
+impl PcPathExecutorBinder<SrsKickTable> {
+    /// Making the executor with SRS. See `PcPathExecutorBinder::default()` for more details.
+    pub fn srs() -> Self {
+        PcPathExecutorBinder::default(Rc::from(SrsKickTable))
+    }
+}
+
+impl<T: RotationSystem180> PcPathExecutorBinder<T> {
+    /// Making the executor with default.
+    ///
+    /// The default values are as follows:
+    ///   + [required] rotation_system: set an argument (wrapped by Rc)
+    ///   + allow move: softdrop
+    ///   + board: blank
+    ///   + height: 4 lines
+    ///   + pattern: factorial of all shapes (like `*p7`)
+    ///   + allows hold: yes
+    ///   + spawn rule: above the height (see `SpawnRule::default()`)
+    pub fn default(rotation_system: Rc<T>) -> Self {
+        Self {
+            rotation_system,
+            allow_move: AllowMove::Softdrop,
+            clipped_board: ClippedBoard::try_new(Board64::blank(), 4).unwrap(),
+            pattern: Rc::from(Pattern::try_from(vec![
+                PatternElement::Factorial(ShapeCounter::one_of_each()),
+            ]).unwrap()),
+            allows_hold: true,
+            spawn_rule: SpawnRule::default(),
+        }
+    }
+
+    // See `PcPathExecutor::{try_new, execute}` for more details.
+    pub fn try_execute(&self) -> Result<PcPathResults, PcPathExecutorCreationError> {
+        let move_rules = MoveRules::new(self.rotation_system.as_ref(), self.allow_move);
+        let executor = self.try_bind(&move_rules)?;
+        Ok(executor.execute())
+    }
+
+    fn try_bind<'a>(&'a self, move_rules: &'a MoveRules<T>) -> Result<PcPathExecutor<'a, T>, PcPathExecutorCreationError> {
+        PcPathExecutor::try_new(
+            move_rules,
+            self.clipped_board,
+            self.pattern.as_ref(),
+            self.allows_hold,
+        ).and_then(|executor| executor.try_with_spawn_rule(self.spawn_rule))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::{ClippedBoard, Pattern, PatternElement, ShapeCounter};
+    use crate::pc_path::PcPathExecutorBinder;
+
+    #[test]
+    fn reuse() {
+        use PatternElement::*;
+
+        let mut binder = PcPathExecutorBinder::srs();
+        let board = Board64::from_str("
+            ####....##
+            #####..###
+            ##########
+            #####..###
+        ").unwrap();
+        binder.clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        binder.pattern = Rc::from(Pattern::try_from(vec![
+            Permutation(ShapeCounter::one_of_each(), 3),
+        ]).unwrap());
+
+        let results = binder.try_execute().unwrap();
+        assert_eq!(results.count_keys(), 210);
+        assert_eq!(results.count_succeed(), 90);
+
+        binder.allows_hold = false;
+        let results_without_hold = binder.try_execute().unwrap();
+        assert!(results_without_hold.count_succeed() < results.count_succeed());
+    }
+}
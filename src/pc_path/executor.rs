@@ -0,0 +1,472 @@
+use bitris::prelude::*;
+use fxhash::FxHashSet;
+use thiserror::Error;
+
+use crate::input_moves::generate_moves;
+use crate::pc_path::{PcPathResults, PcSolution, PlacedPiece};
+use crate::pc_possible::{validate_board, PcPossibleBulkExecutor, PcPossibleExecutorBulkCreationError, VerticalParity};
+use crate::{ClippedBoard, OrderCursor, Pattern, PopOp, RotationSystem180, ShapeOrder, SpawnRule};
+
+/// Dataset for detecting the same state during PC path search.
+/// See `SearchingState` in `PcPossibleBulkExecutor` for more details.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default, Debug)]
+struct SearchingState {
+    // The board does not include filled rows.
+    board: Board64,
+
+    height: u32,
+
+    first: Option<Shape>,
+}
+
+/// Holds the pieces placed so far while searching a sequence.
+struct Stack {
+    pieces: Vec<PlacedPiece>,
+    operations: Vec<BlPlacement>,
+}
+
+/// Returns the index of the `n`-th (0-indexed) set bit.
+#[inline]
+fn nth_set_bit(mut bits: u64, n: u32) -> u32 {
+    for _ in 0..n {
+        bits &= bits - 1;
+    }
+    debug_assert_ne!(bits, 0);
+    bits.trailing_zeros()
+}
+
+/// A collection of errors that occur when making the executor.
+#[derive(Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum PcPathExecutorCreationError {
+    #[error(transparent)]
+    ExecutorCreation(#[from] PcPossibleExecutorBulkCreationError),
+}
+
+/// The executor to enumerate all PC solutions.
+/// This feature was called `path` in solution-finder.
+#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub struct PcPathExecutor<'a, T: RotationSystem180> {
+    move_rules: &'a MoveRules<'a, T>,
+    clipped_board: ClippedBoard,
+    pattern: &'a Pattern,
+    allows_hold: bool,
+    has_extra_shapes: bool,
+    spawn_rule: SpawnRule,
+}
+
+impl<'a, T: RotationSystem180> PcPathExecutor<'a, T> {
+    /// Make PcPathExecutor.
+    ///
+    /// Returns `Err()` if the setting is incorrect or restricted.
+    /// See `PcPathExecutorCreationError` for error patterns.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris::prelude::{Shape, Board64, MoveRules, AllowMove};
+    /// use bitris_commands::{ClippedBoard, Pattern, PatternElement, ShapeCounter};
+    /// use bitris_commands::pc_path::PcPathExecutor;
+    ///
+    /// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    ///
+    /// let board = Board64::from_str("
+    ///     XXXXXX....
+    ///     XXXXXX....
+    /// ").expect("Failed to create a board");
+    /// let height = 2;
+    /// let clipped_board = ClippedBoard::try_new(board, height).expect("Failed to clip");
+    ///
+    /// let pattern = Pattern::try_from(vec![
+    ///     PatternElement::Permutation(ShapeCounter::from(vec![Shape::I, Shape::I, Shape::O, Shape::O]), 2),
+    /// ]).expect("Failed to create a pattern");
+    ///
+    /// let allows_hold = true;
+    ///
+    /// let executor = PcPathExecutor::try_new(&move_rules, clipped_board, &pattern, allows_hold)
+    ///     .expect("Failed to create an executor");
+    ///
+    /// let results = executor.execute();
+    /// assert_eq!(results.count_keys(), 4); // II, IO, OI and OO
+    /// assert_eq!(results.count_solutions(), 2);
+    /// assert_eq!(results.count_succeed(), 2); // II and OO
+    /// ```
+    pub fn try_new(
+        move_rules: &'a MoveRules<T>,
+        clipped_board: ClippedBoard,
+        pattern: &'a Pattern,
+        allows_hold: bool,
+    ) -> Result<Self, PcPathExecutorCreationError> {
+        // Validates the settings in the same way.
+        PcPossibleBulkExecutor::try_new(move_rules, clipped_board, pattern, allows_hold)?;
+
+        let dimension = pattern.dim_shapes() as u32;
+        let has_extra_shapes = clipped_board.spaces() / 4 < dimension;
+
+        Ok(Self {
+            move_rules,
+            clipped_board,
+            pattern,
+            allows_hold,
+            has_extra_shapes,
+            spawn_rule: SpawnRule::default(),
+        })
+    }
+
+    /// Sets the placements where pieces spawn. See `PcPossibleBulkExecutor::try_with_spawn_rule()` for more details.
+    ///
+    /// Returns `Err()` if the spawn of any shape is blocked by the initial board.
+    pub fn try_with_spawn_rule(mut self, spawn_rule: SpawnRule) -> Result<Self, PcPathExecutorCreationError> {
+        let height = self.clipped_board.height();
+        if Shape::all_iter().any(|shape| spawn_rule.is_blocked(shape, self.clipped_board.board_ref(), height)) {
+            return Err(PcPossibleExecutorBulkCreationError::SpawnIsBlocked.into());
+        }
+        self.spawn_rule = spawn_rule;
+        Ok(self)
+    }
+
+    /// Start the search for all PC solutions.
+    ///
+    /// Each sequence is searched exhaustively, then the solutions with identical layouts are merged.
+    pub fn execute(&self) -> PcPathResults {
+        let mut results = PcPathResults::new(self.pattern.to_sequences());
+
+        let mut failed_states = FxHashSet::<SearchingState>::default();
+        let mut found = FxHashSet::<usize>::default();
+
+        for sequence_index in 0..results.count_keys() {
+            failed_states.clear();
+            found.clear();
+
+            let order = results.sequences()[sequence_index].to_shape_order();
+            self.search_all(&order, &mut results, &mut failed_states, &mut found);
+
+            for &solution_index in &found {
+                results.cover(solution_index, sequence_index);
+            }
+        }
+
+        results
+    }
+
+    fn search_all(
+        &self,
+        order: &ShapeOrder,
+        results: &mut PcPathResults,
+        failed_states: &mut FxHashSet<SearchingState>,
+        found: &mut FxHashSet<usize>,
+    ) {
+        let cursor = order.new_cursor();
+        let mut stack = Stack {
+            pieces: Vec::with_capacity(cursor.len_unused()),
+            operations: Vec::with_capacity(cursor.len_unused()),
+        };
+        let parity = VerticalParity::new(self.clipped_board);
+        let rows = (1u64 << self.clipped_board.height()) - 1;
+
+        self.pop_shape(
+            cursor,
+            self.clipped_board,
+            rows,
+            &parity,
+            &mut stack,
+            results,
+            failed_states,
+            found,
+        );
+    }
+
+    /// Returns `true` if one or more solutions are found from the state.
+    #[allow(clippy::too_many_arguments)]
+    fn pop_shape(
+        &self,
+        cursor: OrderCursor,
+        clipped_board: ClippedBoard,
+        rows: u64,
+        parity: &VerticalParity,
+        stack: &mut Stack,
+        results: &mut PcPathResults,
+        failed_states: &mut FxHashSet<SearchingState>,
+        found: &mut FxHashSet<usize>,
+    ) -> bool {
+        let mut succeed = false;
+
+        let (popped, next_cursor) = cursor.pop(PopOp::First);
+        if let Some(shape) = popped {
+            succeed |= self.increment(
+                shape,
+                clipped_board,
+                rows,
+                next_cursor,
+                parity,
+                stack,
+                results,
+                failed_states,
+                found,
+            );
+        } else {
+            return false;
+        }
+
+        if self.allows_hold {
+            let (popped, next_cursor) = cursor.pop(PopOp::Second);
+            // If the second is the same as the first, the result is the same as popping the first.
+            if let Some(shape) = popped.filter(|&shape| Some(shape) != cursor.first()) {
+                succeed |= self.increment(
+                    shape,
+                    clipped_board,
+                    rows,
+                    next_cursor,
+                    parity,
+                    stack,
+                    results,
+                    failed_states,
+                    found,
+                );
+            }
+        }
+
+        succeed
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn increment(
+        &self,
+        shape: Shape,
+        clipped_board: ClippedBoard,
+        rows: u64,
+        next_cursor: OrderCursor,
+        parity: &VerticalParity,
+        stack: &mut Stack,
+        results: &mut PcPathResults,
+        failed_states: &mut FxHashSet<SearchingState>,
+        found: &mut FxHashSet<usize>,
+    ) -> bool {
+        let spawn = self.spawn_rule.placement(shape, self.clipped_board.height());
+        let moves = if !spawn.is_in_free_space(clipped_board.board_ref()) {
+            Vec::new()
+        } else {
+            generate_moves(self.move_rules, None, &self.spawn_rule, clipped_board.board(), spawn)
+        };
+
+        let mut succeed = false;
+
+        for placement in moves {
+            if clipped_board.height() as i32 <= placement.to_tr_placement().position.ty {
+                continue;
+            }
+
+            let locations = placement.locations();
+
+            let mut board = clipped_board.board();
+            for location in locations {
+                board.set_at(location);
+            }
+            let filled_rows = board.cols.iter()
+                .fold((1u64 << clipped_board.height()) - 1, |filled, col| filled & col);
+            board.clear_lines();
+
+            // Locations and rows on the initial board.
+            let original_locations = locations.map(|location| {
+                xy(location.x, nth_set_bit(rows, location.y as u32) as i32)
+            });
+            let mut next_rows = rows;
+            for y in 0..clipped_board.height() {
+                if filled_rows & (1u64 << y) != 0 {
+                    next_rows &= !(1u64 << nth_set_bit(rows, y));
+                }
+            }
+
+            stack.pieces.push(PlacedPiece::new(shape, original_locations));
+            stack.operations.push(placement);
+
+            if board.is_empty() {
+                let mut pieces = stack.pieces.clone();
+                pieces.sort();
+                let solution_index = results.insert_solution(PcSolution::new(pieces, stack.operations.clone()));
+                found.insert(solution_index);
+                succeed = true;
+            } else {
+                let height = clipped_board.height() - filled_rows.count_ones();
+                let state = SearchingState {
+                    board,
+                    height,
+                    first: next_cursor.first(),
+                };
+
+                if !failed_states.contains(&state) {
+                    let next_clipped_board = ClippedBoard::new_unsafe(board, height);
+                    let next_parity = parity.place(placement);
+
+                    let next_succeed = validate_board(&next_clipped_board)
+                        && next_cursor.has_next()
+                        && next_parity.validates(
+                        next_cursor.unused_shapes().shapes(),
+                        0,
+                        self.allows_hold && self.has_extra_shapes,
+                    )
+                        && self.pop_shape(
+                        next_cursor,
+                        next_clipped_board,
+                        next_rows,
+                        &next_parity,
+                        stack,
+                        results,
+                        failed_states,
+                        found,
+                    );
+
+                    if next_succeed {
+                        succeed = true;
+                    } else {
+                        failed_states.insert(state);
+                    }
+                }
+            }
+
+            stack.pieces.pop();
+            stack.operations.pop();
+        }
+
+        succeed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::pc_path::{PcPathExecutor, PcPathExecutorCreationError};
+    use crate::pc_possible::PcPossibleExecutorBulkCreationError;
+    use crate::{ClippedBoard, Pattern, PatternElement, ShapeCounter, ShapeSequence, SpawnRow, SpawnRule};
+
+    #[test]
+    fn solutions_with_line_clear() {
+        use PatternElement::*;
+        use Shape::*;
+
+        let board = Board64::from_str(
+            "
+            ####....##
+            #####..###
+            ##########
+            #####..###
+        ",
+        )
+        .unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let pattern = Pattern::try_from(vec![Permutation(ShapeCounter::one_of_each(), 3)]).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+
+        let executor = PcPathExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap();
+        let results = executor.execute();
+        assert_eq!(results.count_keys(), 210);
+        assert_eq!(results.count_succeed(), 90);
+
+        for solution in results.solutions() {
+            assert_eq!(solution.pieces().len(), 2);
+
+            // The pieces fill the spaces of the initial board.
+            let mut filled = clipped_board.board();
+            for piece in solution.pieces() {
+                for location in piece.locations {
+                    assert!(filled.is_free_at(location));
+                    filled.set_at(location);
+                }
+            }
+            assert_eq!(filled.count_blocks(), 30);
+        }
+
+        let solutions = results.solutions_for(&ShapeSequence::new(vec![O, I, T]));
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].shape_counter(), ShapeCounter::from(vec![O, I]));
+        assert!(results.solutions_for(&ShapeSequence::new(vec![S, O, L])).is_empty());
+    }
+
+    #[test]
+    fn coverage_with_and_without_hold() {
+        use PatternElement::*;
+        use Shape::*;
+
+        let board = Board64::from_str(
+            "
+            ######....
+            ######....
+        ",
+        )
+        .unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 2).unwrap();
+        let pattern = Pattern::try_from(vec![One(O), One(I), One(O)]).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+
+        let executor = PcPathExecutor::try_new(&move_rules, clipped_board, &pattern, false).unwrap();
+        let results = executor.execute();
+        assert_eq!(results.count_solutions(), 0);
+        assert_eq!(results.count_succeed(), 0);
+
+        // Holds I and uses the two Os.
+        let executor = PcPathExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap();
+        let results = executor.execute();
+        assert_eq!(results.count_solutions(), 1);
+        assert_eq!(results.count_covered(0), 1);
+
+        let solution = &results.solutions()[0];
+        assert_eq!(solution.shape_counter(), ShapeCounter::from(vec![O, O]));
+        assert_eq!(
+            solution.operations(),
+            &[
+                O.with(Orientation::North).with(bl(6, 0)),
+                O.with(Orientation::North).with(bl(8, 0)),
+            ],
+        );
+    }
+
+    #[test]
+    fn spawn_rule() {
+        use PatternElement::*;
+        use Shape::*;
+
+        let board = Board64::from_str(
+            "
+            ######....
+            ######....
+        ",
+        )
+        .unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 2).unwrap();
+        let pattern = Pattern::try_from(vec![One(O), One(I), One(O)]).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+
+        let executor = PcPathExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap();
+        let results = executor.execute();
+
+        // Spawning far above the height, the results are the same.
+        let spawn_rule = SpawnRule::default().with_row(SpawnRow::Fixed(20)).with_columns(6);
+        assert_eq!(executor.clone().try_with_spawn_rule(spawn_rule).unwrap().execute(), results);
+
+        // Blocked by the initial board
+        let spawn_rule = SpawnRule::default().with_row(SpawnRow::Fixed(0));
+        assert_eq!(
+            executor.try_with_spawn_rule(spawn_rule).unwrap_err(),
+            PcPathExecutorCreationError::ExecutorCreation(PcPossibleExecutorBulkCreationError::SpawnIsBlocked),
+        );
+    }
+
+    #[test]
+    fn error_unexpected_board_spaces() {
+        use PatternElement::*;
+        let board = Board64::from_str(
+            "
+            ######...#
+            ######..##
+        ",
+        )
+        .unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 2).unwrap();
+        let pattern = Pattern::try_from(vec![One(Shape::O), One(Shape::O)]).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        assert_eq!(
+            PcPathExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap_err(),
+            PcPathExecutorCreationError::ExecutorCreation(PcPossibleExecutorBulkCreationError::UnexpectedBoardSpaces),
+        );
+    }
+}
@@ -0,0 +1,9 @@
+pub use binder::*;
+pub use executor::*;
+pub use pc_solutions::*;
+pub use solution_cover::*;
+
+mod binder;
+mod executor;
+mod pc_solutions;
+mod solution_cover;
@@ -0,0 +1,291 @@
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+use bitris::prelude::*;
+use bitvec::prelude::*;
+use fxhash::FxHashMap;
+
+use crate::internals::SequenceTable;
+use crate::{ShapeCounter, ShapeSequence};
+
+/// A piece that makes up a PC solution.
+///
+/// The locations are on the clipped board before any lines are cleared.
+/// Therefore, a piece placed after a line clear can be split vertically by the cleared line.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct PlacedPiece {
+    pub shape: Shape,
+    pub locations: [Location; 4],
+}
+
+impl PlacedPiece {
+    #[inline]
+    pub fn new(shape: Shape, mut locations: [Location; 4]) -> Self {
+        locations.sort();
+        Self { shape, locations }
+    }
+}
+
+/// Represents one PC solution: a set of pieces that fills the board.
+///
+/// Solutions are identified by their final layout.
+/// That is, two solutions are the same if they have the same pieces at the same locations, even if the build orders differ.
+#[derive(Clone, Debug)]
+pub struct PcSolution {
+    pieces: Vec<PlacedPiece>,
+    operations: Vec<BlPlacement>,
+}
+
+impl PcSolution {
+    #[inline]
+    pub(crate) fn new(pieces: Vec<PlacedPiece>, operations: Vec<BlPlacement>) -> Self {
+        debug_assert_eq!(pieces.len(), operations.len());
+        Self { pieces, operations }
+    }
+
+    /// Returns the pieces that make up the solution. They are sorted and do not represent the build order.
+    #[inline]
+    pub fn pieces(&self) -> &[PlacedPiece] {
+        self.pieces.as_slice()
+    }
+
+    /// Returns the placements in one of the valid build orders.
+    /// Each placement is relative to the board at the time it's placed, that is, after the lines cleared so far are removed.
+    #[inline]
+    pub fn operations(&self) -> &[BlPlacement] {
+        self.operations.as_slice()
+    }
+
+    /// Returns the count of each shape used in the solution.
+    #[inline]
+    pub fn shape_counter(&self) -> ShapeCounter {
+        ShapeCounter::from(self.pieces.iter().map(|piece| piece.shape).collect::<Vec<_>>())
+    }
+}
+
+impl PartialEq for PcSolution {
+    fn eq(&self, other: &Self) -> bool {
+        self.pieces == other.pieces
+    }
+}
+
+impl Eq for PcSolution {}
+
+impl Hash for PcSolution {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.pieces.hash(state);
+    }
+}
+
+impl PartialOrd for PcSolution {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PcSolution {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.pieces.cmp(&other.pieces)
+    }
+}
+
+/// Holds all PC solutions found and the shape sequences covered by each.
+///
+/// A solution covers a sequence if the solution can be built from the sequence.
+/// The sequences are kept in the order enumerated by the pattern, without duplicates.
+/// The equality does not depend on the order of the sequences and the solutions.
+#[derive(Clone, Debug)]
+pub struct PcPathResults {
+    sequences: SequenceTable<()>,
+    solutions: Vec<PcSolution>,
+    layouts: FxHashMap<Vec<PlacedPiece>, usize>,
+    coverages: Vec<BitVec>,
+}
+
+impl PcPathResults {
+    #[inline]
+    pub(crate) fn new(sequences: Vec<ShapeSequence>) -> Self {
+        Self {
+            sequences: SequenceTable::new(sequences, || ()),
+            solutions: Vec::new(),
+            layouts: FxHashMap::default(),
+            coverages: Vec::new(),
+        }
+    }
+
+    /// Registers the solution if it's new, and returns its index.
+    pub(crate) fn insert_solution(&mut self, solution: PcSolution) -> usize {
+        if let Some(&index) = self.layouts.get(&solution.pieces) {
+            return index;
+        }
+        let index = self.solutions.len();
+        self.layouts.insert(solution.pieces.clone(), index);
+        self.solutions.push(solution);
+        self.coverages.push(BitVec::repeat(false, self.sequences.len()));
+        index
+    }
+
+    #[inline]
+    pub(crate) fn cover(&mut self, solution_index: usize, sequence_index: usize) {
+        self.coverages[solution_index].set(sequence_index, true);
+    }
+
+    #[inline]
+    pub(crate) fn coverage(&self, solution_index: usize) -> &BitSlice {
+        self.coverages[solution_index].as_bitslice()
+    }
+
+    /// Returns all distinct solutions in the order they were found.
+    #[inline]
+    pub fn solutions(&self) -> &[PcSolution] {
+        self.solutions.as_slice()
+    }
+
+    /// Returns all distinct sequences in the order enumerated by the pattern.
+    #[inline]
+    pub fn sequences(&self) -> &[ShapeSequence] {
+        self.sequences.sequences()
+    }
+
+    /// Returns the sequences covered by the solution.
+    /// Panics if the index is out of range.
+    #[inline]
+    pub fn covered_sequences(&self, solution_index: usize) -> Vec<&ShapeSequence> {
+        self.coverages[solution_index].iter_ones()
+            .map(|index| &self.sequences.sequences()[index])
+            .collect()
+    }
+
+    /// Returns the count of sequences covered by the solution.
+    /// Panics if the index is out of range.
+    #[inline]
+    pub fn count_covered(&self, solution_index: usize) -> u64 {
+        self.coverages[solution_index].count_ones() as u64
+    }
+
+    /// Returns the solutions that can be built from the sequence.
+    /// If the sequence is not in the pattern, returns empty.
+    pub fn solutions_for(&self, sequence: &ShapeSequence) -> Vec<&PcSolution> {
+        match self.sequences.index_of(sequence) {
+            Some(sequence_index) => self.coverages.iter()
+                .zip(self.solutions.iter())
+                .filter(|(coverage, _)| coverage[sequence_index])
+                .map(|(_, solution)| solution)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns the count of sequences covered by at least one solution.
+    pub fn count_succeed(&self) -> u64 {
+        let mut covered = BitVec::<usize, Lsb0>::repeat(false, self.sequences.len());
+        for coverage in &self.coverages {
+            covered |= coverage.as_bitslice();
+        }
+        covered.count_ones() as u64
+    }
+
+    /// Returns the count of distinct solutions.
+    #[inline]
+    pub fn count_solutions(&self) -> usize {
+        self.solutions.len()
+    }
+
+    /// Return the count of all shape sequences independent of the result.
+    #[inline]
+    pub fn count_keys(&self) -> usize {
+        self.sequences.len()
+    }
+}
+
+impl PartialEq for PcPathResults {
+    fn eq(&self, other: &Self) -> bool {
+        self.sequences == other.sequences
+            && self.solutions.len() == other.solutions.len()
+            && self.solutions.iter().enumerate().all(|(index, solution)| {
+                other.layouts.get(&solution.pieces).is_some_and(|&other_index| {
+                    self.count_covered(index) == other.count_covered(other_index)
+                        && self.coverage(index).iter_ones().all(|sequence_index| {
+                            other.sequences.index_of(&self.sequences.sequences()[sequence_index])
+                                .is_some_and(|other_sequence_index| other.coverage(other_index)[other_sequence_index])
+                        })
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitris::prelude::*;
+    use tap::Tap;
+
+    use crate::pc_path::{PcPathResults, PcSolution, PlacedPiece};
+    use crate::ShapeSequence;
+
+    #[test]
+    fn dedup_sequences_and_solutions() {
+        use Shape::*;
+
+        let mut results = PcPathResults::new(vec![
+            ShapeSequence::new(vec![O, O]),
+            ShapeSequence::new(vec![I, I]),
+            ShapeSequence::new(vec![O, O]),
+        ]);
+        assert_eq!(results.count_keys(), 2);
+
+        let o1 = O.with(Orientation::North).with(bl(0, 0));
+        let o2 = O.with(Orientation::North).with(bl(2, 0));
+        let make = |first: BlPlacement, second: BlPlacement| PcSolution::new(
+            vec![
+                PlacedPiece::new(O, first.locations()),
+                PlacedPiece::new(O, second.locations()),
+            ].tap_mut(|pieces| pieces.sort()),
+            vec![first, second],
+        );
+
+        // The same layout regardless of the build order
+        assert_eq!(make(o1, o2), make(o2, o1));
+        assert_eq!(make(o1, o2).cmp(&make(o2, o1)), std::cmp::Ordering::Equal);
+
+        let index = results.insert_solution(make(o1, o2));
+        results.cover(index, 0);
+        assert_eq!(results.insert_solution(make(o2, o1)), index);
+        assert_eq!(results.count_solutions(), 1);
+
+        assert_eq!(results.count_covered(index), 1);
+        assert_eq!(results.covered_sequences(index), vec![&ShapeSequence::new(vec![O, O])]);
+        assert_eq!(results.solutions_for(&ShapeSequence::new(vec![O, O])).len(), 1);
+        assert_eq!(results.solutions_for(&ShapeSequence::new(vec![I, I])).len(), 0);
+        assert_eq!(results.count_succeed(), 1);
+    }
+
+    #[test]
+    fn eq_in_any_order() {
+        use Shape::*;
+
+        let o = |x| O.with(Orientation::North).with(bl(x, 0));
+        let make = |first: BlPlacement, second: BlPlacement| PcSolution::new(
+            vec![
+                PlacedPiece::new(O, first.locations()),
+                PlacedPiece::new(O, second.locations()),
+            ].tap_mut(|pieces| pieces.sort()),
+            vec![first, second],
+        );
+
+        let mut results = PcPathResults::new(vec![ShapeSequence::new(vec![O, O]), ShapeSequence::new(vec![O, I])]);
+        let index = results.insert_solution(make(o(0), o(2)));
+        results.cover(index, 0);
+        let index = results.insert_solution(make(o(4), o(6)));
+        results.cover(index, 0);
+
+        let mut reversed = PcPathResults::new(vec![ShapeSequence::new(vec![O, I]), ShapeSequence::new(vec![O, O])]);
+        let index = reversed.insert_solution(make(o(4), o(6)));
+        reversed.cover(index, 1);
+        let index = reversed.insert_solution(make(o(0), o(2)));
+        reversed.cover(index, 1);
+        assert_eq!(results, reversed);
+
+        reversed.cover(index, 0);
+        assert_ne!(results, reversed);
+    }
+}
@@ -0,0 +1,336 @@
+use bitvec::prelude::*;
+
+use crate::pc_path::PcPathResults;
+
+/// A collection of strategies to select solutions covering sequences.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default, Debug)]
+pub enum CoverStrategy {
+    /// Picks the solution covering the most uncovered sequences one by one, then removes redundant solutions.
+    /// It's fast, but the result may not be minimal.
+    #[default]
+    Greedy,
+
+    /// Finds a minimum set by branch and bound.
+    /// It takes exponential time in the worst case, so it's for small cases.
+    Exact,
+}
+
+/// A solution selected in `SolutionCover`.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct CoverEntry {
+    /// The index in `PcPathResults::solutions()`.
+    pub solution_index: usize,
+
+    /// The count of sequences covered by the solution.
+    pub count_covered: u64,
+
+    /// The count of sequences attributed to the solution.
+    /// Each sequence is attributed to the first entry that covers it, so the sum over the entries is the count of covered sequences.
+    pub count_attributed: u64,
+}
+
+/// Holds solutions that together cover every PC-possible sequence.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct SolutionCover {
+    entries: Vec<CoverEntry>,
+    count_covered: u64,
+}
+
+impl SolutionCover {
+    /// Returns the selected solutions. They are sorted by the count of covered sequences in descending order.
+    #[inline]
+    pub fn entries(&self) -> &[CoverEntry] {
+        self.entries.as_slice()
+    }
+
+    /// Returns the indices of the selected solutions in the same order as `entries()`.
+    #[inline]
+    pub fn solution_indices(&self) -> Vec<usize> {
+        self.entries.iter().map(|entry| entry.solution_index).collect()
+    }
+
+    /// Returns the count of the selected solutions.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the count of sequences covered by the selected solutions.
+    /// It's equal to `PcPathResults::count_succeed()`.
+    #[inline]
+    pub fn count_covered(&self) -> u64 {
+        self.count_covered
+    }
+}
+
+/// Returns the count of bits that are set in `bits` and still uncovered.
+#[inline]
+fn count_intersection(bits: &BitSlice, uncovered: &BitSlice) -> usize {
+    bits.iter_ones().filter(|&index| uncovered[index]).count()
+}
+
+#[inline]
+fn remove_covered(uncovered: &mut BitVec, bits: &BitSlice) {
+    for index in bits.iter_ones() {
+        uncovered.set(index, false);
+    }
+}
+
+fn select_greedy(results: &PcPathResults, targets: &BitVec) -> Vec<usize> {
+    let mut uncovered = targets.clone();
+    let mut selected = Vec::<usize>::new();
+
+    while uncovered.any() {
+        let best = (0..results.count_solutions())
+            .map(|index| (count_intersection(results.coverage(index), &uncovered), index))
+            // Prefer the smaller index if the counts are the same.
+            .max_by(|(left_count, left_index), (right_count, right_index)| {
+                left_count.cmp(right_count).then(right_index.cmp(left_index))
+            })
+            .map(|(_, index)| index)
+            .unwrap();
+        remove_covered(&mut uncovered, results.coverage(best));
+        selected.push(best);
+    }
+
+    // Removes solutions whose sequences are all covered by the others, starting with the latest selected.
+    let mut index = selected.len();
+    while 0 < index {
+        index -= 1;
+        let mut others = BitVec::<usize, Lsb0>::repeat(false, targets.len());
+        for (other, &solution_index) in selected.iter().enumerate() {
+            if other != index {
+                others |= results.coverage(solution_index);
+            }
+        }
+        if results.coverage(selected[index]).iter_ones().all(|sequence| others[sequence]) {
+            selected.remove(index);
+        }
+    }
+
+    selected
+}
+
+struct ExactSearcher<'a> {
+    results: &'a PcPathResults,
+    covering_solutions: Vec<Vec<usize>>,
+    max_covered: usize,
+    best: Vec<usize>,
+}
+
+impl ExactSearcher<'_> {
+    fn search(&mut self, uncovered: &BitVec, selected: &mut Vec<usize>) {
+        let count_uncovered = uncovered.count_ones();
+        if count_uncovered == 0 {
+            if selected.len() < self.best.len() {
+                self.best = selected.clone();
+            }
+            return;
+        }
+
+        // Lower bound: each additional solution covers at most `max_covered` sequences.
+        let lower_bound = selected.len() + count_uncovered.div_ceil(self.max_covered);
+        if self.best.len() <= lower_bound {
+            return;
+        }
+
+        // Branches on the solutions covering the sequence with the fewest candidates.
+        let sequence = uncovered.iter_ones()
+            .min_by_key(|&sequence| self.covering_solutions[sequence].len())
+            .unwrap();
+
+        let mut candidates = self.covering_solutions[sequence].iter()
+            .map(|&index| (count_intersection(self.results.coverage(index), uncovered), index))
+            .collect::<Vec<_>>();
+        candidates.sort_by(|left, right| right.0.cmp(&left.0).then(left.1.cmp(&right.1)));
+
+        for (_, index) in candidates {
+            let mut next_uncovered = uncovered.clone();
+            remove_covered(&mut next_uncovered, self.results.coverage(index));
+            selected.push(index);
+            self.search(&next_uncovered, selected);
+            selected.pop();
+        }
+    }
+}
+
+fn select_exact(results: &PcPathResults, targets: &BitVec) -> Vec<usize> {
+    let mut covering_solutions = vec![Vec::<usize>::new(); targets.len()];
+    for index in 0..results.count_solutions() {
+        for sequence in results.coverage(index).iter_ones() {
+            covering_solutions[sequence].push(index);
+        }
+    }
+
+    let max_covered = (0..results.count_solutions())
+        .map(|index| results.coverage(index).count_ones())
+        .max()
+        .unwrap_or(0);
+
+    // The greedy result is the initial upper bound.
+    let mut searcher = ExactSearcher {
+        results,
+        covering_solutions,
+        max_covered,
+        best: select_greedy(results, targets),
+    };
+    searcher.search(targets, &mut Vec::new());
+    searcher.best
+}
+
+impl PcPathResults {
+    /// Returns a set of solutions that together cover every sequence for which a PC is possible.
+    /// This is what players memorize for a setup.
+    ///
+    /// See `CoverStrategy` for the trade-offs of each strategy.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris::prelude::{Board64, MoveRules, AllowMove};
+    /// use bitris_commands::{ClippedBoard, Pattern, PatternElement, ShapeCounter};
+    /// use bitris_commands::pc_path::{CoverStrategy, PcPathExecutor};
+    ///
+    /// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    ///
+    /// let board = Board64::from_str("
+    ///     XXXX....XX
+    ///     XXXXX..XXX
+    ///     XXXXXXXXXX
+    ///     XXXXX..XXX
+    /// ").expect("Failed to create a board");
+    /// let clipped_board = ClippedBoard::try_new(board, 4).expect("Failed to clip");
+    ///
+    /// let pattern = Pattern::try_from(vec![
+    ///     PatternElement::Permutation(ShapeCounter::one_of_each(), 3),
+    /// ]).expect("Failed to create a pattern");
+    ///
+    /// let executor = PcPathExecutor::try_new(&move_rules, clipped_board, &pattern, true)
+    ///     .expect("Failed to create an executor");
+    /// let results = executor.execute();
+    ///
+    /// let cover = results.minimal_cover(CoverStrategy::Exact);
+    /// assert_eq!(cover.count_covered(), results.count_succeed());
+    /// assert!(cover.len() <= results.minimal_cover(CoverStrategy::Greedy).len());
+    ///
+    /// for entry in cover.entries() {
+    ///     assert_eq!(entry.count_covered, results.count_covered(entry.solution_index));
+    /// }
+    /// ```
+    pub fn minimal_cover(&self, strategy: CoverStrategy) -> SolutionCover {
+        let mut targets = BitVec::<usize, Lsb0>::repeat(false, self.count_keys());
+        for index in 0..self.count_solutions() {
+            targets |= self.coverage(index);
+        }
+
+        let mut selected = match strategy {
+            CoverStrategy::Greedy => select_greedy(self, &targets),
+            CoverStrategy::Exact => select_exact(self, &targets),
+        };
+        selected.sort_by(|&left, &right| {
+            self.count_covered(right).cmp(&self.count_covered(left)).then(left.cmp(&right))
+        });
+
+        let mut uncovered = targets.clone();
+        let entries = selected.into_iter()
+            .map(|solution_index| {
+                let coverage = self.coverage(solution_index);
+                let count_attributed = count_intersection(coverage, &uncovered) as u64;
+                remove_covered(&mut uncovered, coverage);
+                CoverEntry {
+                    solution_index,
+                    count_covered: coverage.count_ones() as u64,
+                    count_attributed,
+                }
+            })
+            .collect();
+
+        SolutionCover {
+            entries,
+            count_covered: targets.count_ones() as u64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitris::prelude::*;
+    use itertools::Itertools;
+
+    use crate::pc_path::{CoverStrategy, PcPathResults, PcSolution, PlacedPiece};
+    use crate::ShapeSequence;
+
+    // Makes results where the solutions cover the given sequence indices.
+    fn make_results(count_sequences: usize, coverages: Vec<Vec<usize>>) -> PcPathResults {
+        let sequences = Shape::all_iter()
+            .cartesian_product(Shape::all_iter())
+            .take(count_sequences)
+            .map(|(first, second)| ShapeSequence::new(vec![first, second]))
+            .collect_vec();
+        let mut results = PcPathResults::new(sequences);
+        for (x, coverage) in coverages.into_iter().enumerate() {
+            let placement = Shape::I.with(Orientation::East).with(bl(x as i32, 0));
+            let solution = PcSolution::new(
+                vec![PlacedPiece::new(Shape::I, placement.locations())],
+                vec![placement],
+            );
+            let index = results.insert_solution(solution);
+            for sequence in coverage {
+                results.cover(index, sequence);
+            }
+        }
+        results
+    }
+
+    #[test]
+    fn greedy_is_not_minimal() {
+        let results = make_results(14, vec![
+            (0..7).collect(),
+            (7..14).collect(),
+            vec![0, 1, 2, 3, 7, 8, 9, 10],
+            vec![4, 5, 11, 12],
+            vec![6, 13],
+        ]);
+
+        let greedy = results.minimal_cover(CoverStrategy::Greedy);
+        assert_eq!(greedy.solution_indices(), vec![2, 3, 4]);
+        assert_eq!(greedy.count_covered(), 14);
+
+        let exact = results.minimal_cover(CoverStrategy::Exact);
+        assert_eq!(exact.solution_indices(), vec![0, 1]);
+        assert_eq!(exact.count_covered(), 14);
+        assert_eq!(exact.entries()[0].count_covered, 7);
+        assert_eq!(exact.entries()[0].count_attributed, 7);
+    }
+
+    #[test]
+    fn redundant_solutions_are_removed() {
+        let results = make_results(6, vec![
+            vec![0, 1, 2, 3],
+            vec![0, 1, 4],
+            vec![2, 3, 5],
+        ]);
+
+        let greedy = results.minimal_cover(CoverStrategy::Greedy);
+        assert_eq!(greedy.solution_indices(), vec![1, 2]);
+
+        let entries = greedy.entries();
+        assert_eq!(entries[0].count_attributed + entries[1].count_attributed, 6);
+    }
+
+    #[test]
+    fn uncoverable_sequences_are_ignored() {
+        let results = make_results(4, vec![vec![0], vec![0, 1]]);
+        for strategy in [CoverStrategy::Greedy, CoverStrategy::Exact] {
+            let cover = results.minimal_cover(strategy);
+            assert_eq!(cover.solution_indices(), vec![1]);
+            assert_eq!(cover.count_covered(), 2);
+        }
+
+        let results = make_results(4, vec![]);
+        assert!(results.minimal_cover(CoverStrategy::Exact).is_empty());
+    }
+}
@@ -5,7 +5,7 @@ use bitris::srs::SrsKickTable;
 use itertools::Itertools;
 use thiserror::Error;
 
-use crate::{ClippedBoard, Pattern, PatternCreationError, PatternElement, ShapeOrder};
+use crate::{ClippedBoard, Pattern, PatternCreationError, PatternElement, RotationSystem180, ShapeOrder, SpawnRule};
 use crate::pc_possible::{PcPossibleBulkExecutor, PcPossibleExecutorBulkCreationError};
 
 /// A collection of errors that occur when making the executor.
@@ -17,16 +17,20 @@ pub enum PcPossibleExecutorCreationError {
     ShortOrderDimension,
     #[error("Board height exceeds the upper limit. Up to 56 are supported.")]
     BoardIsTooHigh,
+    #[error("The stack blocks the spawn.")]
+    SpawnIsBlocked,
 }
 
 /// The binder to hold and tie settings for `PcPossibleExecutor`.
 #[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
-pub struct PcPossibleExecutorBinder<T: RotationSystem> {
+pub struct PcPossibleExecutorBinder<T: RotationSystem180> {
     pub rotation_system: Rc<T>,
     pub allow_move: AllowMove,
     pub clipped_board: ClippedBoard,
     pub shape_order: Rc<ShapeOrder>,
     pub allows_hold: bool,
+    pub spawn_rule: SpawnRule,
+    pub max_inputs: Option<u32>,
 }
 
 impl PcPossibleExecutorBinder<SrsKickTable> {
@@ -36,7 +40,7 @@ impl PcPossibleExecutorBinder<SrsKickTable> {
     }
 }
 
-impl<T: RotationSystem> PcPossibleExecutorBinder<T> {
+impl<T: RotationSystem180> PcPossibleExecutorBinder<T> {
     /// Making the executor with default.
     ///
     /// The default values are as follows:
@@ -46,6 +50,8 @@ impl<T: RotationSystem> PcPossibleExecutorBinder<T> {
     ///   + board: blank
     ///   + height: 4 lines
     ///   + allows hold: yes
+    ///   + spawn rule: above the height (see `SpawnRule::default()`)
+    ///   + max inputs: unlimited (see `InputLimitedMoves`)
     pub fn default(rotation_system: Rc<T>) -> Self {
         Self {
             rotation_system,
@@ -53,6 +59,8 @@ impl<T: RotationSystem> PcPossibleExecutorBinder<T> {
             clipped_board: ClippedBoard::try_new(Board64::blank(), 4).unwrap(),
             shape_order: Rc::from(ShapeOrder::new(vec![])),
             allows_hold: true,
+            spawn_rule: SpawnRule::default(),
+            max_inputs: None,
         }
     }
 
@@ -81,6 +89,7 @@ impl<T: RotationSystem> PcPossibleExecutorBinder<T> {
                     FromError::UnexpectedBoardSpaces => ToError::UnexpectedBoardSpaces,
                     FromError::ShortPatternDimension => ToError::ShortOrderDimension,
                     FromError::BoardIsTooHigh => ToError::BoardIsTooHigh,
+                    FromError::SpawnIsBlocked => ToError::SpawnIsBlocked,
                 }
             })
     }
@@ -91,7 +100,7 @@ impl<T: RotationSystem> PcPossibleExecutorBinder<T> {
             self.clipped_board,
             pattern,
             self.allows_hold,
-        )
+        ).and_then(|executor| executor.with_max_inputs(self.max_inputs).try_with_spawn_rule(self.spawn_rule))
     }
 }
 
@@ -103,7 +112,7 @@ mod tests {
 
     use bitris::prelude::*;
 
-    use crate::{ClippedBoard, ShapeOrder};
+    use crate::{ClippedBoard, ShapeOrder, SpawnRow, SpawnRule};
     use crate::pc_possible::{PcPossibleExecutorBinder, PcPossibleExecutorCreationError};
 
     #[test]
@@ -165,5 +174,12 @@ mod tests {
         binder.shape_order = Rc::new(ShapeOrder::new(vec![Z]));
 
         assert_eq!(binder.try_execute().unwrap_err(), ShortOrderDimension);
+
+        binder.shape_order = Rc::new(ShapeOrder::new(vec![
+            I, O, T, Z, S, J, L,
+        ]));
+        binder.spawn_rule = SpawnRule::default().with_row(SpawnRow::Fixed(1));
+
+        assert_eq!(binder.try_execute().unwrap_err(), SpawnIsBlocked);
     }
 }
@@ -1,19 +1,25 @@
+use std::io::{Read, Write};
 use std::rc::Rc;
 
 use bitris::prelude::*;
 use bitris::srs::SrsKickTable;
 
-use crate::{ClippedBoard, Pattern, PatternElement, ShapeCounter};
-use crate::pc_possible::{ExecuteInstruction, PcPossibleBulkExecutor, PcPossibleExecutorBulkCreationError, PcResults};
+use crate::{ClippedBoard, Pattern, PatternElement, RotationSystem180, ShapeCounter, SpawnRule, TSpinKind};
+use crate::pc_possible::{DEFAULT_TRANSPOSITION_CAPACITY, ExecuteInstruction, ExecuteProgress, LineClearConstraints, PcCheckpointError, PcPossibleBulkExecutor, PcPossibleExecutorBulkCreationError, PcResults, StopConditions};
 
 /// The binder to hold and tie settings for `PcPossibleBulkExecutor`.
 #[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
-pub struct PcPossibleBulkExecutorBinder<T: RotationSystem> {
+pub struct PcPossibleBulkExecutorBinder<T: RotationSystem180> {
     pub rotation_system: Rc<T>,
     pub allow_move: AllowMove,
     pub clipped_board: ClippedBoard,
     pub pattern: Rc<Pattern>,
     pub allows_hold: bool,
+    pub transposition_capacity: usize,
+    pub line_clear_constraints: LineClearConstraints,
+    pub required_t_spins: Vec<TSpinKind>,
+    pub spawn_rule: SpawnRule,
+    pub max_inputs: Option<u32>,
 }
 
 impl PcPossibleBulkExecutorBinder<SrsKickTable> {
@@ -23,7 +29,7 @@ impl PcPossibleBulkExecutorBinder<SrsKickTable> {
     }
 }
 
-impl<T: RotationSystem> PcPossibleBulkExecutorBinder<T> {
+impl<T: RotationSystem180> PcPossibleBulkExecutorBinder<T> {
     /// Making the executor with default.
     ///
     /// The default values are as follows:
@@ -33,6 +39,11 @@ impl<T: RotationSystem> PcPossibleBulkExecutorBinder<T> {
     ///   + height: 4 lines
     ///   + pattern: factorial of all shapes (like `*p7`)
     ///   + allows hold: yes
+    ///   + transposition capacity: `DEFAULT_TRANSPOSITION_CAPACITY` states (per thread)
+    ///   + line clear constraints: unconstrained
+    ///   + required T-spins: none
+    ///   + spawn rule: above the height (see `SpawnRule::default()`)
+    ///   + max inputs: unlimited (see `InputLimitedMoves`)
     pub fn default(rotation_system: Rc<T>) -> Self {
         Self {
             rotation_system,
@@ -42,6 +53,11 @@ impl<T: RotationSystem> PcPossibleBulkExecutorBinder<T> {
                 PatternElement::Factorial(ShapeCounter::one_of_each()),
             ]).unwrap()),
             allows_hold: true,
+            transposition_capacity: DEFAULT_TRANSPOSITION_CAPACITY,
+            line_clear_constraints: LineClearConstraints::default(),
+            required_t_spins: Vec::new(),
+            spawn_rule: SpawnRule::default(),
+            max_inputs: None,
         }
     }
 
@@ -59,13 +75,76 @@ impl<T: RotationSystem> PcPossibleBulkExecutorBinder<T> {
         Ok(executor.execute_with_early_stopping(early_stopping))
     }
 
+    // See `PcPossibleBulkExecutor::{try_new, execute_with_progress}` for more details.
+    pub fn try_execute_with_progress(&self, stop_conditions: &StopConditions, observer: impl FnMut(&ExecuteProgress) -> ExecuteInstruction) -> Result<PcResults, PcPossibleExecutorBulkCreationError> {
+        let move_rules = MoveRules::new(self.rotation_system.as_ref(), self.allow_move);
+        let executor = self.try_bind(&move_rules)?;
+        Ok(executor.execute_with_progress(stop_conditions, observer))
+    }
+
+    // See `PcPossibleBulkExecutor::{try_new, save_checkpoint}` for more details.
+    pub fn try_save_checkpoint(&self, results: &PcResults, writer: impl Write) -> Result<(), PcCheckpointError> {
+        let move_rules = MoveRules::new(self.rotation_system.as_ref(), self.allow_move);
+        let executor = self.try_bind(&move_rules)?;
+        executor.save_checkpoint(results, writer)
+    }
+
+    // See `PcPossibleBulkExecutor::{try_new, try_resume}` for more details.
+    pub fn try_resume(&self, reader: impl Read) -> Result<PcResults, PcCheckpointError> {
+        self.try_resume_with_early_stopping(reader, |_| ExecuteInstruction::Continue)
+    }
+
+    // See `PcPossibleBulkExecutor::{try_new, try_resume_with_early_stopping}` for more details.
+    pub fn try_resume_with_early_stopping(&self, reader: impl Read, early_stopping: impl Fn(&PcResults) -> ExecuteInstruction) -> Result<PcResults, PcCheckpointError> {
+        let move_rules = MoveRules::new(self.rotation_system.as_ref(), self.allow_move);
+        let executor = self.try_bind(&move_rules)?;
+        executor.try_resume_with_early_stopping(reader, early_stopping)
+    }
+
     fn try_bind<'a>(&'a self, move_rules: &'a MoveRules<T>) -> Result<PcPossibleBulkExecutor<T>, PcPossibleExecutorBulkCreationError> {
         PcPossibleBulkExecutor::try_new(
             move_rules,
             self.clipped_board,
             self.pattern.as_ref(),
             self.allows_hold,
-        )
+        ).and_then(|executor| {
+            executor
+                .with_transposition_capacity(self.transposition_capacity)
+                .with_line_clear_constraints(self.line_clear_constraints.clone())
+                .with_required_t_spins(&self.required_t_spins)
+                .with_max_inputs(self.max_inputs)
+                .try_with_spawn_rule(self.spawn_rule)
+        })
+    }
+}
+
+impl<T: RotationSystem180 + Sync> PcPossibleBulkExecutorBinder<T> {
+    // See `PcPossibleBulkExecutor::{try_new, execute_in_parallel}` for more details.
+    pub fn try_execute_in_parallel(&self, threads: usize) -> Result<PcResults, PcPossibleExecutorBulkCreationError> {
+        let move_rules = MoveRules::new(self.rotation_system.as_ref(), self.allow_move);
+        let executor = self.try_bind(&move_rules)?;
+        Ok(executor.execute_in_parallel(threads))
+    }
+
+    // See `PcPossibleBulkExecutor::{try_new, execute_in_parallel_with_early_stopping}` for more details.
+    pub fn try_execute_in_parallel_with_early_stopping(&self, threads: usize, early_stopping: impl Fn(&PcResults) -> ExecuteInstruction + Sync) -> Result<PcResults, PcPossibleExecutorBulkCreationError> {
+        let move_rules = MoveRules::new(self.rotation_system.as_ref(), self.allow_move);
+        let executor = self.try_bind(&move_rules)?;
+        Ok(executor.execute_in_parallel_with_early_stopping(threads, early_stopping))
+    }
+
+    // See `PcPossibleBulkExecutor::{try_new, execute_in_parallel_with_progress}` for more details.
+    pub fn try_execute_in_parallel_with_progress(&self, threads: usize, stop_conditions: &StopConditions, observer: impl FnMut(&ExecuteProgress) -> ExecuteInstruction + Send) -> Result<PcResults, PcPossibleExecutorBulkCreationError> {
+        let move_rules = MoveRules::new(self.rotation_system.as_ref(), self.allow_move);
+        let executor = self.try_bind(&move_rules)?;
+        Ok(executor.execute_in_parallel_with_progress(threads, stop_conditions, observer))
+    }
+
+    // See `PcPossibleBulkExecutor::{try_new, try_resume_in_parallel_with_early_stopping}` for more details.
+    pub fn try_resume_in_parallel_with_early_stopping(&self, threads: usize, reader: impl Read, early_stopping: impl Fn(&PcResults) -> ExecuteInstruction + Sync) -> Result<PcResults, PcCheckpointError> {
+        let move_rules = MoveRules::new(self.rotation_system.as_ref(), self.allow_move);
+        let executor = self.try_bind(&move_rules)?;
+        executor.try_resume_in_parallel_with_early_stopping(threads, reader, early_stopping)
     }
 }
 
@@ -78,7 +157,7 @@ mod tests {
     use bitris::prelude::*;
 
     use crate::{ClippedBoard, Pattern, PatternElement, ShapeCounter};
-    use crate::pc_possible::PcPossibleBulkExecutorBinder;
+    use crate::pc_possible::{LineClearConstraints, PcPossibleBulkExecutorBinder};
 
     #[test]
     fn reuse() {
@@ -109,5 +188,16 @@ mod tests {
         ]).unwrap());
         let result = binder.try_execute().unwrap();
         assert_eq!(result.count_succeed(), 4088);
+
+        let result_in_parallel = binder.try_execute_in_parallel(4).unwrap();
+        assert_eq!(result_in_parallel, result);
+
+        binder.transposition_capacity = 0;
+        let result_without_memo = binder.try_execute().unwrap();
+        assert_eq!(result_without_memo, result);
+
+        binder.line_clear_constraints = LineClearConstraints::no_line_clear_until_last();
+        let result_constrained = binder.try_execute().unwrap();
+        assert!(result_constrained.count_succeed() < result.count_succeed());
     }
 }
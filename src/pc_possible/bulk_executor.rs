@@ -1,17 +1,26 @@
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
 use bitris::prelude::*;
 use fxhash::FxHashSet;
 use thiserror::Error;
 
+use crate::input_moves::generate_moves;
+use crate::kick_table::is_mirror_symmetric;
 use crate::internals::{FuzzyShape, FuzzyShapeOrder};
 use crate::pc_possible::bulk_executor::ExecuteInstruction::Continue;
-use crate::pc_possible::{Buffer, PcResults, VerticalParity};
-use crate::{ClippedBoard, ForEachVisitor, OrderCursor, Pattern, PopOp, ShapeOrder, ShapeSequence};
+use crate::pc_possible::{encode_configuration, fingerprint, read_checkpoint, write_checkpoint, Buffer, CompactPcResults, ExecuteProgress, LineClearChecker, LineClearConstraints, PcCheckpointError, PcResults, Proof, RegionPruner, ResultsSink, SearchStats, StopConditions, TranspositionTable, VerticalParity, COUNTS_STATS};
+use crate::{ClippedBoard, ForEachVisitor, Mirror, OrderCursor, Pattern, PopOp, RotationSystem180, ShapeOrder, ShapeSequence, SpawnRow, SpawnRule, TSpinDetector, TSpinKind};
 
-struct Visitor<'a> {
-    result: &'a mut PcResults,
+struct Visitor<'a, R: ResultsSink> {
+    result: &'a mut R,
+    stats: &'a mut SearchStats,
 }
 
-impl<'a> ForEachVisitor<[FuzzyShape]> for Visitor<'a> {
+impl<'a, R: ResultsSink> ForEachVisitor<[FuzzyShape]> for Visitor<'a, R> {
     #[inline]
     fn visit(&mut self, fuzzy_shapes: &[FuzzyShape]) {
         let fuzzy_shape_order = FuzzyShapeOrder::new(fuzzy_shapes.to_vec());
@@ -19,11 +28,14 @@ impl<'a> ForEachVisitor<[FuzzyShape]> for Visitor<'a> {
     }
 }
 
-impl<'a> ForEachVisitor<[Shape]> for Visitor<'a> {
+impl<'a, R: ResultsSink> ForEachVisitor<[Shape]> for Visitor<'a, R> {
     #[inline]
     fn visit(&mut self, shapes: &[Shape]) {
         let order = ShapeSequence::new(shapes.to_vec());
-        self.result.accept_if_present(&order, true);
+        let is_pending = COUNTS_STATS && self.result.get(&order).is_none();
+        if self.result.accept_if_present(&order, true) && is_pending {
+            self.stats.reused_by_hold += 1;
+        }
     }
 }
 
@@ -37,11 +49,49 @@ struct SearchingState {
 
     height: u32,
 
+    // The progress toward the constraints. See `PcPossibleBulkExecutor::progress_key()`.
+    progress: u64,
+
     first: Option<Shape>,
 }
 
+/// The progress toward the constraints, carried along the placements.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+struct SearchProgress {
+    // The rows of the initial board not yet cleared. See `LineClearChecker`.
+    rows: u64,
+
+    // Whether one of the required T-spins has been done.
+    has_t_spin: bool,
+}
+
+/// Holds what is known during PC possible search.
+struct SearchMemo {
+    // The states already visited while searching the current sequence. Cleared for each sequence.
+    visited_states: FxHashSet<SearchingState>,
+
+    // The proven results shared across sequences.
+    table: TranspositionTable,
+
+    // Counted only if `COUNTS_STATS` is `true`.
+    stats: SearchStats,
+}
+
+impl SearchMemo {
+    #[inline]
+    fn new(transposition_capacity: usize) -> Self {
+        Self {
+            visited_states: FxHashSet::default(),
+            table: TranspositionTable::with_capacity(transposition_capacity),
+            stats: SearchStats::default(),
+        }
+    }
+}
+
+/// Returns `false` if the board contains an area that cannot be filled by pieces.
+/// Columns fully blocked from top to bottom divide the field into areas; each area must have spaces in multiples of 4.
 #[inline]
-fn validate_board(clipped: &ClippedBoard) -> bool {
+pub(crate) fn validate_board(clipped: &ClippedBoard) -> bool {
     let wall = (1 << clipped.height()) - 1;
     let mut frees_sum = clipped.height() - clipped.board_ref().cols[0].count_ones();
 
@@ -71,17 +121,30 @@ pub enum PcPossibleExecutorBulkCreationError {
     ShortPatternDimension,
     #[error("Board height exceeds the upper limit. Up to 56 are supported.")]
     BoardIsTooHigh,
+    #[error("The stack blocks the spawn.")]
+    SpawnIsBlocked,
 }
 
+/// The default count of states memorized across sequences. See `PcPossibleBulkExecutor::with_transposition_capacity()`.
+pub const DEFAULT_TRANSPOSITION_CAPACITY: usize = 1 << 16;
+
 /// The executor to find PC possibles.
 #[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
-pub struct PcPossibleBulkExecutor<'a, T: RotationSystem> {
+pub struct PcPossibleBulkExecutor<'a, T: RotationSystem180> {
     move_rules: &'a MoveRules<'a, T>,
     clipped_board: ClippedBoard,
     pattern: &'a Pattern,
     allows_hold: bool,
     has_extra_shapes: bool,
-    spawn_position: BlPosition,
+    spawn_rule: SpawnRule,
+    transposition_capacity: usize,
+    line_clear_checker: LineClearChecker,
+    required_t_spins: Vec<TSpinKind>,
+    max_inputs: Option<u32>,
+    mirror_reduction: bool,
+
+    // Set by `PcRegionBulkExecutor` if the board is divided by walls.
+    region_pruner: Option<Arc<RegionPruner>>,
 }
 
 /// A collection of statements that instruct execution to continue/stop.
@@ -92,7 +155,7 @@ pub enum ExecuteInstruction {
     Stop,
 }
 
-impl<'a, T: RotationSystem> PcPossibleBulkExecutor<'a, T> {
+impl<'a, T: RotationSystem180> PcPossibleBulkExecutor<'a, T> {
     /// Make PcPossibleBulkExecutor.
     ///
     /// Returns `Err()` if the setting is incorrect or restricted.
@@ -155,19 +218,170 @@ impl<'a, T: RotationSystem> PcPossibleBulkExecutor<'a, T> {
 
         let has_extra_shapes = clipped_board.spaces() / 4 < dimension;
 
-        // Spawn above the height so that it is not stuck when harddrop only.
-        let spawn_position = bl(5, clipped_board.height() as i32 + 4);
-
         Ok(Self {
             move_rules,
             clipped_board,
             pattern,
             allows_hold,
             has_extra_shapes,
-            spawn_position,
+            spawn_rule: SpawnRule::default(),
+            transposition_capacity: DEFAULT_TRANSPOSITION_CAPACITY,
+            line_clear_checker: LineClearConstraints::default().to_checker(clipped_board),
+            required_t_spins: Vec::new(),
+            max_inputs: None,
+            mirror_reduction: false,
+            region_pruner: None,
         })
     }
 
+    /// Sets the maximum count of states memorized across sequences. If 0, nothing is memorized.
+    ///
+    /// The proven result of a state, identified by the board and the remaining order, is reused when the same state is reached from another sequence.
+    /// When the capacity is reached, the states not used recently are dropped.
+    /// The default is `DEFAULT_TRANSPOSITION_CAPACITY`. In parallel, each thread has its own memo of this capacity.
+    #[inline]
+    pub fn with_transposition_capacity(mut self, capacity: usize) -> Self {
+        self.transposition_capacity = capacity;
+        self
+    }
+
+    /// Sets the constraints on line clears. Only the sequences that can take a PC satisfying them succeed.
+    /// The default is unconstrained. See `LineClearConstraints` for more details.
+    #[inline]
+    pub fn with_line_clear_constraints(mut self, constraints: LineClearConstraints) -> Self {
+        self.line_clear_checker = constraints.to_checker(self.clipped_board);
+        self
+    }
+
+    /// Requires one of the T-spins in the PC. Only the sequences that can take a PC containing them succeed.
+    /// The default is empty, which requires nothing. See `TSpinDetector` for how T-spins are detected.
+    #[inline]
+    pub fn with_required_t_spins(mut self, kinds: &[TSpinKind]) -> Self {
+        let mut kinds = kinds.to_vec();
+        kinds.sort();
+        kinds.dedup();
+        self.required_t_spins = kinds;
+        self
+    }
+
+    /// Sets the placements where pieces spawn. The default spawns above the height. See `SpawnRule` for more details.
+    ///
+    /// Returns `Err(PcPossibleExecutorBulkCreationError::SpawnIsBlocked)` if the spawn of any shape is blocked on the initial board.
+    /// If the spawn is blocked by the stack during the search, the piece cannot be placed.
+    /// In 20G, the moves are searched by inputs, since the moves of bitris do not drop the piece after every move.
+    pub fn try_with_spawn_rule(mut self, spawn_rule: SpawnRule) -> Result<Self, PcPossibleExecutorBulkCreationError> {
+        let height = self.clipped_board.height();
+        if Shape::all_iter().any(|shape| spawn_rule.is_blocked(shape, self.clipped_board.board_ref(), height)) {
+            return Err(PcPossibleExecutorBulkCreationError::SpawnIsBlocked);
+        }
+        self.spawn_rule = spawn_rule;
+        Ok(self)
+    }
+
+    /// Limits each placement to the count of inputs before the harddrop. If `None`, any reachable placement is accepted.
+    /// The default is `None`. See `InputLimitedMoves` for how inputs are counted.
+    #[inline]
+    pub fn with_max_inputs(mut self, max_inputs: Option<u32>) -> Self {
+        self.max_inputs = max_inputs;
+        self
+    }
+
+    /// Enables the mirror symmetry reduction. The default is disabled.
+    ///
+    /// If the board is mirror-symmetric, a mirrored sequence (L↔J and S↔Z swapped) has the same result.
+    /// So when a sequence is searched, the result is also recorded to its mirrored sequence, and the mirrored one is not searched.
+    /// It has no effect unless the moves are also symmetric. See `reduces_mirror()` for the conditions.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris::prelude::*;
+    /// use bitris_commands::{ClippedBoard, KickTable, Pattern, PatternElement, ShapeCounter, SRS_KICK_TABLE};
+    /// use bitris_commands::pc_possible::PcPossibleBulkExecutor;
+    ///
+    /// let board = Board64::from_str("
+    ///     XX......XX
+    ///     XX......XX
+    /// ").expect("Failed to create a board");
+    /// let clipped_board = ClippedBoard::try_new(board, 2).expect("Failed to clip");
+    ///
+    /// let pattern = Pattern::try_from(vec![
+    ///     PatternElement::Permutation(ShapeCounter::one_of_each(), 4),
+    /// ]).expect("Failed to create a pattern");
+    ///
+    /// // SRS is not symmetric because of the I kicks.
+    /// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    /// let executor = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true)
+    ///     .expect("Failed to create an executor");
+    /// assert!(!executor.with_mirror_reduction(true).reduces_mirror());
+    ///
+    /// // SRS with the I kicks only at the rotation center is symmetric.
+    /// let (others, rest) = SRS_KICK_TABLE.split_once("[I]").unwrap();
+    /// let (_, o) = rest.split_once("[O]").unwrap();
+    /// let i = "
+    ///     N->E = (1, 0)
+    ///     E->N = (-1, 0)
+    ///     E->S = (0, -1)
+    ///     S->E = (0, 1)
+    ///     S->W = (-1, 0)
+    ///     W->S = (1, 0)
+    ///     W->N = (0, 1)
+    ///     N->W = (0, -1)
+    /// ";
+    /// let kick_table = KickTable::from_str(&format!("{others}[I]{i}[O]{o}")).expect("Failed to parse");
+    /// let move_rules = MoveRules::new(&kick_table, AllowMove::Softdrop);
+    ///
+    /// let executor = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true)
+    ///     .expect("Failed to create an executor");
+    /// let reduced = executor.clone().with_mirror_reduction(true);
+    /// assert!(reduced.reduces_mirror());
+    ///
+    /// assert_eq!(reduced.execute(), executor.execute());
+    /// ```
+    pub fn with_mirror_reduction(mut self, enabled: bool) -> Self {
+        // The move rules do not change after the creation, so the kicks are checked only once here.
+        self.mirror_reduction = enabled && is_mirror_symmetric(self.move_rules.rotation_system);
+        self
+    }
+
+    /// Prunes the boards on which a region divided by walls is never filled. See `PcRegionBulkExecutor`.
+    #[inline]
+    pub(crate) fn with_region_pruner(mut self, region_pruner: RegionPruner) -> Self {
+        self.region_pruner = Some(Arc::new(region_pruner));
+        self
+    }
+
+    #[inline]
+    pub fn clipped_board(&self) -> ClippedBoard {
+        self.clipped_board
+    }
+
+    /// Returns `true` if the mirror symmetry reduction is enabled and takes effect.
+    ///
+    /// It takes effect only if all the following hold, so that the moves on the mirrored board are mirrored:
+    ///   + The board is mirror-symmetric.
+    ///   + The rotation system is mirror-symmetric, including the order of kicks and the 180° kicks.
+    ///   + The pieces spawn above the height, facing north in the same column. The column does not matter since the space above is empty.
+    ///   + The pieces do not drop in 20G, since they drop at the spawn column.
+    ///   + The inputs are not limited, since the count of inputs depends on the spawn column.
+    #[inline]
+    pub fn reduces_mirror(&self) -> bool {
+        let spawn_column = self.spawn_rule.column(Shape::T);
+        self.mirror_reduction
+            && self.max_inputs.is_none()
+            && self.spawn_rule.row() == SpawnRow::AboveHeight
+            && !self.spawn_rule.is_20g()
+            && Shape::all_iter().all(|shape| {
+                self.spawn_rule.orientation(shape) == Orientation::North && self.spawn_rule.column(shape) == spawn_column
+            })
+            && self.clipped_board.mirror() == self.clipped_board
+    }
+
+    /// Returns the part of the progress that distinguishes searching states.
+    /// The rows never reach the highest bit since the height is up to 56, so it's used for the T-spin.
+    #[inline]
+    fn progress_key(&self, progress: SearchProgress) -> u64 {
+        self.line_clear_checker.state_key(progress.rows) | ((progress.has_t_spin as u64) << 63)
+    }
+
     /// Start the search for PC possible in bulk.
     pub fn execute(&self) -> PcResults {
         self.execute_with_early_stopping(move |_| Continue)
@@ -220,44 +434,362 @@ impl<'a, T: RotationSystem> PcPossibleBulkExecutor<'a, T> {
         early_stopping: impl Fn(&PcResults) -> ExecuteInstruction,
     ) -> PcResults {
         let sequences = self.pattern.to_sequences();
-        let infer_size = self.pattern.dim_shapes();
+        let results = self.new_results(&sequences);
+        self.search_sequences(sequences, results, early_stopping)
+    }
+
+    /// Start the search for PC possible in bulk, and returns the statistics of the search together.
+    /// Available only if the `stats` feature is enabled. See `SearchStats` for the counters.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris::prelude::{Board64, MoveRules, AllowMove};
+    /// use bitris_commands::{ClippedBoard, Pattern, PatternElement, ShapeCounter};
+    /// use bitris_commands::pc_possible::PcPossibleBulkExecutor;
+    ///
+    /// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    ///
+    /// let board = Board64::from_str("
+    ///     XXX.....XX
+    ///     XXX....XXX
+    ///     XXX...XXXX
+    ///     XXX....XXX
+    /// ").expect("Failed to create a board");
+    /// let clipped_board = ClippedBoard::try_new(board, 4).expect("Failed to clip");
+    ///
+    /// let pattern = Pattern::try_from(vec![
+    ///     PatternElement::Permutation(ShapeCounter::one_of_each(), 5),
+    /// ]).expect("Failed to create a pattern");
+    ///
+    /// let executor = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true)
+    ///     .expect("Failed to create an executor");
+    ///
+    /// let (results, stats) = executor.execute_with_stats();
+    /// assert_eq!(results, executor.execute());
+    /// assert!(0 < stats.nodes_expanded);
+    /// assert!(0 < stats.placements_generated);
+    /// assert!(0 < stats.reused_by_hold);
+    /// ```
+    #[cfg(feature = "stats")]
+    pub fn execute_with_stats(&self) -> (PcResults, SearchStats) {
+        let sequences = self.pattern.to_sequences();
+        let results = self.new_results(&sequences);
+        self.search_sequences_with_stats(sequences, results, move |_| Continue)
+    }
 
-        let mut results = PcResults::new(&sequences);
+    /// Returns the fingerprint of the configuration: the board, the height, the pattern, the move rules, the hold, the line clear constraints, the required T-spins, the spawn rule and the input limit.
+    /// The rotation system is identified by its kicks.
+    /// It's fixed across platforms and Rust versions, so it can be used to identify the checkpoints.
+    pub fn fingerprint(&self) -> u64 {
+        fingerprint(&self.configuration())
+    }
 
-        let mut visited_states = FxHashSet::<SearchingState>::default();
+    /// Returns the configuration as bytes. Checkpoints can only be loaded by executors with the same configuration.
+    fn configuration(&self) -> Vec<u8> {
+        encode_configuration(self.move_rules, self.clipped_board, self.pattern, self.allows_hold, self.line_clear_checker.constraints(), &self.required_t_spins, &self.spawn_rule, self.max_inputs)
+    }
+
+    /// Makes the results with all sequences pending, recording the hold of this executor.
+    #[inline]
+    fn new_results(&self, sequences: &Vec<ShapeSequence>) -> PcResults {
+        PcResults::new(sequences).with_allows_hold(self.allows_hold)
+    }
+
+    /// Saves the results as a checkpoint.
+    /// The checkpoint is compact because it holds only the configuration and the results (2 bits per sequence), not the sequences.
+    ///
+    /// It's intended to be called periodically from the closure of `execute_with_early_stopping()`.
+    /// Note that the writer is responsible for writing atomically, e.g. writing to a temporary file and renaming it.
+    pub fn save_checkpoint(&self, results: &PcResults, writer: impl Write) -> Result<(), PcCheckpointError> {
+        let sequences = self.pattern.to_sequences();
+        write_checkpoint(writer, &self.configuration(), &sequences, results)
+    }
+
+    /// Loads the results from a checkpoint saved by `save_checkpoint()`.
+    ///
+    /// Returns `Err(PcCheckpointError::ConfigurationMismatch)` if the checkpoint was saved with a different configuration.
+    pub fn load_checkpoint(&self, reader: impl Read) -> Result<PcResults, PcCheckpointError> {
+        let sequences = self.pattern.to_sequences();
+        read_checkpoint(reader, &self.configuration(), &sequences, self.new_results(&sequences))
+    }
+
+    /// Loads a checkpoint and searches only the sequences still pending.
+    /// See `try_resume_with_early_stopping()` for more details.
+    pub fn try_resume(&self, reader: impl Read) -> Result<PcResults, PcCheckpointError> {
+        self.try_resume_with_early_stopping(reader, move |_| Continue)
+    }
+
+    /// Loads a checkpoint and searches only the sequences still pending, with early stopping.
+    ///
+    /// Returns `Err()` without searching if the checkpoint cannot be loaded. See `load_checkpoint()` for more details.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris::prelude::{Board64, MoveRules, AllowMove};
+    /// use bitris_commands::{ClippedBoard, Pattern, PatternElement, ShapeCounter};
+    /// use bitris_commands::pc_possible::{ExecuteInstruction, PcCheckpointError, PcPossibleBulkExecutor};
+    ///
+    /// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    ///
+    /// let board = Board64::from_str("
+    ///     XXXX....XX
+    ///     XXXX...XXX
+    ///     XXXX..XXXX
+    ///     XXXX...XXX
+    /// ").expect("Failed to create a board");
+    /// let clipped_board = ClippedBoard::try_new(board, 4).expect("Failed to clip");
+    ///
+    /// let pattern = Pattern::try_from(vec![
+    ///     PatternElement::Permutation(ShapeCounter::one_of_each(), 4),
+    /// ]).expect("Failed to create a pattern");
+    ///
+    /// let executor = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true)
+    ///     .expect("Failed to create an executor");
+    ///
+    /// // Saves a checkpoint every 100 sequences searched, and is interrupted halfway.
+    /// // The closure is called once per sequence, and a sequence may accept several results, so the calls are counted.
+    /// // A file can be used instead of `Vec<u8>`.
+    /// let checkpoint = std::cell::RefCell::new(Vec::<u8>::new());
+    /// let searched = std::cell::Cell::new(0);
+    /// let interrupted = executor.execute_with_early_stopping(|results| {
+    ///     searched.set(searched.get() + 1);
+    ///     if searched.get() % 100 == 0 {
+    ///         let mut checkpoint = checkpoint.borrow_mut();
+    ///         checkpoint.clear();
+    ///         executor.save_checkpoint(results, &mut *checkpoint).expect("Failed to save");
+    ///     }
+    ///     if searched.get() < 300 { ExecuteInstruction::Continue } else { ExecuteInstruction::Stop }
+    /// });
+    /// assert!(0 < interrupted.count_pending());
+    ///
+    /// // Resumes from the checkpoint.
+    /// let checkpoint = checkpoint.into_inner();
+    /// let results = executor.try_resume(checkpoint.as_slice()).expect("Failed to resume");
+    /// assert_eq!(results, executor.execute());
+    ///
+    /// // Refuses to resume with another configuration.
+    /// let executor_without_hold = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, false)
+    ///     .expect("Failed to create an executor");
+    /// assert!(matches!(
+    ///     executor_without_hold.try_resume(checkpoint.as_slice()),
+    ///     Err(PcCheckpointError::ConfigurationMismatch),
+    /// ));
+    /// ```
+    pub fn try_resume_with_early_stopping(
+        &self,
+        reader: impl Read,
+        early_stopping: impl Fn(&PcResults) -> ExecuteInstruction,
+    ) -> Result<PcResults, PcCheckpointError> {
+        let sequences = self.pattern.to_sequences();
+        let results = read_checkpoint(reader, &self.configuration(), &sequences, self.new_results(&sequences))?;
+        Ok(self.search_sequences(sequences, results, early_stopping))
+    }
+
+    /// Start the search for PC possible in bulk, writing the results into `CompactPcResults`.
+    ///
+    /// The sequences are made from the ranks one by one, so neither the sequences nor the results are held as `ShapeSequence`.
+    /// It's suitable for huge patterns that `execute()` cannot hold in memory.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris::prelude::{Shape, Board64, MoveRules, AllowMove};
+    /// use bitris_commands::{ClippedBoard, Pattern, PatternElement, ShapeCounter};
+    /// use bitris_commands::pc_possible::PcPossibleBulkExecutor;
+    ///
+    /// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    ///
+    /// let board = Board64::from_str("
+    ///     XXX.....XX
+    ///     XXX....XXX
+    ///     XXX...XXXX
+    ///     XXX....XXX
+    /// ").expect("Failed to create a board");
+    /// let clipped_board = ClippedBoard::try_new(board, 4).expect("Failed to clip");
+    ///
+    /// let pattern = Pattern::try_from(vec![
+    ///     PatternElement::One(Shape::I),
+    ///     PatternElement::Permutation(ShapeCounter::one_of_each(), 4),
+    /// ]).expect("Failed to create a pattern");
+    ///
+    /// let executor = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true)
+    ///     .expect("Failed to create an executor");
+    ///
+    /// let results = executor.execute_compact();
+    /// assert_eq!(results.count_succeed(), 711);
+    /// assert_eq!(results.count_failed(), 129);
+    /// assert_eq!(results.to_pc_results(), executor.execute());
+    /// ```
+    pub fn execute_compact(&self) -> CompactPcResults {
+        self.execute_compact_with_early_stopping(move |_| Continue)
+    }
+
+    /// Start the search for PC possible in bulk, writing the results into `CompactPcResults`, with early stopping.
+    /// See `execute_compact()` and `execute_with_early_stopping()`.
+    pub fn execute_compact_with_early_stopping(
+        &self,
+        early_stopping: impl Fn(&CompactPcResults) -> ExecuteInstruction,
+    ) -> CompactPcResults {
+        let sequences = (0..self.pattern.len_shapes_vec())
+            .map(|rank| self.pattern.sequence_at(rank).unwrap());
+        let results = CompactPcResults::new(self.pattern).with_allows_hold(self.allows_hold);
+        self.search_sequences(sequences, results, early_stopping)
+    }
+
+    /// Searches the sequences whose results are not yet in `results`, which is made from the sequences of the pattern.
+    pub(crate) fn execute_pending(&self, results: PcResults) -> PcResults {
+        let sequences = self.pattern.to_sequences();
+        self.search_sequences(sequences, results.with_allows_hold(self.allows_hold), move |_| Continue)
+    }
+
+    /// Searches the sequences whose results are not yet in `results`.
+    fn search_sequences<R: ResultsSink>(
+        &self,
+        sequences: impl IntoIterator<Item=ShapeSequence>,
+        results: R,
+        early_stopping: impl FnMut(&R) -> ExecuteInstruction,
+    ) -> R {
+        self.search_sequences_with_stats(sequences, results, early_stopping).0
+    }
+
+    /// Searches the sequences whose results are not yet in `results`, and returns the statistics together.
+    fn search_sequences_with_stats<R: ResultsSink>(
+        &self,
+        sequences: impl IntoIterator<Item=ShapeSequence>,
+        mut results: R,
+        mut early_stopping: impl FnMut(&R) -> ExecuteInstruction,
+    ) -> (R, SearchStats) {
+        let infer_size = self.pattern.dim_shapes();
+
+        let mut memo = SearchMemo::new(self.transposition_capacity);
 
         for sequence in sequences {
-            if let Some(_) = results.get(&sequence) {
+            if results.get(&sequence).is_some() {
                 if early_stopping(&results) == ExecuteInstruction::Stop {
                     break;
                 }
                 continue;
             }
 
-            visited_states.clear();
+            memo.visited_states.clear();
 
             let order = sequence.to_shape_order();
-            if let Some(sequence_pc) =
-                self.search_pc_order(self.clipped_board, order, &mut visited_states)
-            {
-                results.accept_if_present(&sequence, true);
-
-                if self.allows_hold {
-                    let mut visitor = Visitor {
-                        result: &mut results,
-                    };
-                    sequence_pc.infer_input_walk(infer_size, &mut visitor);
-                }
-            } else {
-                results.accept_if_present(&sequence, false);
-            }
+            let sequence_pc = self.search_pc_order(self.clipped_board, order, &mut memo);
+            self.accept(&mut results, &sequence, sequence_pc, infer_size, &mut memo.stats);
 
             if early_stopping(&results) == ExecuteInstruction::Stop {
                 break;
             }
         }
 
-        results
+        (results, memo.stats)
+    }
+
+    /// Start the search for PC possible in bulk, reporting the progress.
+    ///
+    /// The observer is called as the early stopping of `execute_with_early_stopping()`, that is, after each sequence of the pattern.
+    /// If it returns `ExecuteInstruction::Stop`, it stops.
+    /// It also stops when any of the stop conditions is satisfied, which are checked at the start and after each sequence.
+    /// ```
+    /// use std::str::FromStr;
+    /// use std::time::Duration;
+    /// use bitris::prelude::{Board64, MoveRules, AllowMove};
+    /// use bitris_commands::{ClippedBoard, Pattern, PatternElement, ShapeCounter};
+    /// use bitris_commands::pc_possible::{CancellationToken, ExecuteInstruction, PcPossibleBulkExecutor, StopConditions};
+    ///
+    /// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    ///
+    /// let board = Board64::from_str("
+    ///     XXX.....XX
+    ///     XXX....XXX
+    ///     XXX...XXXX
+    ///     XXX....XXX
+    /// ").expect("Failed to create a board");
+    /// let clipped_board = ClippedBoard::try_new(board, 4).expect("Failed to clip");
+    ///
+    /// let pattern = Pattern::try_from(vec![
+    ///     PatternElement::Permutation(ShapeCounter::one_of_each(), 5),
+    /// ]).expect("Failed to create a pattern");
+    ///
+    /// let executor = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true)
+    ///     .expect("Failed to create an executor");
+    ///
+    /// // Gives up after 10 minutes, or when the token is cancelled from another thread.
+    /// let token = CancellationToken::new();
+    /// let stop_conditions = StopConditions {
+    ///     deadline: Some(std::time::Instant::now() + Duration::from_secs(600)),
+    ///     cancellation: Some(token.clone()),
+    /// };
+    ///
+    /// let results = executor.execute_with_progress(&stop_conditions, |progress| {
+    ///     println!("{}/{} sequences", progress.count_accepted, progress.count_accepted + progress.count_pending);
+    ///     if let Some(remaining) = progress.estimated_remaining() {
+    ///         println!("about {:?} remaining", remaining);
+    ///     }
+    ///     ExecuteInstruction::Continue
+    /// });
+    /// assert_eq!(results.count_pending(), 0);
+    ///
+    /// // Once cancelled, no more sequences are searched.
+    /// token.cancel();
+    /// let results = executor.execute_with_progress(&stop_conditions, |_| ExecuteInstruction::Continue);
+    /// assert_eq!(results.count_accepted(), 0);
+    /// ```
+    pub fn execute_with_progress(
+        &self,
+        stop_conditions: &StopConditions,
+        mut observer: impl FnMut(&ExecuteProgress) -> ExecuteInstruction,
+    ) -> PcResults {
+        let started_at = Instant::now();
+
+        let sequences = self.pattern.to_sequences();
+        let results = self.new_results(&sequences);
+        if stop_conditions.is_satisfied() {
+            return results;
+        }
+
+        self.search_sequences(sequences, results, |results| {
+            let progress = ExecuteProgress::new(results, started_at.elapsed());
+            if observer(&progress) == ExecuteInstruction::Stop || stop_conditions.is_satisfied() {
+                ExecuteInstruction::Stop
+            } else {
+                ExecuteInstruction::Continue
+            }
+        })
+    }
+
+    /// Records the result of the sequence.
+    /// If the hold is allowed, the sequences that can be inferred from the succeed sequence are also recorded.
+    /// If the mirror is reduced, the mirrored sequences are also recorded in the same way.
+    fn accept(
+        &self,
+        results: &mut impl ResultsSink,
+        sequence: &ShapeSequence,
+        sequence_pc: Option<ShapeSequence>,
+        infer_size: usize,
+        stats: &mut SearchStats,
+    ) {
+        if self.reduces_mirror() {
+            let mirrored_pc = sequence_pc.as_ref().map(|sequence_pc| sequence_pc.mirror());
+            self.accept_without_mirror(results, &sequence.mirror(), mirrored_pc, infer_size, stats);
+        }
+        self.accept_without_mirror(results, sequence, sequence_pc, infer_size, stats);
+    }
+
+    fn accept_without_mirror(
+        &self,
+        results: &mut impl ResultsSink,
+        sequence: &ShapeSequence,
+        sequence_pc: Option<ShapeSequence>,
+        infer_size: usize,
+        stats: &mut SearchStats,
+    ) {
+        if let Some(sequence_pc) = sequence_pc {
+            results.accept_if_present(sequence, true);
+
+            if self.allows_hold {
+                let mut visitor = Visitor { result: results, stats };
+                sequence_pc.infer_input_walk(infer_size, &mut visitor);
+            }
+        } else {
+            results.accept_if_present(sequence, false);
+        }
     }
 
     /// This function is dedicated to a single sequence because .
@@ -271,8 +803,8 @@ impl<'a, T: RotationSystem> PcPossibleBulkExecutor<'a, T> {
         );
         let order = sequences.first().unwrap().to_shape_order();
 
-        let mut visited_states = FxHashSet::<SearchingState>::default();
-        self.search_pc_order(self.clipped_board, order, &mut visited_states)
+        let mut memo = SearchMemo::new(0);
+        self.search_pc_order(self.clipped_board, order, &mut memo)
             .is_some()
     }
 
@@ -280,7 +812,7 @@ impl<'a, T: RotationSystem> PcPossibleBulkExecutor<'a, T> {
         &self,
         current_clipped_board: ClippedBoard,
         order: ShapeOrder,
-        visited_states: &mut FxHashSet<SearchingState>,
+        memo: &mut SearchMemo,
     ) -> Option<ShapeSequence> {
         let cursor = order.new_cursor();
         let mut buffer = Buffer::with_resized(cursor.len_unused());
@@ -289,7 +821,8 @@ impl<'a, T: RotationSystem> PcPossibleBulkExecutor<'a, T> {
         self.pop_shape(
             cursor,
             current_clipped_board,
-            visited_states,
+            SearchProgress { rows: self.line_clear_checker.initial_rows(), has_t_spin: false },
+            memo,
             &mut buffer,
             &parity,
         )
@@ -299,7 +832,43 @@ impl<'a, T: RotationSystem> PcPossibleBulkExecutor<'a, T> {
         &self,
         cursor: OrderCursor,
         clipped_board: ClippedBoard,
-        visited_states: &mut FxHashSet<SearchingState>,
+        progress: SearchProgress,
+        memo: &mut SearchMemo,
+        buffer: &mut Buffer,
+        parity: &VerticalParity,
+    ) -> Option<ShapeSequence> {
+        let (board, height) = (clipped_board.board(), clipped_board.height());
+        let progress_key = self.progress_key(progress);
+        match memo.table.get(board, height, progress_key, &cursor) {
+            Some(Proof::Failed) => return None,
+            Some(Proof::Succeeded(rest)) => {
+                let shapes = buffer.as_slice().iter().chain(rest.iter()).copied().collect();
+                return Some(ShapeSequence::new(shapes));
+            }
+            None => {}
+        }
+
+        if COUNTS_STATS {
+            memo.stats.nodes_expanded += 1;
+        }
+
+        let placed = buffer.as_slice().len();
+        let sequence_pc = self.pop_shape_without_memo(cursor, clipped_board, progress, memo, buffer, parity);
+        let proof = match &sequence_pc {
+            Some(sequence_pc) => Proof::Succeeded(sequence_pc.shapes()[placed..].to_vec()),
+            None => Proof::Failed,
+        };
+        memo.table.insert(board, height, progress_key, &cursor, proof);
+
+        sequence_pc
+    }
+
+    fn pop_shape_without_memo(
+        &self,
+        cursor: OrderCursor,
+        clipped_board: ClippedBoard,
+        progress: SearchProgress,
+        memo: &mut SearchMemo,
         buffer: &mut Buffer,
         parity: &VerticalParity,
     ) -> Option<ShapeSequence> {
@@ -308,8 +877,9 @@ impl<'a, T: RotationSystem> PcPossibleBulkExecutor<'a, T> {
             if let Some(order) = self.increment(
                 shape,
                 clipped_board,
+                progress,
                 next_cursor,
-                visited_states,
+                memo,
                 buffer,
                 parity,
             ) {
@@ -325,8 +895,9 @@ impl<'a, T: RotationSystem> PcPossibleBulkExecutor<'a, T> {
                 if let Some(order) = self.increment(
                     shape,
                     clipped_board,
+                    progress,
                     next_cursor,
-                    visited_states,
+                    memo,
                     buffer,
                     parity,
                 ) {
@@ -338,44 +909,84 @@ impl<'a, T: RotationSystem> PcPossibleBulkExecutor<'a, T> {
         None
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn increment(
         &self,
         shape: Shape,
         clipped_board: ClippedBoard,
+        progress: SearchProgress,
         next_cursor: OrderCursor,
-        visited_states: &mut FxHashSet<SearchingState>,
+        memo: &mut SearchMemo,
         buffer: &mut Buffer,
         parity: &VerticalParity,
     ) -> Option<ShapeSequence> {
         buffer.increment(shape);
 
-        let placement = shape.with(Orientation::North).with(self.spawn_position);
-        let moves = self
-            .move_rules
-            .generate_minimized_moves(clipped_board.board(), placement);
+        let spawn = self.spawn_rule.placement(shape, self.clipped_board.height());
+        let moves = if !spawn.is_in_free_space(clipped_board.board_ref()) {
+            Vec::new()
+        } else {
+            generate_moves(self.move_rules, self.max_inputs, &self.spawn_rule, clipped_board.board(), spawn)
+        };
+
+        if COUNTS_STATS {
+            memo.stats.placements_generated += moves.len() as u64;
+        }
+
+        // Made on the first T placement since it searches all reachable positions.
+        let mut t_spin_detector = None;
 
         for placement in moves {
             if clipped_board.height() as i32 <= placement.to_tr_placement().position.ty {
                 continue;
             }
 
+            let has_t_spin = progress.has_t_spin || (shape == Shape::T && !self.required_t_spins.is_empty() && {
+                let detector = t_spin_detector
+                    .get_or_insert_with(|| if self.spawn_rule.is_20g() {
+                        TSpinDetector::new_in_20g(self.move_rules, clipped_board.board(), spawn)
+                    } else {
+                        TSpinDetector::new(self.move_rules, clipped_board.board(), spawn)
+                    });
+                detector.detect(placement.to_cc_placement())
+                    .is_some_and(|kind| self.required_t_spins.contains(&kind))
+            });
+            let needs_t_spin = !self.required_t_spins.is_empty() && !has_t_spin;
+
             let mut board = clipped_board.board();
             let lines_cleared = placement.place_on_and_clear_lines(&mut board).unwrap();
+            let Some(next_rows) = self.line_clear_checker.clear(progress.rows, lines_cleared, board.is_empty()) else {
+                continue;
+            };
+            let next_progress = SearchProgress { rows: next_rows, has_t_spin };
+
             if board.is_empty() {
+                if needs_t_spin {
+                    continue;
+                }
                 return Some(ShapeSequence::new(buffer.as_slice().to_vec()));
             }
 
             let height = clipped_board.height() - lines_cleared.count();
-            if !visited_states.insert(SearchingState {
+            if !memo.visited_states.insert(SearchingState {
                 board,
                 height,
+                progress: self.progress_key(next_progress),
                 first: next_cursor.first(),
             }) {
+                if COUNTS_STATS {
+                    memo.stats.states_deduplicated += 1;
+                }
                 continue;
             }
 
             let next_clipped_board = ClippedBoard::new_unsafe(board, height);
-            if !validate_board(&next_clipped_board) {
+            let is_dead = !validate_board(&next_clipped_board)
+                || self.region_pruner.as_ref().is_some_and(|pruner| pruner.prunes(&next_clipped_board));
+            if is_dead {
+                if COUNTS_STATS {
+                    memo.stats.pruned_by_board += 1;
+                }
                 continue;
             }
 
@@ -384,13 +995,21 @@ impl<'a, T: RotationSystem> PcPossibleBulkExecutor<'a, T> {
             let next_parity = parity.place(placement);
             // The flag is off if the hold is enabled but does not have an extra piece (because parity is not affected by the shape order)
             if !next_parity.validates(rest_shapes, 0, self.allows_hold && self.has_extra_shapes) {
+                if COUNTS_STATS {
+                    memo.stats.pruned_by_parity += 1;
+                }
+                continue;
+            }
+
+            if needs_t_spin && !rest_shapes.contains(&Shape::T) {
                 continue;
             }
 
             if let Some(order) = self.pop_shape(
                 next_cursor,
                 next_clipped_board,
-                visited_states,
+                next_progress,
+                memo,
                 buffer,
                 &next_parity,
             ) {
@@ -404,14 +1023,209 @@ impl<'a, T: RotationSystem> PcPossibleBulkExecutor<'a, T> {
     }
 }
 
+impl<'a, T: RotationSystem180 + Sync> PcPossibleBulkExecutor<'a, T> {
+    /// Start the search for PC possible in bulk on multiple threads.
+    /// See `execute_in_parallel_with_early_stopping()` for more details.
+    pub fn execute_in_parallel(&self, threads: usize) -> PcResults {
+        self.execute_in_parallel_with_early_stopping(threads, move |_| Continue)
+    }
+
+    /// Start the search for PC possible in bulk with early stopping on multiple threads.
+    ///
+    /// The sequences are distributed to `threads` threads one by one.
+    /// Each result, including those inferred by the hold, is shared among the threads, so sequences already found are not searched again.
+    /// The results are the same as `execute_with_early_stopping()`, except that the sequences accepted before stopping may differ.
+    ///
+    /// Panics if `threads` is 0.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris::prelude::{Board64, MoveRules, AllowMove};
+    /// use bitris_commands::{ClippedBoard, Pattern, PatternElement, ShapeCounter};
+    /// use bitris_commands::pc_possible::{ExecuteInstruction, PcPossibleBulkExecutor};
+    ///
+    /// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    ///
+    /// let board = Board64::from_str("
+    ///     XXX.....XX
+    ///     XXX....XXX
+    ///     XXX...XXXX
+    ///     XXX....XXX
+    /// ").expect("Failed to create a board");
+    /// let clipped_board = ClippedBoard::try_new(board, 4).expect("Failed to clip");
+    ///
+    /// let pattern = Pattern::try_from(vec![
+    ///     PatternElement::Permutation(ShapeCounter::one_of_each(), 5),
+    /// ]).expect("Failed to create a pattern");
+    ///
+    /// let executor = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true)
+    ///     .expect("Failed to create an executor");
+    ///
+    /// let results = executor.execute_in_parallel(4);
+    /// assert_eq!(results, executor.execute());
+    ///
+    /// // Stops after 10 failures. Other threads may add a few more results before stopping.
+    /// let results = executor.execute_in_parallel_with_early_stopping(4, |results| {
+    ///     if results.count_failed() < 10 {
+    ///         ExecuteInstruction::Continue
+    ///     } else {
+    ///         ExecuteInstruction::Stop
+    ///     }
+    /// });
+    /// assert!(10 <= results.count_failed());
+    /// assert!(0 < results.count_pending());
+    /// ```
+    pub fn execute_in_parallel_with_early_stopping(
+        &self,
+        threads: usize,
+        early_stopping: impl Fn(&PcResults) -> ExecuteInstruction + Sync,
+    ) -> PcResults {
+        let sequences = self.pattern.to_sequences();
+        let results = self.new_results(&sequences);
+        self.search_sequences_in_parallel(threads, sequences, results, early_stopping)
+    }
+
+    /// Start the search for PC possible in bulk on multiple threads, and returns the statistics summed over the threads together.
+    /// Available only if the `stats` feature is enabled. See `execute_with_stats()` and `execute_in_parallel_with_early_stopping()`.
+    ///
+    /// Since the threads share the results found, the statistics can vary between runs.
+    ///
+    /// Panics if `threads` is 0.
+    #[cfg(feature = "stats")]
+    pub fn execute_in_parallel_with_stats(&self, threads: usize) -> (PcResults, SearchStats) {
+        let sequences = self.pattern.to_sequences();
+        let results = self.new_results(&sequences);
+        self.search_sequences_in_parallel_with_stats(threads, sequences, results, move |_| Continue)
+    }
+
+    /// Loads a checkpoint and searches only the sequences still pending on multiple threads, with early stopping.
+    /// See `try_resume_with_early_stopping()` and `execute_in_parallel_with_early_stopping()` for more details.
+    ///
+    /// Panics if `threads` is 0.
+    pub fn try_resume_in_parallel_with_early_stopping(
+        &self,
+        threads: usize,
+        reader: impl Read,
+        early_stopping: impl Fn(&PcResults) -> ExecuteInstruction + Sync,
+    ) -> Result<PcResults, PcCheckpointError> {
+        let sequences = self.pattern.to_sequences();
+        let results = read_checkpoint(reader, &self.configuration(), &sequences, self.new_results(&sequences))?;
+        Ok(self.search_sequences_in_parallel(threads, sequences, results, early_stopping))
+    }
+
+    /// Searches the sequences whose results are not yet in `results` on multiple threads.
+    fn search_sequences_in_parallel(
+        &self,
+        threads: usize,
+        sequences: Vec<ShapeSequence>,
+        results: PcResults,
+        early_stopping: impl Fn(&PcResults) -> ExecuteInstruction + Sync,
+    ) -> PcResults {
+        self.search_sequences_in_parallel_with_stats(threads, sequences, results, early_stopping).0
+    }
+
+    /// Searches the sequences whose results are not yet in `results` on multiple threads, and returns the statistics summed over the threads together.
+    fn search_sequences_in_parallel_with_stats(
+        &self,
+        threads: usize,
+        sequences: Vec<ShapeSequence>,
+        results: PcResults,
+        early_stopping: impl Fn(&PcResults) -> ExecuteInstruction + Sync,
+    ) -> (PcResults, SearchStats) {
+        assert!(0 < threads, "The count of threads must be 1 or more.");
+
+        let infer_size = self.pattern.dim_shapes();
+
+        let results = Mutex::new(results);
+        let next_index = AtomicUsize::new(0);
+        let stopped = AtomicBool::new(false);
+
+        let stats = thread::scope(|scope| {
+            let mut handles = Vec::with_capacity(threads);
+            for _ in 0..threads {
+                handles.push(scope.spawn(|| {
+                    let mut memo = SearchMemo::new(self.transposition_capacity);
+
+                    while !stopped.load(Ordering::Relaxed) {
+                        let index = next_index.fetch_add(1, Ordering::Relaxed);
+                        let Some(sequence) = sequences.get(index) else {
+                            break;
+                        };
+
+                        if results.lock().unwrap().get(sequence).is_some() {
+                            continue;
+                        }
+
+                        memo.visited_states.clear();
+
+                        let order = sequence.to_shape_order();
+                        let sequence_pc = self.search_pc_order(self.clipped_board, order, &mut memo);
+
+                        let mut results = results.lock().unwrap();
+                        self.accept(&mut *results, sequence, sequence_pc, infer_size, &mut memo.stats);
+
+                        if early_stopping(&results) == ExecuteInstruction::Stop {
+                            stopped.store(true, Ordering::Relaxed);
+                        }
+                    }
+
+                    memo.stats
+                }));
+            }
+
+            let mut stats = SearchStats::default();
+            for handle in handles {
+                stats += handle.join().unwrap();
+            }
+            stats
+        });
+
+        (results.into_inner().unwrap(), stats)
+    }
+
+    /// Start the search for PC possible in bulk on multiple threads, reporting the progress.
+    /// The observer is called by one thread at a time.
+    /// See `execute_with_progress()` and `execute_in_parallel_with_early_stopping()` for more details.
+    ///
+    /// Panics if `threads` is 0.
+    pub fn execute_in_parallel_with_progress(
+        &self,
+        threads: usize,
+        stop_conditions: &StopConditions,
+        observer: impl FnMut(&ExecuteProgress) -> ExecuteInstruction + Send,
+    ) -> PcResults {
+        assert!(0 < threads, "The count of threads must be 1 or more.");
+
+        let started_at = Instant::now();
+
+        let sequences = self.pattern.to_sequences();
+        let results = self.new_results(&sequences);
+        if stop_conditions.is_satisfied() {
+            return results;
+        }
+
+        // Called while the results are locked, so the observer is called by one thread at a time.
+        let observer = Mutex::new(observer);
+        self.search_sequences_in_parallel(threads, sequences, results, |results| {
+            let progress = ExecuteProgress::new(results, started_at.elapsed());
+            let mut observer = observer.lock().unwrap();
+            if (*observer)(&progress) == ExecuteInstruction::Stop || stop_conditions.is_satisfied() {
+                ExecuteInstruction::Stop
+            } else {
+                ExecuteInstruction::Continue
+            }
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
+    use std::time::Duration;
 
-    use bitris::prelude::{xy, AllowMove, Board64, BoardOp, MoveRules, Shape};
+    use bitris::prelude::{xy, AllowMove, Board64, BoardOp, MoveRules, Orientation, Shape};
 
-    use crate::pc_possible::{PcPossibleBulkExecutor, PcPossibleExecutorBulkCreationError};
-    use crate::{BitShapes, ClippedBoard, Pattern, PatternElement, ShapeCounter, ShapeSequence};
+    use crate::pc_possible::{CancellationToken, DEFAULT_TRANSPOSITION_CAPACITY, ExecuteInstruction, ExecuteProgress, PcPossibleBulkExecutor, PcPossibleExecutorBulkCreationError, StopConditions};
+    use crate::{BitShapes, ClippedBoard, Mirror, Pattern, PatternElement, ShapeCounter, ShapeSequence, SpawnRow, SpawnRule, TSpinKind};
 
     #[test]
     fn success_rate_contain_filled_line() {
@@ -444,6 +1258,441 @@ mod tests {
         assert_eq!(result.get(&ShapeSequence::new(vec![O, O, O])), None);
     }
 
+    #[test]
+    fn execute_in_parallel() {
+        use PatternElement::*;
+
+        let board = Board64::from_str(
+            "
+            ###.....##
+            ###....###
+            ###...####
+            ###....###
+        ",
+        )
+        .unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let pattern = Pattern::try_from(vec![Permutation(ShapeCounter::one_of_each(), 5)]).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+
+        for allows_hold in [true, false] {
+            let executor =
+                PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, allows_hold).unwrap();
+            let expected = executor.execute();
+            for threads in [1, 3, 8] {
+                let result = executor.execute_in_parallel(threads);
+                assert_eq!(result, expected);
+                assert_eq!(result.count_pending(), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn transposition_capacity() {
+        use PatternElement::*;
+
+        let board = Board64::from_str(
+            "
+            ##.....###
+            ###....###
+            ####...###
+            ###....###
+        ",
+        )
+        .unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let pattern = Pattern::try_from(vec![Permutation(ShapeCounter::one_of_each(), 5)]).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+
+        for allows_hold in [true, false] {
+            let executor =
+                PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, allows_hold).unwrap();
+            let expected = executor.clone().with_transposition_capacity(0).execute();
+            if allows_hold {
+                assert_eq!(expected.count_succeed(), 1672);
+            }
+
+            // Includes capacities small enough to evict states during the search.
+            for capacity in [2, 64, DEFAULT_TRANSPOSITION_CAPACITY] {
+                let executor = executor.clone().with_transposition_capacity(capacity);
+                assert_eq!(executor.execute(), expected);
+                assert_eq!(executor.execute_in_parallel(3), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn transposition_table_changes_no_results() {
+        use PatternElement::*;
+        use Shape::*;
+
+        // The cases of `benches/pc_possible.rs`.
+        let cases = [
+            ("
+                ####....##
+                ####...###
+                ####..####
+                ####...###
+            ", vec![Permutation(ShapeCounter::one_of_each(), 4)], 514),
+            ("
+                ######....
+                ######....
+                ######....
+                ######....
+            ", vec![One(T), Permutation(ShapeCounter::one_of_each(), 4)], 744),
+        ];
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+
+        for (board, elements, expected_succeed) in cases {
+            let clipped_board = ClippedBoard::try_new(Board64::from_str(board).unwrap(), 4).unwrap();
+            let pattern = Pattern::try_from(elements).unwrap();
+            for allows_hold in [true, false] {
+                let executor = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, allows_hold).unwrap();
+                let with_table = executor.clone().with_transposition_capacity(DEFAULT_TRANSPOSITION_CAPACITY);
+                let without_table = executor.with_transposition_capacity(0);
+
+                let results = with_table.execute();
+                assert_eq!(results, without_table.execute());
+                assert_eq!(results.allows_hold(), allows_hold);
+                assert_eq!(with_table.execute_compact().to_pc_results(), without_table.execute_compact().to_pc_results());
+                if allows_hold {
+                    assert_eq!(results.count_succeed(), expected_succeed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "The count of threads must be 1 or more.")]
+    fn execute_in_parallel_with_zero_threads() {
+        let board = Board64::from_str(
+            "
+            ###.....##
+            ###....###
+            ###...####
+            ###....###
+        ",
+        )
+        .unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let pattern = Pattern::try_from(vec![PatternElement::Permutation(ShapeCounter::one_of_each(), 5)]).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let executor = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap();
+        executor.execute_in_parallel(0);
+    }
+
+    #[test]
+    fn execute_with_progress() {
+        use PatternElement::*;
+
+        let board = Board64::from_str(
+            "
+            ###.....##
+            ###....###
+            ###...####
+            ###....###
+        ",
+        )
+        .unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let pattern = Pattern::try_from(vec![Permutation(ShapeCounter::one_of_each(), 5)]).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let executor = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap();
+        let expected = executor.execute();
+
+        for threads in [1, 3] {
+            let mut last = None;
+            let observer = |progress: &ExecuteProgress| {
+                assert_eq!(progress.count_accepted + progress.count_pending, 2520);
+                last = Some(*progress);
+                ExecuteInstruction::Continue
+            };
+            let result = if threads == 1 {
+                executor.execute_with_progress(&StopConditions::default(), observer)
+            } else {
+                executor.execute_in_parallel_with_progress(threads, &StopConditions::default(), observer)
+            };
+            assert_eq!(result, expected);
+
+            let last = last.unwrap();
+            assert_eq!(last.count_accepted, expected.count_accepted());
+            assert_eq!(last.count_succeed, expected.count_succeed());
+            assert_eq!(last.count_pending, 0);
+        }
+    }
+
+    #[test]
+    fn stop_with_conditions() {
+        use PatternElement::*;
+
+        let board = Board64::from_str(
+            "
+            ####......
+            ####......
+            ####......
+            ####......
+        ",
+        )
+        .unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let pattern = Pattern::try_from(vec![Factorial(ShapeCounter::one_of_each())]).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let executor = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap();
+
+        // The deadline has already passed.
+        let stop_conditions = StopConditions::with_time_budget(Duration::ZERO);
+        let result = executor.execute_with_progress(&stop_conditions, |_| ExecuteInstruction::Continue);
+        assert_eq!(result.count_accepted(), 0);
+        assert_eq!(result.count_pending(), 5040);
+
+        // Cancels from the observer as if from another thread.
+        for threads in [1, 3] {
+            let token = CancellationToken::new();
+            let stop_conditions = StopConditions::with_cancellation(token.clone());
+            let observer = |_: &ExecuteProgress| {
+                token.cancel();
+                ExecuteInstruction::Continue
+            };
+            let result = if threads == 1 {
+                executor.execute_with_progress(&stop_conditions, observer)
+            } else {
+                executor.execute_in_parallel_with_progress(threads, &stop_conditions, observer)
+            };
+            assert!(0 < result.count_accepted());
+            assert!(0 < result.count_pending());
+        }
+    }
+
+    #[test]
+    fn required_t_spins() {
+        use Shape::*;
+
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let board = Board64::from_str("
+            ####....##
+            ###...####
+            ####.#####
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 3).unwrap();
+        let pattern = Pattern::try_from(vec![
+            PatternElement::Permutation(ShapeCounter::from(vec![T, I]), 2),
+        ]).unwrap();
+        let executor = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, false).unwrap();
+        let (ti, it) = (ShapeSequence::new(vec![T, I]), ShapeSequence::new(vec![I, T]));
+
+        let results = executor.clone().execute();
+        assert_eq!((results.get(&ti), results.get(&it)), (Some(true), Some(true)));
+
+        // Only `TI` can take T-spin double, then I fills the top.
+        let results = executor.clone().with_required_t_spins(&[TSpinKind::Double]).execute();
+        assert_eq!((results.get(&ti), results.get(&it)), (Some(true), Some(false)));
+
+        let results = executor.with_required_t_spins(&[TSpinKind::Single, TSpinKind::Triple]).execute();
+        assert_eq!(results.get(&ti), Some(false));
+        assert_eq!(results.count_succeed(), 0);
+    }
+
+    #[test]
+    fn spawn_rule() {
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let board = Board64::from_str("
+            ####......
+            ####......
+            ####......
+            ####......
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let pattern = Pattern::try_from(vec![
+            PatternElement::Factorial(ShapeCounter::one_of_each()),
+        ]).unwrap();
+        let executor = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap();
+        let results = executor.clone().execute();
+        assert_eq!(results.count_succeed(), 5040);
+
+        // Spawning high above gives the same results.
+        let spawn_rule = SpawnRule::default().with_row(SpawnRow::Fixed(20)).with_columns(4);
+        assert_eq!(executor.clone().try_with_spawn_rule(spawn_rule).unwrap().execute(), results);
+
+        // Spawning in the field, the stack blocks the spawn of the last pieces.
+        let spawn_rule = SpawnRule::default().with_row(SpawnRow::Fixed(2));
+        let results_in_field = executor.clone().try_with_spawn_rule(spawn_rule).unwrap().execute();
+        assert!(results_in_field.count_succeed() < results.count_succeed());
+
+        // Blocked by the initial board
+        let spawn_rule = SpawnRule::default().with_row(SpawnRow::Fixed(0)).with_column(Shape::I, 0);
+        assert_eq!(executor.try_with_spawn_rule(spawn_rule).unwrap_err(), PcPossibleExecutorBulkCreationError::SpawnIsBlocked);
+    }
+
+    #[test]
+    fn spawn_in_20g() {
+        use PatternElement::*;
+
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let board = Board64::from_str("
+            ###.##....
+            ###.##....
+            ###.##....
+            ###.##....
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let pattern = Pattern::try_from(vec![One(Shape::I), One(Shape::O), One(Shape::O), One(Shape::O), One(Shape::O)]).unwrap();
+        let executor = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, false).unwrap();
+
+        // The I goes over the wall into the well, and the O pieces fill the right.
+        let spawn_rule = SpawnRule::default().with_columns(6);
+        assert_eq!(executor.clone().try_with_spawn_rule(spawn_rule).unwrap().execute().count_succeed(), 1);
+
+        // In 20G, the I drops at spawn on the right and never gets over the wall.
+        let spawn_rule = spawn_rule.with_20g(true);
+        assert_eq!(executor.clone().try_with_spawn_rule(spawn_rule).unwrap().execute().count_succeed(), 0);
+
+        // The placements in 20G can be reached with softdrop, so it never succeeds more.
+        let board = Board64::from_str("
+            ####......
+            ####......
+            ####......
+            ####......
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let pattern = Pattern::try_from(vec![Permutation(ShapeCounter::one_of_each(), 6)]).unwrap();
+        let executor = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap();
+        let results = executor.clone().execute();
+        let results_20g = executor.try_with_spawn_rule(SpawnRule::default().with_20g(true)).unwrap().execute();
+        assert!(0 < results_20g.count_succeed());
+        assert!(results_20g.iter().all(|(sequence, &result)| result != Some(true) || results.get(sequence) == Some(true)));
+    }
+
+    #[test]
+    fn max_inputs() {
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let board = Board64::from_str("
+            ####....##
+            ####...###
+            ####..####
+            ####...###
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let pattern = Pattern::try_from(vec![
+            PatternElement::Permutation(ShapeCounter::one_of_each(), 4),
+        ]).unwrap();
+        let executor = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap();
+        let unlimited = executor.clone().execute();
+
+        // Enough inputs give the same results.
+        assert_eq!(executor.clone().with_max_inputs(Some(20)).execute(), unlimited);
+
+        let limited = executor.clone().with_max_inputs(Some(2)).execute();
+        assert!(limited.count_succeed() < unlimited.count_succeed());
+        for (sequence, &succeed) in limited.iter() {
+            if succeed == Some(true) {
+                assert_eq!(unlimited.get(sequence), Some(true));
+            }
+        }
+
+        // Nothing reaches there without inputs.
+        assert_eq!(executor.with_max_inputs(Some(0)).execute().count_succeed(), 0);
+    }
+
+    #[test]
+    fn mirror_reduction() {
+        let kick_table = crate::kick_table::symmetric_kick_table();
+        let move_rules = MoveRules::new(&kick_table, AllowMove::Softdrop);
+        let pattern = Pattern::try_from(vec![
+            PatternElement::Permutation(ShapeCounter::one_of_each(), 5),
+        ]).unwrap();
+
+        let board = Board64::from_str("
+            X........X
+            X........X
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 2).unwrap();
+        let executor = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap();
+        let reduced = executor.clone().with_mirror_reduction(true);
+        assert!(reduced.reduces_mirror());
+
+        let expected = executor.execute();
+        assert!(0 < expected.count_succeed());
+        assert_eq!(reduced.execute(), expected);
+        assert_eq!(reduced.execute_in_parallel(3), expected);
+        assert_eq!(reduced.execute_compact().to_pc_results(), expected);
+
+        // Not effective on the fixed spawn row
+        let fixed = reduced.clone().try_with_spawn_rule(SpawnRule::default().with_row(SpawnRow::Fixed(20))).unwrap();
+        assert!(!fixed.reduces_mirror());
+
+        // Not effective in 20G
+        let dropped = reduced.clone().try_with_spawn_rule(SpawnRule::default().with_20g(true)).unwrap();
+        assert!(!dropped.reduces_mirror());
+
+        // Not effective if the spawn differs by shape
+        let shifted = reduced.clone().try_with_spawn_rule(SpawnRule::default().with_column(Shape::I, 3)).unwrap();
+        assert!(!shifted.reduces_mirror());
+        let rotated = reduced.clone().try_with_spawn_rule(SpawnRule::default().with_orientation(Shape::I, Orientation::East)).unwrap();
+        assert!(!rotated.reduces_mirror());
+
+        // Not effective with the limited inputs, since mirrored inputs are not always equal in number
+        assert!(!reduced.clone().with_max_inputs(Some(10)).reduces_mirror());
+
+        // Not effective with the asymmetric kicks
+        let srs_move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let srs_executor = PcPossibleBulkExecutor::try_new(&srs_move_rules, clipped_board, &pattern, true).unwrap()
+            .with_mirror_reduction(true);
+        assert!(!srs_executor.reduces_mirror());
+
+        // The results on the mirrored board are mirrored.
+        let board = Board64::from_str("
+            XX........
+            XX........
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 2).unwrap();
+        let executor = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap()
+            .with_mirror_reduction(true);
+        assert!(!executor.reduces_mirror());
+
+        let mirrored_executor = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board.mirror(), &pattern, true).unwrap();
+        assert_eq!(mirrored_executor.execute(), executor.execute().mirror());
+    }
+
+    #[test]
+    #[cfg(feature = "stats")]
+    fn execute_with_stats() {
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let board = Board64::from_str("
+            XXXX....XX
+            XXXX...XXX
+            XXXX..XXXX
+            XXXX...XXX
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let pattern = Pattern::try_from(vec![
+            PatternElement::Permutation(ShapeCounter::one_of_each(), 4),
+        ]).unwrap();
+
+        let executor = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap();
+        let (results, stats) = executor.execute_with_stats();
+        assert_eq!(results, executor.execute());
+        assert!(0 < stats.nodes_expanded);
+        assert!(stats.nodes_expanded <= stats.placements_generated + results.count_keys() as u64);
+        assert!(0 < stats.pruned_by_board + stats.pruned_by_parity);
+        assert!(0 < stats.reused_by_hold);
+        assert!(stats.reused_by_hold < results.count_succeed());
+
+        // Nothing is inferred without the hold.
+        let executor = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, false).unwrap();
+        let (_, stats) = executor.execute_with_stats();
+        assert_eq!(stats.reused_by_hold, 0);
+
+        // The statistics are summed over the threads.
+        let (parallel_results, parallel_stats) = executor.execute_in_parallel_with_stats(3);
+        assert_eq!(parallel_results, executor.execute());
+        assert!(0 < parallel_stats.nodes_expanded);
+        assert_eq!(parallel_stats.reused_by_hold, 0);
+
+        // Without the memo across sequences, more states are expanded.
+        let (_, stats_without_memo) = executor.clone().with_transposition_capacity(0).execute_with_stats();
+        assert!(stats.nodes_expanded <= stats_without_memo.nodes_expanded);
+    }
+
     #[test]
     fn execute_single() {
         use super::Shape::*;
@@ -0,0 +1,385 @@
+use std::io::{Read, Write};
+
+use bitris::prelude::*;
+use thiserror::Error;
+
+use crate::pc_possible::{LineClearConstraints, PcPossibleExecutorBulkCreationError, PcResults};
+use crate::{ClippedBoard, Pattern, PatternElement, RotationSystem180, ShapeCounter, ShapeSequence, SpawnRow, SpawnRule, TSpinKind};
+
+const MAGIC: &[u8; 8] = b"BTRSPCR\0";
+const VERSION: u32 = 3;
+
+/// A collection of errors that occur when saving or loading a checkpoint.
+#[derive(Error, Debug)]
+pub enum PcCheckpointError {
+    #[error("Failed to read or write the checkpoint: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("The data is not a checkpoint, or it's broken.")]
+    InvalidFormat,
+    #[error("The checkpoint version {0} is not supported.")]
+    UnsupportedVersion(u32),
+    #[error("The checkpoint was made with a different configuration.")]
+    ConfigurationMismatch,
+    #[error(transparent)]
+    ExecutorCreation(#[from] PcPossibleExecutorBulkCreationError),
+}
+
+/// Writes the values of the configuration as bytes.
+/// Each value is written explicitly in a fixed width and little-endian, not through the derived `Hash`,
+/// so the bytes are the same across platforms and Rust versions.
+struct ConfigurationWriter(Vec<u8>);
+
+impl ConfigurationWriter {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn bool(&mut self, value: bool) {
+        self.u8(value as u8);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn i32(&mut self, value: i32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn len(&mut self, len: usize) {
+        self.u64(len as u64);
+    }
+
+    fn shape(&mut self, shape: Shape) {
+        self.u8(shape as u8);
+    }
+
+    fn shape_counter(&mut self, counter: ShapeCounter) {
+        let pairs = counter.to_pairs();
+        self.len(pairs.len());
+        for (shape, count) in pairs {
+            self.shape(shape);
+            self.u8(count);
+        }
+    }
+
+    fn kicks(&mut self, kicks: &[Kick]) {
+        self.len(kicks.len());
+        for kick in kicks {
+            self.i32(kick.offset.dx);
+            self.i32(kick.offset.dy);
+        }
+    }
+}
+
+const ORIENTATIONS: [Orientation; 4] = [Orientation::North, Orientation::East, Orientation::South, Orientation::West];
+
+/// Returns the configuration that determines the results as bytes, which are saved in the checkpoint.
+/// The rotation system is identified by its kicks, including the 180° kicks if defined.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn encode_configuration<T: RotationSystem180>(
+    move_rules: &MoveRules<T>,
+    clipped_board: ClippedBoard,
+    pattern: &Pattern,
+    allows_hold: bool,
+    line_clear_constraints: &LineClearConstraints,
+    required_t_spins: &[TSpinKind],
+    spawn_rule: &SpawnRule,
+    max_inputs: Option<u32>,
+) -> Vec<u8> {
+    let mut writer = ConfigurationWriter(Vec::new());
+
+    for &col in &clipped_board.board_ref().cols {
+        writer.u64(col);
+    }
+    writer.u32(clipped_board.height());
+
+    writer.len(pattern.elements().len());
+    for &element in pattern.elements() {
+        match element {
+            PatternElement::One(shape) => {
+                writer.u8(0);
+                writer.shape(shape);
+            }
+            PatternElement::Fixed(shapes) => {
+                let shapes = shapes.to_vec();
+                writer.u8(1);
+                writer.len(shapes.len());
+                shapes.into_iter().for_each(|shape| writer.shape(shape));
+            }
+            PatternElement::Wildcard => writer.u8(2),
+            PatternElement::Permutation(counter, pop) => {
+                writer.u8(3);
+                writer.shape_counter(counter);
+                writer.len(pop);
+            }
+            PatternElement::Factorial(counter) => {
+                writer.u8(4);
+                writer.shape_counter(counter);
+            }
+        }
+    }
+
+    writer.bool(allows_hold);
+
+    writer.bool(line_clear_constraints.max_lines_before_last().is_some());
+    writer.u32(line_clear_constraints.max_lines_before_last().unwrap_or(0));
+    writer.len(line_clear_constraints.rows_cleared_together().len());
+    for &rows in line_clear_constraints.rows_cleared_together() {
+        writer.u64(rows);
+    }
+
+    writer.len(required_t_spins.len());
+    for &kind in required_t_spins {
+        writer.u8(kind as u8);
+    }
+
+    for shape in Shape::all_iter() {
+        writer.u8(spawn_rule.orientation(shape) as u8);
+        writer.i32(spawn_rule.column(shape));
+    }
+    match spawn_rule.row() {
+        SpawnRow::AboveHeight => writer.u8(0),
+        SpawnRow::Fixed(row) => {
+            writer.u8(1);
+            writer.i32(row);
+        }
+    }
+    writer.bool(spawn_rule.is_20g());
+
+    writer.bool(max_inputs.is_some());
+    writer.u32(max_inputs.unwrap_or(0));
+
+    writer.u8(move_rules.allow_move as u8);
+    for shape in Shape::all_iter() {
+        writer.bool(move_rules.rotation_system.is_moving_in_rotation(shape));
+        for orientation in ORIENTATIONS {
+            for rotation in [Rotation::Cw, Rotation::Ccw] {
+                writer.kicks(move_rules.rotation_system.iter_kicks(shape.with(orientation), rotation).as_slice());
+            }
+        }
+    }
+
+    writer.bool(move_rules.rotation_system.has_kicks_180());
+    if move_rules.rotation_system.has_kicks_180() {
+        for shape in Shape::all_iter() {
+            for orientation in ORIENTATIONS {
+                writer.kicks(move_rules.rotation_system.iter_kicks_180(shape.with(orientation)).as_slice());
+            }
+        }
+    }
+
+    writer.0
+}
+
+/// Returns the 64-bit FNV-1a hash of the configuration encoded by `encode_configuration()`.
+/// Since the bytes are explicit, it's fixed across platforms and Rust versions.
+pub(crate) fn fingerprint(configuration: &[u8]) -> u64 {
+    configuration.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Writes the results in the order of `sequences`.
+///
+/// The format is as follows (integers are in little-endian):
+///   + magic: `BTRSPCR\0`
+///   + version: u32
+///   + length of the configuration: u64
+///   + configuration encoded by `encode_configuration()`
+///   + count of the sequences: u64
+///   + results: 2 bits per sequence (0: pending, 1: failed, 2: succeed), packed from the lowest bit
+pub(crate) fn write_checkpoint(
+    mut writer: impl Write,
+    configuration: &[u8],
+    sequences: &[ShapeSequence],
+    results: &PcResults,
+) -> Result<(), PcCheckpointError> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&VERSION.to_le_bytes())?;
+    writer.write_all(&(configuration.len() as u64).to_le_bytes())?;
+    writer.write_all(configuration)?;
+    writer.write_all(&(sequences.len() as u64).to_le_bytes())?;
+
+    let mut bytes = vec![0u8; sequences.len().div_ceil(4)];
+    for (index, sequence) in sequences.iter().enumerate() {
+        let state: u8 = match results.get(sequence) {
+            None => 0,
+            Some(false) => 1,
+            Some(true) => 2,
+        };
+        bytes[index / 4] |= state << (index % 4 * 2);
+    }
+    writer.write_all(&bytes)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Reads the results written by `write_checkpoint()` into `results` made from the same `sequences`.
+/// Returns `Err()` if the configuration or the count of sequences is not as expected.
+pub(crate) fn read_checkpoint(
+    mut reader: impl Read,
+    configuration: &[u8],
+    sequences: &[ShapeSequence],
+    mut results: PcResults,
+) -> Result<PcResults, PcCheckpointError> {
+    use PcCheckpointError::*;
+
+    fn read_exact(reader: &mut impl Read, buffer: &mut [u8]) -> Result<(), PcCheckpointError> {
+        reader.read_exact(buffer).map_err(|error| match error.kind() {
+            std::io::ErrorKind::UnexpectedEof => InvalidFormat,
+            _ => Io(error),
+        })
+    }
+
+    fn read_array<const N: usize>(reader: &mut impl Read) -> Result<[u8; N], PcCheckpointError> {
+        let mut buffer = [0u8; N];
+        read_exact(reader, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    if &read_array::<8>(&mut reader)? != MAGIC {
+        return Err(InvalidFormat);
+    }
+
+    let version = u32::from_le_bytes(read_array(&mut reader)?);
+    if version != VERSION {
+        return Err(UnsupportedVersion(version));
+    }
+
+    // The length is compared first, so as not to allocate by a broken length.
+    if u64::from_le_bytes(read_array(&mut reader)?) != configuration.len() as u64 {
+        return Err(ConfigurationMismatch);
+    }
+    let mut saved_configuration = vec![0u8; configuration.len()];
+    read_exact(&mut reader, &mut saved_configuration)?;
+    if saved_configuration != configuration {
+        return Err(ConfigurationMismatch);
+    }
+
+    if u64::from_le_bytes(read_array(&mut reader)?) != sequences.len() as u64 {
+        return Err(InvalidFormat);
+    }
+
+    let mut bytes = vec![0u8; sequences.len().div_ceil(4)];
+    read_exact(&mut reader, &mut bytes)?;
+
+    for (index, sequence) in sequences.iter().enumerate() {
+        match (bytes[index / 4] >> (index % 4 * 2)) & 0b11 {
+            0 => {}
+            1 => {
+                results.accept_if_present(sequence, false);
+            }
+            2 => {
+                results.accept_if_present(sequence, true);
+            }
+            _ => return Err(InvalidFormat),
+        }
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::pc_possible::{encode_configuration, fingerprint, read_checkpoint, write_checkpoint, LineClearConstraints, PcCheckpointError, PcResults};
+    use crate::{ClippedBoard, KickTable, Pattern, PatternElement, ShapeCounter, SpawnRow, SpawnRule, TSpinKind};
+
+    #[test]
+    fn configuration_depends_on_settings() {
+        let board = Board64::from_str("
+            ####....##
+            ####...###
+            ####..####
+            ####...###
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let pattern = Pattern::try_from(vec![
+            PatternElement::Permutation(ShapeCounter::one_of_each(), 4),
+        ]).unwrap();
+        let softdrop = MoveRules::srs(AllowMove::Softdrop);
+
+        let unconstrained = LineClearConstraints::default();
+        let default_spawn = SpawnRule::default();
+        let base = encode_configuration(&softdrop, clipped_board, &pattern, true, &unconstrained, &[], &default_spawn, None);
+        assert_eq!(base, encode_configuration(&MoveRules::srs(AllowMove::Softdrop), clipped_board, &pattern, true, &unconstrained, &[], &default_spawn, None));
+
+        assert_ne!(base, encode_configuration(&MoveRules::srs(AllowMove::Harddrop), clipped_board, &pattern, true, &unconstrained, &[], &default_spawn, None));
+        assert_ne!(base, encode_configuration(&softdrop, clipped_board, &pattern, false, &unconstrained, &[], &default_spawn, None));
+        assert_ne!(base, encode_configuration(&softdrop, ClippedBoard::try_new(board, 8).unwrap(), &pattern, true, &unconstrained, &[], &default_spawn, None));
+
+        let other_pattern = Pattern::try_from(vec![
+            PatternElement::Factorial(ShapeCounter::one_of_each()),
+        ]).unwrap();
+        assert_ne!(base, encode_configuration(&softdrop, clipped_board, &other_pattern, true, &unconstrained, &[], &default_spawn, None));
+        assert_ne!(base, encode_configuration(&softdrop, clipped_board, &pattern, true, &LineClearConstraints::no_line_clear_until_last(), &[], &default_spawn, None));
+        assert_ne!(base, encode_configuration(&softdrop, clipped_board, &pattern, true, &unconstrained, &[TSpinKind::Double], &default_spawn, None));
+        assert_ne!(base, encode_configuration(&softdrop, clipped_board, &pattern, true, &unconstrained, &[], &SpawnRule::default().with_row(SpawnRow::Fixed(20)), None));
+        assert_ne!(base, encode_configuration(&softdrop, clipped_board, &pattern, true, &unconstrained, &[], &default_spawn, Some(10)));
+
+        // The same kicks except for the 180° rotation.
+        let srs = KickTable::srs();
+        let srs_x = KickTable::srs_x();
+        let with_srs = encode_configuration(&MoveRules::new(&srs, AllowMove::Softdrop), clipped_board, &pattern, true, &unconstrained, &[], &default_spawn, None);
+        assert_eq!(base, with_srs);
+        assert_ne!(base, encode_configuration(&MoveRules::new(&srs_x, AllowMove::Softdrop), clipped_board, &pattern, true, &unconstrained, &[], &default_spawn, None));
+    }
+
+    #[test]
+    fn stable_fingerprint() {
+        // The values of FNV-1a.
+        assert_eq!(fingerprint(&[]), 0xcbf29ce484222325);
+        assert_eq!(fingerprint(b"a"), 0xaf63dc4c8601ec8c);
+    }
+
+    #[test]
+    fn round_trip() {
+        use Shape::*;
+
+        let pattern = Pattern::try_from(vec![
+            PatternElement::Permutation(ShapeCounter::from(vec![I, O, T]), 2),
+        ]).unwrap();
+        let sequences = pattern.to_sequences();
+
+        let mut results = PcResults::new(&sequences);
+        results.accept_if_present(&sequences[0], true);
+        results.accept_if_present(&sequences[1], false);
+        results.accept_if_present(&sequences[5], true);
+
+        let mut bytes = Vec::<u8>::new();
+        write_checkpoint(&mut bytes, &[1, 2, 3], &sequences, &results).unwrap();
+        assert_eq!(bytes.len(), 8 + 4 + 8 + 3 + 8 + 2);
+
+        let loaded = read_checkpoint(bytes.as_slice(), &[1, 2, 3], &sequences, PcResults::new(&sequences)).unwrap();
+        assert_eq!(loaded, results);
+        assert_eq!(loaded.count_succeed(), 2);
+        assert_eq!(loaded.count_failed(), 1);
+        assert_eq!(loaded.count_pending(), 3);
+
+        assert!(matches!(
+            read_checkpoint(bytes.as_slice(), &[1, 2, 4], &sequences, PcResults::new(&sequences)),
+            Err(PcCheckpointError::ConfigurationMismatch),
+        ));
+        assert!(matches!(
+            read_checkpoint(bytes.as_slice(), &[1, 2], &sequences, PcResults::new(&sequences)),
+            Err(PcCheckpointError::ConfigurationMismatch),
+        ));
+        assert!(matches!(
+            read_checkpoint(&bytes[..bytes.len() - 1], &[1, 2, 3], &sequences, PcResults::new(&sequences)),
+            Err(PcCheckpointError::InvalidFormat),
+        ));
+        assert!(matches!(
+            read_checkpoint(&bytes[1..], &[1, 2, 3], &sequences, PcResults::new(&sequences)),
+            Err(PcCheckpointError::InvalidFormat),
+        ));
+    }
+}
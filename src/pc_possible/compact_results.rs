@@ -0,0 +1,293 @@
+use bitvec::prelude::*;
+
+use crate::pc_possible::PcResults;
+use crate::{Pattern, ShapeSequence};
+
+/// The destination of the results written by the executor.
+pub(crate) trait ResultsSink {
+    fn get(&self, sequence: &ShapeSequence) -> Option<bool>;
+
+    fn accept_if_present(&mut self, sequence: &ShapeSequence, succeed: bool) -> bool;
+}
+
+impl ResultsSink for PcResults {
+    #[inline]
+    fn get(&self, sequence: &ShapeSequence) -> Option<bool> {
+        PcResults::get(self, sequence)
+    }
+
+    #[inline]
+    fn accept_if_present(&mut self, sequence: &ShapeSequence, succeed: bool) -> bool {
+        PcResults::accept_if_present(self, sequence, succeed)
+    }
+}
+
+impl ResultsSink for CompactPcResults {
+    #[inline]
+    fn get(&self, sequence: &ShapeSequence) -> Option<bool> {
+        CompactPcResults::get(self, sequence)
+    }
+
+    #[inline]
+    fn accept_if_present(&mut self, sequence: &ShapeSequence, succeed: bool) -> bool {
+        CompactPcResults::accept_if_present(self, sequence, succeed)
+    }
+}
+
+/// Holds the results of Perfect Clears in two bitsets indexed by the rank of the sequence in the pattern.
+/// See `Pattern::rank_of()` for the rank.
+///
+/// It has the same 3-states and queries as `PcResults`, but does not hold the sequences themselves.
+/// It takes about 2 bits per sequence, so it's suitable for huge patterns.
+/// The sequences are restored from the ranks when iterating, in the enumeration order of the pattern.
+///
+/// If the pattern represents the same sequence more than once, only the first one is the key, as `PcResults` does.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct CompactPcResults {
+    pattern: Pattern,
+    accepted: BitVec,
+    succeeded: BitVec,
+
+    // `None` if the pattern has no duplicates. Otherwise, marks the ranks that are the keys.
+    keys: Option<BitVec>,
+
+    allows_hold: bool,
+
+    count_keys: usize,
+    count_succeed: u64,
+    count_failed: u64,
+}
+
+impl CompactPcResults {
+    /// Makes the results with all sequences of the pattern pending.
+    pub fn new(pattern: &Pattern) -> Self {
+        let len = pattern.len_shapes_vec();
+
+        let keys = pattern.has_duplicates().then(|| {
+            (0..len)
+                .map(|rank| {
+                    let sequence = pattern.sequence_at(rank).unwrap();
+                    pattern.rank_of(sequence.shapes()) == Some(rank)
+                })
+                .collect::<BitVec>()
+        });
+        let count_keys = keys.as_ref().map_or(len, |keys| keys.count_ones());
+
+        Self {
+            pattern: pattern.clone(),
+            accepted: BitVec::repeat(false, len),
+            succeeded: BitVec::repeat(false, len),
+            keys,
+            allows_hold: false,
+            count_keys,
+            count_succeed: 0,
+            count_failed: 0,
+        }
+    }
+
+    /// Records whether the hold was allowed in the search. Set by the executors.
+    #[inline]
+    pub(crate) fn with_allows_hold(mut self, allows_hold: bool) -> Self {
+        self.allows_hold = allows_hold;
+        self
+    }
+
+    /// Returns `true` if the results were searched with the hold. It's passed on to `to_pc_results()`.
+    #[inline]
+    pub fn allows_hold(&self) -> bool {
+        self.allows_hold
+    }
+
+    /// Returns the pattern that defines the ranks.
+    #[inline]
+    pub fn pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+
+    /// Records the result of the sequence. Returns `false` if the pattern does not represent the sequence.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::pc_possible::CompactPcResults;
+    /// use PatternElement::*;
+    /// use Shape::*;
+    ///
+    /// let pattern = Pattern::try_from(vec![One(I), Permutation(ShapeCounter::one_of_each(), 2)]).unwrap();
+    /// let mut results = CompactPcResults::new(&pattern);
+    /// assert_eq!(results.count_keys(), 42);
+    ///
+    /// assert!(results.accept_if_present(&ShapeSequence::new(vec![I, T, O]), true));
+    /// assert!(results.accept_if_present(&ShapeSequence::new(vec![I, O, T]), false));
+    /// assert!(!results.accept_if_present(&ShapeSequence::new(vec![T, I, O]), true));
+    ///
+    /// assert_eq!(results.get(&ShapeSequence::new(vec![I, T, O])), Some(true));
+    /// assert_eq!(results.get(&ShapeSequence::new(vec![I, O, T])), Some(false));
+    /// assert_eq!(results.get(&ShapeSequence::new(vec![I, T, S])), None);
+    /// assert_eq!(results.count_succeed(), 1);
+    /// assert_eq!(results.count_failed(), 1);
+    /// assert_eq!(results.count_pending(), 40);
+    /// ```
+    #[inline]
+    pub fn accept_if_present(&mut self, sequence: &ShapeSequence, succeed: bool) -> bool {
+        match self.pattern.rank_of(sequence.shapes()) {
+            Some(rank) => {
+                self.accept_rank(rank, succeed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn accept_rank(&mut self, rank: usize, succeed: bool) {
+        match self.get_rank(rank) {
+            Some(true) => self.count_succeed -= 1,
+            Some(false) => self.count_failed -= 1,
+            None => {}
+        }
+        self.accepted.set(rank, true);
+        self.succeeded.set(rank, succeed);
+        if succeed {
+            self.count_succeed += 1;
+        } else {
+            self.count_failed += 1;
+        }
+    }
+
+    #[inline]
+    fn get_rank(&self, rank: usize) -> Option<bool> {
+        self.accepted[rank].then(|| self.succeeded[rank])
+    }
+
+    #[inline]
+    fn is_key(&self, rank: usize) -> bool {
+        !matches!(&self.keys, Some(keys) if !keys[rank])
+    }
+
+    /// Returns the result of a shape sequence. Returns `None` if it's pending or not in the pattern.
+    #[inline]
+    pub fn get(&self, sequence: &ShapeSequence) -> Option<bool> {
+        self.pattern.rank_of(sequence.shapes()).and_then(|rank| self.get_rank(rank))
+    }
+
+    /// Returns accepted shape sequence in the enumeration order of the pattern.
+    pub fn accepted_shape_sequences(&self) -> Vec<ShapeSequence> {
+        self.accepted.iter_ones()
+            .filter(|&rank| self.is_key(rank))
+            .map(|rank| self.pattern.sequence_at(rank).unwrap())
+            .collect()
+    }
+
+    /// Returns the pair of shape sequence and result in the enumeration order of the pattern.
+    /// The sequences are made from the ranks on the fly.
+    pub fn iter(&self) -> impl Iterator<Item=(ShapeSequence, Option<bool>)> + '_ {
+        (0..self.accepted.len())
+            .filter(|&rank| self.is_key(rank))
+            .map(|rank| (self.pattern.sequence_at(rank).unwrap(), self.get_rank(rank)))
+    }
+
+    /// Converts into `PcResults`, which holds the sequences.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::pc_possible::CompactPcResults;
+    /// use PatternElement::*;
+    /// use Shape::*;
+    ///
+    /// let pattern = Pattern::try_from(vec![Permutation(ShapeCounter::one_of_each(), 2)]).unwrap();
+    /// let mut results = CompactPcResults::new(&pattern);
+    /// results.accept_if_present(&ShapeSequence::new(vec![T, I]), true);
+    ///
+    /// let results = results.to_pc_results();
+    /// assert_eq!(results.count_keys(), 42);
+    /// assert_eq!(results.get(&ShapeSequence::new(vec![T, I])), Some(true));
+    /// ```
+    pub fn to_pc_results(&self) -> PcResults {
+        let sequences = self.iter().map(|(sequence, _)| sequence).collect();
+        let mut results = PcResults::new(&sequences).with_allows_hold(self.allows_hold);
+        for (sequence, result) in self.iter() {
+            if let Some(succeed) = result {
+                results.accept_if_present(&sequence, succeed);
+            }
+        }
+        results
+    }
+
+    /// Returns the count of shape sequences found to be succeed.
+    #[inline]
+    pub fn count_succeed(&self) -> u64 {
+        self.count_succeed
+    }
+
+    /// Returns the count of shape sequences found to be failed.
+    #[inline]
+    pub fn count_failed(&self) -> u64 {
+        self.count_failed
+    }
+
+    /// Returns the count of shape sequences for which results are found.
+    #[inline]
+    pub fn count_accepted(&self) -> u64 {
+        self.count_succeed + self.count_failed
+    }
+
+    /// Returns the count of shape sequences for which results are not yet found.
+    #[inline]
+    pub fn count_pending(&self) -> u64 {
+        self.count_keys as u64 - self.count_accepted()
+    }
+
+    /// Return the count of all shape sequences independent of the result.
+    #[inline]
+    pub fn count_keys(&self) -> usize {
+        self.count_keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitris::prelude::*;
+    use itertools::Itertools;
+
+    use crate::pc_possible::{CompactPcResults, PcResults};
+    use crate::{Pattern, PatternElement, ShapeCounter, ShapeSequence};
+
+    #[test]
+    fn same_as_pc_results() {
+        use PatternElement::*;
+        use Shape::*;
+
+        // `[TTIO]p3` has duplicates.
+        let pattern = Pattern::try_from(vec![
+            Permutation(ShapeCounter::from(vec![T, T, I, O]), 3),
+            Wildcard,
+        ]).unwrap();
+        let sequences = pattern.to_sequences();
+
+        let mut compact = CompactPcResults::new(&pattern);
+        let mut results = PcResults::new(&sequences);
+        assert_eq!(compact.count_keys(), results.count_keys());
+
+        for (index, sequence) in sequences.iter().enumerate().step_by(3) {
+            assert!(compact.accept_if_present(sequence, index % 2 == 0));
+            results.accept_if_present(sequence, index % 2 == 0);
+        }
+
+        assert_eq!(compact.count_succeed(), results.count_succeed());
+        assert_eq!(compact.count_failed(), results.count_failed());
+        assert_eq!(compact.count_pending(), results.count_pending());
+        assert_eq!(
+            compact.accepted_shape_sequences(),
+            results.accepted_shape_sequences().into_iter().cloned().collect_vec(),
+        );
+        assert_eq!(
+            compact.iter().collect_vec(),
+            results.iter().map(|(sequence, result)| (sequence.clone(), *result)).collect_vec(),
+        );
+        assert_eq!(compact.to_pc_results(), results);
+
+        // Overwrites.
+        let sequence = ShapeSequence::new(vec![T, T, I, O]);
+        compact.accept_if_present(&sequence, true);
+        compact.accept_if_present(&sequence, false);
+        results.accept_if_present(&sequence, false);
+        assert_eq!(compact.to_pc_results(), results);
+    }
+}
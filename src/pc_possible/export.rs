@@ -0,0 +1,194 @@
+use std::io::Write;
+
+use crate::pc_possible::PcResults;
+use crate::ShapeSequence;
+
+#[inline]
+fn sequence_text(sequence: &ShapeSequence) -> String {
+    sequence.shapes().iter().map(|shape| format!("{:?}", shape)).collect()
+}
+
+#[inline]
+fn status_text(result: Option<bool>) -> &'static str {
+    match result {
+        Some(true) => "succeed",
+        Some(false) => "failed",
+        None => "pending",
+    }
+}
+
+impl PcResults {
+    /// Returns the success rate of the accepted sequences in the range 0.0 to 1.0. Returns 0.0 if no sequences are accepted.
+    #[inline]
+    fn accepted_success_rate(&self) -> f64 {
+        if self.count_accepted() == 0 {
+            return 0.0;
+        }
+        self.count_succeed() as f64 / self.count_accepted() as f64
+    }
+
+    /// Returns the sequences with the results in lexicographic order of the shapes (T, I, O, L, J, S, Z).
+    fn sorted_rows(&self) -> Vec<(String, &'static str)> {
+        self.iter_sorted()
+            .map(|(sequence, result)| (sequence_text(sequence), status_text(*result)))
+            .collect()
+    }
+
+    /// Writes the results in CSV.
+    ///
+    /// The rows are `<sequence>,<status>` in lexicographic order of the shapes (T, I, O, L, J, S, Z),
+    /// so the outputs of the same pattern can be compared line by line.
+    /// The status is one of `succeed`, `failed` and `pending`.
+    /// The summary block follows after an empty line.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::pc_possible::PcResults;
+    /// use Shape::*;
+    ///
+    /// let mut results = PcResults::new(&vec![
+    ///     ShapeSequence::new(vec![O, T]),
+    ///     ShapeSequence::new(vec![T, O]),
+    ///     ShapeSequence::new(vec![T, I]),
+    /// ]);
+    /// results.accept_if_present(&ShapeSequence::new(vec![T, O]), true);
+    /// results.accept_if_present(&ShapeSequence::new(vec![O, T]), false);
+    ///
+    /// let mut bytes = Vec::<u8>::new();
+    /// results.write_csv(&mut bytes).unwrap();
+    /// assert_eq!(String::from_utf8(bytes).unwrap(), "\
+    /// sequence,status
+    /// TI,pending
+    /// TO,succeed
+    /// OT,failed
+    ///
+    /// summary,value
+    /// succeed,1
+    /// failed,1
+    /// pending,1
+    /// total,3
+    /// success_rate,0.5
+    /// ");
+    /// ```
+    pub fn write_csv(&self, mut writer: impl Write) -> std::io::Result<()> {
+        writeln!(writer, "sequence,status")?;
+        for (sequence, status) in self.sorted_rows() {
+            writeln!(writer, "{},{}", sequence, status)?;
+        }
+
+        writeln!(writer)?;
+        writeln!(writer, "summary,value")?;
+        writeln!(writer, "succeed,{}", self.count_succeed())?;
+        writeln!(writer, "failed,{}", self.count_failed())?;
+        writeln!(writer, "pending,{}", self.count_pending())?;
+        writeln!(writer, "total,{}", self.count_keys())?;
+        writeln!(writer, "success_rate,{}", self.accepted_success_rate())?;
+        writer.flush()
+    }
+
+    /// Writes the results in JSON.
+    ///
+    /// The object has `summary` and `sequences`. The sequences are in the same order as `write_csv()`.
+    /// The success rate is of the accepted sequences, and it's 0 if no sequences are accepted.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::pc_possible::PcResults;
+    /// use Shape::*;
+    ///
+    /// let mut results = PcResults::new(&vec![
+    ///     ShapeSequence::new(vec![O, T]),
+    ///     ShapeSequence::new(vec![T, O]),
+    /// ]);
+    /// results.accept_if_present(&ShapeSequence::new(vec![T, O]), true);
+    ///
+    /// let mut bytes = Vec::<u8>::new();
+    /// results.write_json(&mut bytes).unwrap();
+    /// assert_eq!(String::from_utf8(bytes).unwrap(), r#"{
+    ///   "summary": {"succeed": 1, "failed": 0, "pending": 1, "total": 2, "success_rate": 1},
+    ///   "sequences": [
+    ///     {"sequence": "TO", "status": "succeed"},
+    ///     {"sequence": "OT", "status": "pending"}
+    ///   ]
+    /// }
+    /// "#);
+    /// ```
+    pub fn write_json(&self, mut writer: impl Write) -> std::io::Result<()> {
+        writeln!(writer, "{{")?;
+        writeln!(
+            writer,
+            r#"  "summary": {{"succeed": {}, "failed": {}, "pending": {}, "total": {}, "success_rate": {}}},"#,
+            self.count_succeed(), self.count_failed(), self.count_pending(), self.count_keys(), self.accepted_success_rate(),
+        )?;
+
+        let rows = self.sorted_rows();
+        if rows.is_empty() {
+            writeln!(writer, r#"  "sequences": []"#)?;
+        } else {
+            writeln!(writer, r#"  "sequences": ["#)?;
+            for (index, (sequence, status)) in rows.iter().enumerate() {
+                let separator = if index + 1 < rows.len() { "," } else { "" };
+                writeln!(writer, r#"    {{"sequence": "{}", "status": "{}"}}{}"#, sequence, status, separator)?;
+            }
+            writeln!(writer, "  ]")?;
+        }
+        writeln!(writer, "}}")?;
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitris::prelude::*;
+    use itertools::Itertools;
+
+    use crate::pc_possible::PcResults;
+    use crate::ShapeSequence;
+
+    #[test]
+    fn stable_ordering() {
+        use Shape::*;
+
+        let sequences = [T, I, O, S].into_iter()
+            .permutations(3)
+            .map(ShapeSequence::new)
+            .collect_vec();
+
+        let mut forward = PcResults::new(&sequences);
+        let mut backward = PcResults::new(&sequences.iter().rev().cloned().collect());
+        for (index, sequence) in sequences.iter().enumerate() {
+            forward.accept_if_present(sequence, index % 3 == 0);
+        }
+        for (index, sequence) in sequences.iter().enumerate().rev() {
+            backward.accept_if_present(sequence, index % 3 == 0);
+        }
+
+        let mut csv = (Vec::<u8>::new(), Vec::<u8>::new());
+        forward.write_csv(&mut csv.0).unwrap();
+        backward.write_csv(&mut csv.1).unwrap();
+        assert_eq!(csv.0, csv.1);
+
+        let mut json = (Vec::<u8>::new(), Vec::<u8>::new());
+        forward.write_json(&mut json.0).unwrap();
+        backward.write_json(&mut json.1).unwrap();
+        assert_eq!(json.0, json.1);
+
+        let csv = String::from_utf8(csv.0).unwrap();
+        let lines = csv.lines().collect_vec();
+        assert_eq!(lines.len(), 1 + 24 + 1 + 6);
+        assert_eq!(lines[1], "TIO,succeed");
+        assert_eq!(lines[24], "SOI,failed");
+        assert_eq!(lines[31], "success_rate,0.3333333333333333");
+    }
+
+    #[test]
+    fn empty_json() {
+        let results = PcResults::new(&vec![]);
+        let mut bytes = Vec::<u8>::new();
+        results.write_json(&mut bytes).unwrap();
+        assert_eq!(String::from_utf8(bytes).unwrap(), concat!(
+            "{\n",
+            r#"  "summary": {"succeed": 0, "failed": 0, "pending": 0, "total": 0, "success_rate": 0},"#, "\n",
+            r#"  "sequences": []"#, "\n",
+            "}\n",
+        ));
+    }
+}
@@ -0,0 +1,371 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use bitris::prelude::*;
+use fxhash::FxHashMap;
+use itertools::Itertools;
+
+use crate::internals::FuzzyShape;
+use crate::pc_possible::PcResults;
+use crate::{ForEachVisitor, ShapeCounter, ShapeSequence};
+
+/// Represents failed sequences compactly. `None` is a wildcard (`*`) that matches any shape.
+///
+/// A wildcard only stands for the shapes that are in the results.
+/// For example, in the results of `[TIO]p2`, `T*` stands for `TI` and `TO`.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct WildcardOrder {
+    /// The shapes of the order. `None` is a wildcard.
+    pub shapes: Vec<Option<Shape>>,
+
+    /// `true` if the sequences equivalent under the hold are merged into this one, which is the first of them.
+    /// They can be placed in the same orders because either of the first two can be placed first.
+    pub merged_by_hold: bool,
+
+    /// The count of failed sequences represented by this order.
+    pub count_sequences: u64,
+}
+
+// The shapes of a wildcard order and whether it's merged by the hold.
+type WildcardKey = (Vec<Option<Shape>>, bool);
+
+impl fmt::Display for WildcardOrder {
+    /// Formats like `TI*O`. If merged by the hold, the first two shapes are enclosed in brackets like `[TI]*O`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, shape) in self.shapes.iter().enumerate() {
+            if self.merged_by_hold && index == 0 {
+                write!(f, "[")?;
+            }
+            match shape {
+                Some(shape) => write!(f, "{:?}", shape)?,
+                None => write!(f, "*")?,
+            }
+            if self.merged_by_hold && index == 1 {
+                write!(f, "]")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PcResults {
+    /// Returns the failed sequences in lexicographic order of the shapes (T, I, O, L, J, S, Z).
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::pc_possible::PcResults;
+    /// use Shape::*;
+    ///
+    /// let mut results = PcResults::new(&vec![
+    ///     ShapeSequence::new(vec![O, T]),
+    ///     ShapeSequence::new(vec![T, O]),
+    ///     ShapeSequence::new(vec![I, O]),
+    /// ]);
+    /// results.accept_if_present(&ShapeSequence::new(vec![O, T]), false);
+    /// results.accept_if_present(&ShapeSequence::new(vec![T, O]), false);
+    /// results.accept_if_present(&ShapeSequence::new(vec![I, O]), true);
+    ///
+    /// assert_eq!(results.failed_sequences(), vec![
+    ///     &ShapeSequence::new(vec![T, O]),
+    ///     &ShapeSequence::new(vec![O, T]),
+    /// ]);
+    /// ```
+    pub fn failed_sequences(&self) -> Vec<&ShapeSequence> {
+        self.iter()
+            .filter(|(_, result)| **result == Some(false))
+            .map(|(sequence, _)| sequence)
+            .sorted()
+            .collect()
+    }
+
+    /// Returns the failed sequences grouped by the first `depth` shapes.
+    /// The groups and the sequences in each group are in lexicographic order.
+    pub fn failed_by_prefix(&self, depth: usize) -> BTreeMap<Vec<Shape>, Vec<&ShapeSequence>> {
+        let mut groups = BTreeMap::<Vec<Shape>, Vec<&ShapeSequence>>::new();
+        for sequence in self.failed_sequences() {
+            let shapes = sequence.shapes();
+            let prefix = shapes[..depth.min(shapes.len())].to_vec();
+            groups.entry(prefix).or_default().push(sequence);
+        }
+        groups
+    }
+
+    /// Returns the failed sequences grouped by the count of each shape, regardless of the order.
+    /// The sequences in each group are in lexicographic order.
+    pub fn failed_by_shape_counter(&self) -> BTreeMap<ShapeCounter, Vec<&ShapeSequence>> {
+        let mut groups = BTreeMap::<ShapeCounter, Vec<&ShapeSequence>>::new();
+        for sequence in self.failed_sequences() {
+            groups.entry(ShapeCounter::from(sequence.shapes().to_vec())).or_default().push(sequence);
+        }
+        groups
+    }
+
+    /// Returns the failed sequences collapsed into wildcard orders for human reading.
+    ///
+    /// Failed sequences that differ in only one shape are merged into a wildcard if all sequences matched by it failed.
+    /// Pending sequences are treated as not failed, so they are never covered by a wildcard.
+    /// If the results were searched with the hold, the sequences equivalent under the hold are merged into one order
+    /// if all of them failed. See `allows_hold()`.
+    ///
+    /// The orders are in lexicographic order where `*` comes after all shapes.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::pc_possible::PcResults;
+    /// use Shape::*;
+    ///
+    /// let sequences = Pattern::try_from(vec![
+    ///     PatternElement::Permutation(ShapeCounter::from(vec![T, I, O]), 3),
+    /// ]).unwrap().to_sequences();
+    /// let mut results = PcResults::new(&sequences);
+    /// for sequence in &sequences {
+    ///     // Fails if it starts with T and I.
+    ///     let shapes = sequence.shapes();
+    ///     results.accept_if_present(sequence, !(shapes[..2] == [T, I] || shapes[..2] == [I, T]));
+    /// }
+    ///
+    /// let collapsed = results.collapse_failed();
+    /// assert_eq!(collapsed.iter().map(|order| order.to_string()).collect::<Vec<_>>(), vec!["TIO", "ITO"]);
+    /// assert_eq!(collapsed[0].count_sequences, 1);
+    /// ```
+    pub fn collapse_failed(&self) -> Vec<WildcardOrder> {
+        let to_wildcard = |shapes: &[Shape]| shapes.iter().map(|&shape| Some(shape)).collect_vec();
+
+        // Each sequence is a unit. With the hold, the sequences equivalent under the hold are also a unit,
+        // which is represented by the first of them and marked as merged.
+        // The units are paired with the count of sequences in the results, and the failed ones are the first orders.
+        let mut counter = WildcardCounter::default();
+        let mut orders = Vec::<(WildcardKey, u64)>::new();
+
+        for (sequence, result) in self.iter() {
+            counter.counts.insert((to_wildcard(sequence.shapes()), false), 1);
+            if !self.allows_hold && *result == Some(false) {
+                orders.push(((to_wildcard(sequence.shapes()), false), 1));
+            }
+        }
+
+        if self.allows_hold {
+            let mut groups = FxHashMap::<Vec<Vec<FuzzyShape>>, Vec<(&ShapeSequence, &Option<bool>)>>::default();
+            for (sequence, result) in self.iter() {
+                groups.entry(hold_key(sequence)).or_default().push((sequence, result));
+            }
+
+            for mut group in groups.into_values() {
+                group.sort();
+                let count_sequences = group.len() as u64;
+                let all_failed = group.iter().all(|(_, result)| **result == Some(false));
+                if 1 < group.len() {
+                    let unit = (to_wildcard(group[0].0.shapes()), true);
+                    counter.counts.insert(unit.clone(), count_sequences);
+                    if all_failed {
+                        orders.push((unit, count_sequences));
+                        continue;
+                    }
+                }
+                orders.extend(
+                    group.into_iter()
+                        .filter(|(_, result)| **result == Some(false))
+                        .map(|(sequence, _)| ((to_wildcard(sequence.shapes()), false), 1)),
+                );
+            }
+        }
+
+        let len = orders.first().map_or(0, |((shapes, _), _)| shapes.len());
+
+        for index in (0..len).rev() {
+            let mut groups = BTreeMap::<WildcardKey, Vec<(WildcardKey, u64)>>::new();
+            for ((shapes, merged), count) in orders {
+                let mut key = shapes.clone();
+                key[index] = None;
+                groups.entry((key, merged)).or_default().push(((shapes, merged), count));
+            }
+
+            orders = Vec::new();
+            for (wildcard, group) in groups {
+                let count_failed = group.iter().map(|(_, count)| count).sum::<u64>();
+                if 1 < group.len() && count_failed == counter.count(&wildcard) {
+                    orders.push((wildcard, count_failed));
+                } else {
+                    orders.extend(group);
+                }
+            }
+        }
+
+        let mut collapsed = orders.into_iter()
+            .map(|((shapes, merged_by_hold), count_sequences)| WildcardOrder { shapes, merged_by_hold, count_sequences })
+            .collect_vec();
+
+        // Sorts so that `*` comes after all shapes.
+        collapsed.sort_by_key(|order| {
+            let shapes = order.shapes.iter().map(|shape| shape.map_or(7, |shape| shape as usize)).collect_vec();
+            (shapes, order.merged_by_hold)
+        });
+        collapsed
+    }
+}
+
+/// Collects the inputs inferred from a sequence.
+struct InputCollector {
+    inputs: Vec<Vec<FuzzyShape>>,
+}
+
+impl ForEachVisitor<[FuzzyShape]> for InputCollector {
+    #[inline]
+    fn visit(&mut self, fuzzy_shapes: &[FuzzyShape]) {
+        self.inputs.push(fuzzy_shapes.to_vec());
+    }
+}
+
+/// Returns the key that is the same among the sequences equivalent under the hold.
+///
+/// The orders that can be placed from a sequence with the hold are the reverse of the inputs inferred from the reversed sequence.
+/// So the sequences with the same inferred inputs can be placed in the same orders.
+fn hold_key(sequence: &ShapeSequence) -> Vec<Vec<FuzzyShape>> {
+    let shapes = sequence.shapes();
+    if shapes.is_empty() {
+        return Vec::new();
+    }
+
+    let reversed = ShapeSequence::new(shapes.iter().rev().copied().collect());
+    let mut collector = InputCollector { inputs: Vec::new() };
+    reversed.infer_input_walk(shapes.len(), &mut collector);
+
+    collector.inputs.sort();
+    collector.inputs.dedup();
+    collector.inputs
+}
+
+/// Counts the sequences in the results matched by wildcard orders.
+///
+/// It's indexed by the units without wildcards first.
+/// The count of a wildcard order is the sum of the ones with each shape at its first wildcard, which are memoized.
+#[derive(Default)]
+struct WildcardCounter {
+    counts: FxHashMap<WildcardKey, u64>,
+}
+
+impl WildcardCounter {
+    fn count(&mut self, key: &WildcardKey) -> u64 {
+        if let Some(&count) = self.counts.get(key) {
+            return count;
+        }
+
+        let count = match key.0.iter().position(|shape| shape.is_none()) {
+            Some(index) => Shape::all_iter()
+                .map(|shape| {
+                    let mut filled = key.clone();
+                    filled.0[index] = Some(shape);
+                    self.count(&filled)
+                })
+                .sum(),
+            None => 0,
+        };
+        self.counts.insert(key.clone(), count);
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitris::prelude::*;
+    use itertools::Itertools;
+
+    use crate::pc_possible::PcResults;
+    use crate::{Pattern, PatternElement, ShapeCounter, ShapeSequence};
+
+    fn make_results(pattern: &Pattern, fails: impl Fn(&[Shape]) -> bool) -> PcResults {
+        let sequences = pattern.to_sequences();
+        let mut results = PcResults::new(&sequences);
+        for sequence in &sequences {
+            results.accept_if_present(sequence, !fails(sequence.shapes()));
+        }
+        results
+    }
+
+    #[test]
+    fn group_failures() {
+        use Shape::*;
+
+        let pattern = Pattern::try_from(vec![
+            PatternElement::Permutation(ShapeCounter::one_of_each(), 3),
+        ]).unwrap();
+        let results = make_results(&pattern, |shapes| shapes.contains(&S) && shapes.contains(&Z));
+        assert_eq!(results.failed_sequences().len(), 30);
+
+        let by_prefix = results.failed_by_prefix(1);
+        assert_eq!(by_prefix.keys().cloned().collect_vec(), vec![
+            vec![T], vec![I], vec![O], vec![L], vec![J], vec![S], vec![Z],
+        ]);
+        assert_eq!(by_prefix[&vec![T]], vec![
+            &ShapeSequence::new(vec![T, S, Z]),
+            &ShapeSequence::new(vec![T, Z, S]),
+        ]);
+        assert_eq!(by_prefix[&vec![S]].len(), 10);
+
+        let by_counter = results.failed_by_shape_counter();
+        assert_eq!(by_counter.len(), 5);
+        assert!(by_counter.values().all(|sequences| sequences.len() == 6));
+        assert_eq!(by_counter[&ShapeCounter::from(vec![T, S, Z])][0], &ShapeSequence::new(vec![T, S, Z]));
+    }
+
+    #[test]
+    fn collapse_into_wildcards() {
+        use Shape::*;
+
+        // The last shape does not matter.
+        let pattern = Pattern::try_from(vec![
+            PatternElement::Fixed(vec![T, I].try_into().unwrap()),
+            PatternElement::Wildcard,
+            PatternElement::Wildcard,
+        ]).unwrap();
+        let results = make_results(&pattern, |shapes| shapes[2] == O || shapes[2] == L);
+        let collapsed = results.collapse_failed();
+        assert_eq!(collapsed.iter().map(|order| order.to_string()).collect_vec(), vec!["TIO*", "TIL*"]);
+        assert_eq!(collapsed[0].count_sequences, 7);
+
+        // All failed.
+        // With the hold, no sequence has its equivalent in the results.
+        let results = make_results(&pattern, |_| true).with_allows_hold(true);
+        let collapsed = results.collapse_failed();
+        assert_eq!(collapsed.iter().map(|order| order.to_string()).collect_vec(), vec!["TI**"]);
+        assert_eq!(collapsed[0].count_sequences, 49);
+
+        // A pending sequence is not covered by a wildcard.
+        let sequences = pattern.to_sequences();
+        let mut results = PcResults::new(&sequences);
+        for sequence in &sequences {
+            if sequence.shapes()[2..] != [Z, Z] {
+                results.accept_if_present(sequence, false);
+            }
+        }
+        let collapsed = results.collapse_failed();
+        assert_eq!(collapsed.iter().map(|order| order.to_string()).collect_vec(), vec![
+            "TIT*", "TII*", "TIO*", "TIL*", "TIJ*", "TIS*", "TIZT", "TIZI", "TIZO", "TIZL", "TIZJ", "TIZS",
+        ]);
+    }
+
+    #[test]
+    fn collapse_by_hold() {
+        use Shape::*;
+
+        let pattern = Pattern::try_from(vec![
+            PatternElement::Permutation(ShapeCounter::one_of_each(), 3),
+        ]).unwrap();
+        let results = make_results(&pattern, |shapes| shapes[..2].contains(&S) && shapes[..2].contains(&O));
+
+        let collapsed = results.collapse_failed();
+        assert_eq!(collapsed.iter().map(|order| order.to_string()).collect_vec(), vec!["OS*", "SO*"]);
+
+        let results = results.with_allows_hold(true);
+        let collapsed = results.collapse_failed();
+        assert_eq!(collapsed.iter().map(|order| order.to_string()).collect_vec(), vec!["[OS]*"]);
+        assert_eq!(collapsed[0].count_sequences, 10);
+        assert_eq!(collapsed.iter().map(|order| order.count_sequences).sum::<u64>(), results.count_failed());
+
+        // The equivalent sequences are not merged unless all of them failed.
+        let mut results = results;
+        results.accept_if_present(&ShapeSequence::new(vec![S, O, T]), true);
+        let collapsed = results.collapse_failed();
+        assert_eq!(collapsed.iter().map(|order| order.to_string()).collect_vec(), vec![
+            "OST", "[OS]I", "[OS]L", "[OS]J", "[OS]Z",
+        ]);
+    }
+}
@@ -0,0 +1,244 @@
+use bitris::prelude::*;
+use thiserror::Error;
+
+use crate::pc_possible::{LineClearConstraints, PcGarbageResults, PcPossibleBulkExecutor, PcPossibleExecutorBulkCreationError, PcResults};
+use crate::{ClippedBoard, ClippedBoardCreationError, GarbageBoard, Pattern, RotationSystem180, SpawnRule, TSpinKind};
+
+/// A collection of errors that occur when making the executor.
+#[derive(Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum PcGarbageExecutorCreationError {
+    #[error(transparent)]
+    ClippedBoardCreation(#[from] ClippedBoardCreationError),
+    #[error(transparent)]
+    ExecutorCreation(#[from] PcPossibleExecutorBulkCreationError),
+}
+
+/// The executor to find PC possibles on a board with unknown garbage holes.
+///
+/// It holds an executor for each variant of the board, so the time grows with the count of variants.
+/// See `GarbageBoard` for the variants.
+#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub struct PcGarbageBulkExecutor<'a, T: RotationSystem180> {
+    executors: Vec<PcPossibleBulkExecutor<'a, T>>,
+}
+
+impl<'a, T: RotationSystem180> PcGarbageBulkExecutor<'a, T> {
+    /// Make PcGarbageBulkExecutor. The settings except the board are the same as `PcPossibleBulkExecutor`,
+    /// and the others can be set by the methods `with_*` for all variants.
+    ///
+    /// Returns `Err()` if the setting is incorrect or restricted for any variant.
+    /// See `PcGarbageExecutorCreationError` for error patterns.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris::prelude::*;
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::pc_possible::PcGarbageBulkExecutor;
+    ///
+    /// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    ///
+    /// // A garbage row comes, but the hole is unknown.
+    /// let garbage_board = GarbageBoard::from_str("
+    ///     XXXXXXX...
+    ///     ??????????
+    /// ").expect("Failed to create a board");
+    ///
+    /// let pattern = Pattern::try_from(vec![
+    ///     PatternElement::Wildcard,
+    /// ]).expect("Failed to create a pattern");
+    ///
+    /// let executor = PcGarbageBulkExecutor::try_new(&move_rules, &garbage_board, 2, &pattern, true)
+    ///     .expect("Failed to create an executor");
+    ///
+    /// let results = executor.execute();
+    /// assert_eq!(results.count_variants(), 10);
+    ///
+    /// // T takes the PC only if the hole is under the middle.
+    /// assert_eq!(results.get(&ShapeSequence::new(vec![Shape::T])), Some(0.1));
+    /// assert_eq!(results.get(&ShapeSequence::new(vec![Shape::O])), Some(0.0));
+    ///
+    /// // J, T and L for each of the three holes
+    /// assert_eq!(results.count_succeed(), 3);
+    /// assert_eq!(results.success_rate(), Some(3.0 / 70.0));
+    /// ```
+    pub fn try_new(
+        move_rules: &'a MoveRules<T>,
+        garbage_board: &GarbageBoard,
+        height: u32,
+        pattern: &'a Pattern,
+        allows_hold: bool,
+    ) -> Result<Self, PcGarbageExecutorCreationError> {
+        let executors = garbage_board.variants().into_iter()
+            .map(|board| {
+                let clipped_board = ClippedBoard::try_new(board, height)?;
+                Ok(PcPossibleBulkExecutor::try_new(move_rules, clipped_board, pattern, allows_hold)?)
+            })
+            .collect::<Result<Vec<_>, PcGarbageExecutorCreationError>>()?;
+
+        Ok(Self { executors })
+    }
+
+    /// Sets the maximum count of states memorized across sequences, for each variant.
+    /// See `PcPossibleBulkExecutor::with_transposition_capacity()` for more details.
+    pub fn with_transposition_capacity(self, capacity: usize) -> Self {
+        self.map(|executor| executor.with_transposition_capacity(capacity))
+    }
+
+    /// Sets the constraints on line clears. See `PcPossibleBulkExecutor::with_line_clear_constraints()` for more details.
+    pub fn with_line_clear_constraints(self, constraints: LineClearConstraints) -> Self {
+        self.map(|executor| executor.with_line_clear_constraints(constraints.clone()))
+    }
+
+    /// Requires one of the T-spins in the PC. See `PcPossibleBulkExecutor::with_required_t_spins()` for more details.
+    pub fn with_required_t_spins(self, kinds: &[TSpinKind]) -> Self {
+        self.map(|executor| executor.with_required_t_spins(kinds))
+    }
+
+    /// Sets the placements where pieces spawn. See `PcPossibleBulkExecutor::try_with_spawn_rule()` for more details.
+    ///
+    /// Returns `Err()` if the spawn is blocked on any variant.
+    pub fn try_with_spawn_rule(self, spawn_rule: SpawnRule) -> Result<Self, PcGarbageExecutorCreationError> {
+        let executors = self.executors.into_iter()
+            .map(|executor| executor.try_with_spawn_rule(spawn_rule))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { executors })
+    }
+
+    /// Limits each placement to the count of inputs. See `PcPossibleBulkExecutor::with_max_inputs()` for more details.
+    pub fn with_max_inputs(self, max_inputs: Option<u32>) -> Self {
+        self.map(|executor| executor.with_max_inputs(max_inputs))
+    }
+
+    /// Enables the mirror symmetry reduction. See `PcPossibleBulkExecutor::with_mirror_reduction()` for more details.
+    pub fn with_mirror_reduction(self, enabled: bool) -> Self {
+        self.map(|executor| executor.with_mirror_reduction(enabled))
+    }
+
+    /// Returns the executors of the variants, in the order of `GarbageBoard::variants()`.
+    #[inline]
+    pub fn executors(&self) -> &[PcPossibleBulkExecutor<'a, T>] {
+        self.executors.as_slice()
+    }
+
+    /// Start the search for PC possible on each variant.
+    pub fn execute(&self) -> PcGarbageResults {
+        self.collect_results(|executor| executor.execute())
+    }
+
+    fn map(self, f: impl FnMut(PcPossibleBulkExecutor<'a, T>) -> PcPossibleBulkExecutor<'a, T>) -> Self {
+        Self { executors: self.executors.into_iter().map(f).collect() }
+    }
+
+    fn collect_results(&self, execute: impl Fn(&PcPossibleBulkExecutor<'a, T>) -> PcResults) -> PcGarbageResults {
+        let variants = self.executors.iter().map(|executor| executor.clipped_board().board()).collect();
+        let results = self.executors.iter().map(execute).collect();
+        PcGarbageResults::new(variants, results)
+    }
+}
+
+impl<'a, T: RotationSystem180 + Sync> PcGarbageBulkExecutor<'a, T> {
+    /// Start the search for PC possible on each variant, one after another on multiple threads.
+    /// See `PcPossibleBulkExecutor::execute_in_parallel()` for more details.
+    ///
+    /// Panics if `threads` is 0.
+    pub fn execute_in_parallel(&self, threads: usize) -> PcGarbageResults {
+        self.collect_results(|executor| executor.execute_in_parallel(threads))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::pc_possible::{LineClearConstraints, PcGarbageBulkExecutor, PcGarbageExecutorCreationError, PcPossibleBulkExecutor, PcPossibleExecutorBulkCreationError};
+    use crate::{ClippedBoard, ClippedBoardCreationError, GarbageBoard, Pattern, PatternElement, ShapeCounter, SpawnRow, SpawnRule};
+
+    #[test]
+    fn same_as_each_board() {
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let garbage_board = GarbageBoard::from_str("
+            XXXX......
+            XXXX......
+            XXXXXXX...
+            XXXX??????
+        ").unwrap();
+        let pattern = Pattern::try_from(vec![
+            PatternElement::Permutation(ShapeCounter::one_of_each(), 4),
+        ]).unwrap();
+
+        let executor = PcGarbageBulkExecutor::try_new(&move_rules, &garbage_board, 4, &pattern, true).unwrap();
+        let results = executor.execute();
+        assert_eq!(results.count_variants(), 6);
+
+        for (variant, variant_results) in results.iter() {
+            let clipped_board = ClippedBoard::try_new(*variant, 4).unwrap();
+            let expected = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap().execute();
+            assert_eq!(variant_results, &expected);
+        }
+
+        let rate = results.success_rate().unwrap();
+        assert!(0.0 < rate && rate < 1.0);
+        assert_eq!(results.count_succeed() + results.count_failed(), 6 * 840);
+    }
+
+    #[test]
+    fn creation_errors() {
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let pattern = Pattern::try_from(vec![
+            PatternElement::Permutation(ShapeCounter::one_of_each(), 4),
+        ]).unwrap();
+
+        let garbage_board = GarbageBoard::from_str("
+            ??????????
+            XXXXXX....
+        ").unwrap();
+        assert_eq!(
+            PcGarbageBulkExecutor::try_new(&move_rules, &garbage_board, 1, &pattern, true).unwrap_err(),
+            PcGarbageExecutorCreationError::ClippedBoardCreation(ClippedBoardCreationError::NotHeightEnough),
+        );
+        assert_eq!(
+            PcGarbageBulkExecutor::try_new(&move_rules, &garbage_board, 3, &pattern, true).unwrap_err(),
+            PcGarbageExecutorCreationError::ExecutorCreation(PcPossibleExecutorBulkCreationError::UnexpectedBoardSpaces),
+        );
+    }
+
+    #[test]
+    fn settings() {
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let garbage_board = GarbageBoard::from_str("
+            XXXX......
+            XXXXXXX...
+            XXXX??????
+        ").unwrap();
+        let pattern = Pattern::try_from(vec![
+            PatternElement::Permutation(ShapeCounter::one_of_each(), 4),
+        ]).unwrap();
+
+        let executor = PcGarbageBulkExecutor::try_new(&move_rules, &garbage_board, 3, &pattern, true).unwrap()
+            .with_transposition_capacity(0)
+            .with_line_clear_constraints(LineClearConstraints::no_line_clear_until_last())
+            .try_with_spawn_rule(SpawnRule::default().with_row(SpawnRow::Fixed(3)))
+            .unwrap();
+        let results = executor.execute();
+        assert_eq!(executor.execute_in_parallel(3), results);
+
+        for (variant, variant_results) in results.iter() {
+            let clipped_board = ClippedBoard::try_new(*variant, 3).unwrap();
+            let expected = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap()
+                .with_line_clear_constraints(LineClearConstraints::no_line_clear_until_last())
+                .try_with_spawn_rule(SpawnRule::default().with_row(SpawnRow::Fixed(3)))
+                .unwrap()
+                .execute();
+            assert_eq!(variant_results, &expected);
+        }
+
+        // Blocked on every variant
+        let spawn_rule = SpawnRule::default().with_row(SpawnRow::Fixed(0)).with_columns(0);
+        assert_eq!(
+            PcGarbageBulkExecutor::try_new(&move_rules, &garbage_board, 3, &pattern, true).unwrap()
+                .try_with_spawn_rule(spawn_rule).unwrap_err(),
+            PcGarbageExecutorCreationError::ExecutorCreation(PcPossibleExecutorBulkCreationError::SpawnIsBlocked),
+        );
+    }
+}
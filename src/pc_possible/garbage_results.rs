@@ -0,0 +1,73 @@
+use bitris::prelude::*;
+
+use crate::pc_possible::PcResults;
+use crate::ShapeSequence;
+
+/// Holds the results of Perfect Clears for each variant of a garbage board.
+///
+/// The variants are equally likely, so they are an extra dimension of probability over the sequences.
+/// The variants are in the order of `GarbageBoard::variants()`, and all results have the same sequences.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PcGarbageResults {
+    variants: Vec<Board64>,
+    results: Vec<PcResults>,
+}
+
+impl PcGarbageResults {
+    #[inline]
+    pub(crate) fn new(variants: Vec<Board64>, results: Vec<PcResults>) -> Self {
+        debug_assert_eq!(variants.len(), results.len());
+        Self { variants, results }
+    }
+
+    /// Returns the probability that the sequence can take a PC across the variants.
+    /// Returns `None` if it's not a key, or pending in any variant.
+    pub fn get(&self, sequence: &ShapeSequence) -> Option<f64> {
+        let mut count_succeed = 0;
+        for results in &self.results {
+            if results.get(sequence)? {
+                count_succeed += 1;
+            }
+        }
+        Some(count_succeed as f64 / self.variants.len() as f64)
+    }
+
+    /// Returns the probability that PC is possible with the board and sequence both unknown.
+    /// It's the success rate over all pairs of the variant and the sequence, where each pair is equally likely.
+    /// Returns `None` if there are no keys.
+    pub fn success_rate(&self) -> Option<f64> {
+        let count_accepted: u64 = self.results.iter().map(|results| results.count_accepted()).sum();
+        if count_accepted == 0 {
+            return None;
+        }
+        Some(self.count_succeed() as f64 / count_accepted as f64)
+    }
+
+    /// Returns the pairs of the variant and its results.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item=(&Board64, &PcResults)> + '_ {
+        self.variants.iter().zip(self.results.iter())
+    }
+
+    #[inline]
+    pub fn variants(&self) -> &[Board64] {
+        self.variants.as_slice()
+    }
+
+    /// Returns the count of pairs of the variant and the sequence that can take a PC.
+    #[inline]
+    pub fn count_succeed(&self) -> u64 {
+        self.results.iter().map(|results| results.count_succeed()).sum()
+    }
+
+    /// Returns the count of pairs of the variant and the sequence that cannot take a PC.
+    #[inline]
+    pub fn count_failed(&self) -> u64 {
+        self.results.iter().map(|results| results.count_failed()).sum()
+    }
+
+    #[inline]
+    pub fn count_variants(&self) -> usize {
+        self.variants.len()
+    }
+}
@@ -0,0 +1,203 @@
+use bitris::prelude::*;
+use thiserror::Error;
+
+use crate::pc_possible::{PcHeightResults, PcPossibleBulkExecutor, PcResults};
+use crate::{ClippedBoard, Pattern, RotationSystem180};
+
+/// A collection of errors that occur when making the executor.
+#[derive(Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum PcHeightExecutorCreationError {
+    #[error("Max height exceeds the upper limit. Up to 56 are supported.")]
+    MaxHeightIsTooHigh,
+    #[error("No height up to the max can take a PC with the board and pattern.")]
+    NoValidHeight,
+}
+
+/// The executor to find the lowest height at which PC is possible for each sequence.
+///
+/// It tries every valid height up to the max in ascending order.
+/// A height is valid if the board fits in it, the spaces are divisible by 4, and the pattern has enough shapes to fill them.
+/// Each height searches only the sequences not yet found at lower heights.
+#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub struct PcHeightBulkExecutor<'a, T: RotationSystem180> {
+    move_rules: &'a MoveRules<'a, T>,
+    board: Board64,
+    pattern: &'a Pattern,
+    allows_hold: bool,
+    heights: Vec<u32>,
+}
+
+impl<'a, T: RotationSystem180> PcHeightBulkExecutor<'a, T> {
+    /// Make PcHeightBulkExecutor.
+    ///
+    /// Returns `Err()` if the setting is incorrect or no height is valid.
+    /// See `PcHeightExecutorCreationError` for error patterns.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris::prelude::*;
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::pc_possible::PcHeightBulkExecutor;
+    /// use Shape::*;
+    ///
+    /// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    ///
+    /// let board = Board64::from_str("
+    ///     XXXXXX....
+    ///     XXXXXX....
+    /// ").expect("Failed to create a board");
+    ///
+    /// // `[LLJ]p3[TISZ]p4`
+    /// let pattern = Pattern::try_from(vec![
+    ///     PatternElement::Permutation(ShapeCounter::from(vec![L, L, J]), 3),
+    ///     PatternElement::Permutation(ShapeCounter::from(vec![T, I, S, Z]), 4),
+    /// ]).expect("Failed to create a pattern");
+    ///
+    /// let executor = PcHeightBulkExecutor::try_new(&move_rules, board, &pattern, true, 6)
+    ///     .expect("Failed to create an executor");
+    ///
+    /// // 3 and 5 lines have spaces not divisible by 4, and 6 lines need more shapes.
+    /// assert_eq!(executor.heights(), &[2, 4]);
+    ///
+    /// // Two L can take a 2-line PC.
+    /// let results = executor.execute();
+    /// assert_eq!(results.get(&ShapeSequence::new(vec![L, L, J, T, I, S, Z])), Some(2));
+    /// assert_eq!(results.get(&ShapeSequence::new(vec![L, J, L, T, I, S, Z])), Some(2)); // With the hold
+    /// assert_eq!(results.count_keys(), 72);
+    /// ```
+    pub fn try_new(
+        move_rules: &'a MoveRules<T>,
+        board: Board64,
+        pattern: &'a Pattern,
+        allows_hold: bool,
+        max_height: u32,
+    ) -> Result<Self, PcHeightExecutorCreationError> {
+        use PcHeightExecutorCreationError::*;
+
+        if 56 < max_height {
+            return Err(MaxHeightIsTooHigh);
+        }
+
+        let heights: Vec<u32> = (1..=max_height)
+            .filter(|&height| {
+                ClippedBoard::try_new(board, height).is_ok_and(|clipped_board| {
+                    PcPossibleBulkExecutor::try_new(move_rules, clipped_board, pattern, allows_hold).is_ok()
+                })
+            })
+            .collect();
+
+        if heights.is_empty() {
+            return Err(NoValidHeight);
+        }
+
+        Ok(Self { move_rules, board, pattern, allows_hold, heights })
+    }
+
+    /// Returns the valid heights to be searched, in ascending order.
+    #[inline]
+    pub fn heights(&self) -> &[u32] {
+        self.heights.as_slice()
+    }
+
+    /// Start the search for the lowest height of each sequence.
+    pub fn execute(&self) -> PcHeightResults {
+        let sequences = self.pattern.to_sequences();
+        let mut results = PcHeightResults::new(&sequences, self.heights.clone());
+
+        for &height in &self.heights {
+            let clipped_board = ClippedBoard::try_new(self.board, height).unwrap();
+            let executor = PcPossibleBulkExecutor::try_new(self.move_rules, clipped_board, self.pattern, self.allows_hold)
+                .unwrap();
+
+            // The sequences found at lower heights are not searched.
+            let mut pc_results = PcResults::new(&sequences);
+            for (sequence, found) in results.iter() {
+                if found.is_some() {
+                    pc_results.accept_if_present(sequence, true);
+                }
+            }
+
+            let pc_results = executor.execute_pending(pc_results);
+            for (sequence, &succeed) in pc_results.iter() {
+                if succeed == Some(true) {
+                    results.accept_if_present(sequence, height);
+                }
+            }
+
+            if results.count_failed() == 0 {
+                break;
+            }
+        }
+
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::pc_possible::{PcHeightBulkExecutor, PcHeightExecutorCreationError, PcPossibleBulkExecutor};
+    use crate::{ClippedBoard, Pattern, PatternElement, ShapeCounter};
+
+    #[test]
+    fn lowest_height() {
+        use Shape::*;
+
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let board = Board64::from_str("
+            ######....
+            ######....
+        ").unwrap();
+        let pattern = Pattern::try_from(vec![
+            PatternElement::Permutation(ShapeCounter::from(vec![L, L, J]), 3),
+            PatternElement::Permutation(ShapeCounter::from(vec![T, I, S, Z]), 4),
+        ]).unwrap();
+
+        // Without the hold, only the sequences starting with two L can take a 2-line PC.
+        let executor = PcHeightBulkExecutor::try_new(&move_rules, board, &pattern, false, 6).unwrap();
+        let results = executor.execute();
+        assert_eq!(results.searched_heights(), &[2, 4]);
+
+        // Matches the results of each height.
+        let at_height = |height: u32| {
+            let clipped_board = ClippedBoard::try_new(board, height).unwrap();
+            PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, false).unwrap().execute()
+        };
+        let (results2, results4) = (at_height(2), at_height(4));
+        for (sequence, &height) in results.iter() {
+            let expected = match (results2.get(sequence), results4.get(sequence)) {
+                (Some(true), _) => Some(2),
+                (_, Some(true)) => Some(4),
+                _ => None,
+            };
+            assert_eq!(height, expected);
+        }
+
+        let distribution = results.distribution();
+        assert_eq!(distribution.get(&2).copied(), Some(24));
+        assert!(distribution.get(&4).is_some());
+        assert_eq!(distribution.values().sum::<u64>(), results.count_succeed());
+    }
+
+    #[test]
+    fn no_valid_height() {
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let board = Board64::from_str("
+            #########.
+            ##########
+            #########.
+        ").unwrap();
+        let pattern = Pattern::try_from(vec![PatternElement::Wildcard]).unwrap();
+
+        assert_eq!(
+            PcHeightBulkExecutor::try_new(&move_rules, board, &pattern, true, 10).unwrap_err(),
+            PcHeightExecutorCreationError::NoValidHeight,
+        );
+        assert_eq!(
+            PcHeightBulkExecutor::try_new(&move_rules, board, &pattern, true, 57).unwrap_err(),
+            PcHeightExecutorCreationError::MaxHeightIsTooHigh,
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+
+use crate::internals::SequenceTable;
+use crate::ShapeSequence;
+
+/// Holds the lowest height at which a Perfect Clear is possible for each sequence.
+///
+/// The result of a sequence is `None` if PC is impossible at all searched heights.
+/// The iteration is in the order of the sequences passed to `new()`.
+#[derive(Clone, Eq, PartialEq, Default, Debug)]
+pub struct PcHeightResults {
+    heights: SequenceTable<Option<u32>>,
+
+    searched_heights: Vec<u32>,
+}
+
+impl PcHeightResults {
+    #[inline]
+    pub fn new(sequences: &Vec<ShapeSequence>, searched_heights: Vec<u32>) -> Self {
+        Self { heights: SequenceTable::new(sequences.iter().cloned(), || None), searched_heights }
+    }
+
+    /// Records the height if it's lower than the recorded one. Returns `false` if the sequence is not a key.
+    #[inline]
+    pub fn accept_if_present(&mut self, sequence: &ShapeSequence, height: u32) -> bool {
+        if let Some(recorded) = self.heights.get_mut(sequence) {
+            if !matches!(recorded, Some(recorded) if *recorded <= height) {
+                *recorded = Some(height);
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the lowest height at which PC is possible. Returns `None` if it's impossible or not a key.
+    #[inline]
+    pub fn get(&self, sequence: &ShapeSequence) -> Option<u32> {
+        self.heights.get(sequence).copied().flatten()
+    }
+
+    /// Returns the heights searched, in ascending order.
+    #[inline]
+    pub fn searched_heights(&self) -> &[u32] {
+        self.searched_heights.as_slice()
+    }
+
+    /// Returns the pair of shape sequence and result in the order of the sequences passed to `new()`.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item=(&ShapeSequence, &Option<u32>)> + '_ {
+        self.heights.iter()
+    }
+
+    /// Returns the count of sequences for each lowest height. Sequences without PC are not counted.
+    pub fn distribution(&self) -> BTreeMap<u32, u64> {
+        let mut distribution = BTreeMap::new();
+        for &height in self.heights.values().iter().flatten() {
+            *distribution.entry(height).or_default() += 1;
+        }
+        distribution
+    }
+
+    /// Returns the count of shape sequences for which PC is possible at any height.
+    #[inline]
+    pub fn count_succeed(&self) -> u64 {
+        self.heights.values().iter().flatten().count() as u64
+    }
+
+    /// Returns the count of shape sequences for which PC is impossible at all searched heights.
+    #[inline]
+    pub fn count_failed(&self) -> u64 {
+        self.count_keys() as u64 - self.count_succeed()
+    }
+
+    /// Return the count of all shape sequences independent of the result.
+    #[inline]
+    pub fn count_keys(&self) -> usize {
+        self.heights.len()
+    }
+}
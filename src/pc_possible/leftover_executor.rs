@@ -0,0 +1,299 @@
+use bitris::prelude::*;
+use fxhash::FxHashSet;
+
+use crate::input_moves::generate_moves;
+use crate::pc_possible::{validate_board, PcLeftoverResults, PcPossibleBulkExecutor, PcPossibleExecutorBulkCreationError, VerticalParity};
+use crate::{ClippedBoard, OrderCursor, Pattern, PopOp, RotationSystem180, ShapeOrder, SpawnRule};
+
+/// Dataset for detecting the same state during the leftover search.
+/// See `SearchingState` in `PcPossibleBulkExecutor` for more details.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default, Debug)]
+struct SearchingState {
+    // The board does not include filled rows.
+    board: Board64,
+
+    height: u32,
+
+    first: Option<Shape>,
+}
+
+/// The leftovers found while searching a sequence.
+#[derive(Default)]
+struct Collector {
+    visited_states: FxHashSet<SearchingState>,
+    leftovers: FxHashSet<ShapeOrder>,
+}
+
+/// The executor to find the leftovers reachable by PCs.
+///
+/// Unlike `PcPossibleBulkExecutor`, it does not stop at the first PC found, but searches all PCs of each sequence.
+/// The leftovers matter when the pattern has more shapes than the PC needs.
+#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub struct PcLeftoverBulkExecutor<'a, T: RotationSystem180> {
+    move_rules: &'a MoveRules<'a, T>,
+    clipped_board: ClippedBoard,
+    pattern: &'a Pattern,
+    allows_hold: bool,
+    spawn_rule: SpawnRule,
+}
+
+impl<'a, T: RotationSystem180> PcLeftoverBulkExecutor<'a, T> {
+    /// Make PcLeftoverBulkExecutor. The settings are the same as `PcPossibleBulkExecutor`.
+    ///
+    /// Returns `Err()` if the setting is incorrect or restricted.
+    /// See `PcPossibleExecutorBulkCreationError` for error patterns.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris::prelude::*;
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::pc_possible::PcLeftoverBulkExecutor;
+    /// use Shape::*;
+    ///
+    /// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    ///
+    /// let board = Board64::from_str("
+    ///     XXXXXX....
+    ///     XXXXXX....
+    /// ").expect("Failed to create a board");
+    /// let clipped_board = ClippedBoard::try_new(board, 2).expect("Failed to clip");
+    ///
+    /// // `[OOIT]p3`: Two O take the PC, and the one left is I or T.
+    /// let pattern = Pattern::try_from(vec![
+    ///     PatternElement::Permutation(ShapeCounter::from(vec![O, O, I, T]), 3),
+    /// ]).expect("Failed to create a pattern");
+    ///
+    /// let executor = PcLeftoverBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true)
+    ///     .expect("Failed to create an executor");
+    ///
+    /// let results = executor.execute();
+    /// assert_eq!(results.get(&ShapeSequence::new(vec![O, I, O])), Some([ShapeOrder::new(vec![I])].as_slice()));
+    /// assert_eq!(results.get(&ShapeSequence::new(vec![I, T, O])), Some([].as_slice()));
+    ///
+    /// // `OOI`, `OIO` and `IOO` leave I.
+    /// assert_eq!(results.count_by_leftover().get(&ShapeOrder::new(vec![I])), Some(&3));
+    /// ```
+    pub fn try_new(
+        move_rules: &'a MoveRules<T>,
+        clipped_board: ClippedBoard,
+        pattern: &'a Pattern,
+        allows_hold: bool,
+    ) -> Result<Self, PcPossibleExecutorBulkCreationError> {
+        // Validates the settings in the same way.
+        PcPossibleBulkExecutor::try_new(move_rules, clipped_board, pattern, allows_hold)?;
+
+        Ok(Self { move_rules, clipped_board, pattern, allows_hold, spawn_rule: SpawnRule::default() })
+    }
+
+    /// Sets the placements where pieces spawn. See `PcPossibleBulkExecutor::try_with_spawn_rule()` for more details.
+    pub fn try_with_spawn_rule(mut self, spawn_rule: SpawnRule) -> Result<Self, PcPossibleExecutorBulkCreationError> {
+        let height = self.clipped_board.height();
+        if Shape::all_iter().any(|shape| spawn_rule.is_blocked(shape, self.clipped_board.board_ref(), height)) {
+            return Err(PcPossibleExecutorBulkCreationError::SpawnIsBlocked);
+        }
+        self.spawn_rule = spawn_rule;
+        Ok(self)
+    }
+
+    /// Start the search for the leftovers of each sequence.
+    pub fn execute(&self) -> PcLeftoverResults {
+        let sequences = self.pattern.to_sequences();
+        let mut results = PcLeftoverResults::new(&sequences);
+
+        for sequence in &sequences {
+            if results.get(sequence).is_some() {
+                continue;
+            }
+
+            let order = sequence.to_shape_order();
+            let mut collector = Collector::default();
+            let parity = VerticalParity::new(self.clipped_board);
+            self.pop_shape(order.new_cursor(), self.clipped_board, &parity, &mut collector);
+
+            results.accept_if_present(sequence, collector.leftovers.into_iter().collect());
+        }
+
+        results
+    }
+
+    fn pop_shape(
+        &self,
+        cursor: OrderCursor,
+        clipped_board: ClippedBoard,
+        parity: &VerticalParity,
+        collector: &mut Collector,
+    ) {
+        let (popped, next_cursor) = cursor.pop(PopOp::First);
+        let Some(shape) = popped else {
+            return;
+        };
+        self.increment(shape, clipped_board, next_cursor, parity, collector);
+
+        if self.allows_hold {
+            let (popped, next_cursor) = cursor.pop(PopOp::Second);
+            // If the second is the same as the first, the result is the same as popping the first.
+            if let Some(shape) = popped.filter(|&shape| Some(shape) != cursor.first()) {
+                self.increment(shape, clipped_board, next_cursor, parity, collector);
+            }
+        }
+    }
+
+    fn increment(
+        &self,
+        shape: Shape,
+        clipped_board: ClippedBoard,
+        next_cursor: OrderCursor,
+        parity: &VerticalParity,
+        collector: &mut Collector,
+    ) {
+        let spawn = self.spawn_rule.placement(shape, self.clipped_board.height());
+        let moves = if !spawn.is_in_free_space(clipped_board.board_ref()) {
+            Vec::new()
+        } else {
+            generate_moves(self.move_rules, None, &self.spawn_rule, clipped_board.board(), spawn)
+        };
+
+        for placement in moves {
+            if clipped_board.height() as i32 <= placement.to_tr_placement().position.ty {
+                continue;
+            }
+
+            let mut board = clipped_board.board();
+            let lines_cleared = placement.place_on_and_clear_lines(&mut board).unwrap();
+            let rest_order = if next_cursor.has_next() { next_cursor.unused_shapes() } else { ShapeOrder::default() };
+
+            if board.is_empty() {
+                collector.leftovers.insert(rest_order);
+                continue;
+            }
+
+            let height = clipped_board.height() - lines_cleared.count();
+            if !collector.visited_states.insert(SearchingState {
+                board,
+                height,
+                first: next_cursor.first(),
+            }) {
+                continue;
+            }
+
+            let next_clipped_board = ClippedBoard::new_unsafe(board, height);
+            if !validate_board(&next_clipped_board) {
+                continue;
+            }
+
+            let rest_shapes = rest_order.shapes();
+            let has_extra_shapes = (next_clipped_board.spaces() / 4) < rest_shapes.len() as u32;
+            let next_parity = parity.place(placement);
+            if !next_parity.validates(rest_shapes, 0, self.allows_hold && has_extra_shapes) {
+                continue;
+            }
+
+            self.pop_shape(next_cursor, next_clipped_board, &next_parity, collector);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::pc_possible::{PcLeftoverBulkExecutor, PcPossibleBulkExecutor, PcPossibleExecutorBulkCreationError};
+    use crate::{ClippedBoard, Pattern, PatternElement, ShapeCounter, ShapeOrder, ShapeSequence, SpawnRow, SpawnRule};
+
+    #[test]
+    fn leftovers_with_hold() {
+        use Shape::*;
+
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let board = Board64::from_str("
+            ####....##
+            ####...###
+            ####..####
+            ####...###
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let pattern = Pattern::try_from(vec![
+            PatternElement::Permutation(ShapeCounter::one_of_each(), 4),
+        ]).unwrap();
+
+        let executor = PcLeftoverBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap();
+        let results = executor.execute();
+
+        // The succeed sequences are the same as PC possible.
+        let pc_results = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap().execute();
+        assert_eq!(results.count_succeed(), pc_results.count_succeed());
+        assert_eq!(results.count_failed(), pc_results.count_failed());
+        assert_eq!(results.count_pending(), 0);
+
+        // Each leftover is one shape of the sequence.
+        for (sequence, leftovers) in results.iter() {
+            for leftover in leftovers.unwrap() {
+                assert_eq!(leftover.shapes().len(), 1);
+                assert!(sequence.shapes().contains(&leftover.shapes()[0]));
+            }
+        }
+
+        // T and I take the PC with either O or L.
+        assert_eq!(
+            results.get(&ShapeSequence::new(vec![T, I, O, L])),
+            Some([ShapeOrder::new(vec![O]), ShapeOrder::new(vec![L])].as_slice()),
+        );
+
+        let counts = results.count_by_leftover();
+        assert!(counts.keys().all(|leftover| leftover.shapes().len() == 1));
+        assert!(results.count_succeed() <= counts.values().sum::<u64>());
+    }
+
+    #[test]
+    fn spawn_rule() {
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let board = Board64::from_str("
+            ####....##
+            ####...###
+            ####..####
+            ####...###
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let pattern = Pattern::try_from(vec![
+            PatternElement::Permutation(ShapeCounter::one_of_each(), 4),
+        ]).unwrap();
+
+        // Spawning in the top row, the succeed sequences are the same as PC possible with the same spawn.
+        let spawn_rule = SpawnRule::default().with_row(SpawnRow::Fixed(3)).with_columns(4);
+        let results = PcLeftoverBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap()
+            .try_with_spawn_rule(spawn_rule).unwrap()
+            .execute();
+        let pc_results = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap()
+            .try_with_spawn_rule(spawn_rule).unwrap()
+            .execute();
+        assert_eq!(results.count_succeed(), pc_results.count_succeed());
+        assert_eq!(results.count_failed(), pc_results.count_failed());
+
+        // Blocked by the initial board
+        let spawn_rule = SpawnRule::default().with_row(SpawnRow::Fixed(2));
+        let executor = PcLeftoverBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap();
+        assert_eq!(executor.try_with_spawn_rule(spawn_rule).unwrap_err(), PcPossibleExecutorBulkCreationError::SpawnIsBlocked);
+    }
+
+    #[test]
+    fn no_extra_shapes() {
+        use Shape::*;
+
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let board = Board64::from_str("
+            ######....
+            ######....
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 2).unwrap();
+        let pattern = Pattern::try_from(vec![
+            PatternElement::Permutation(ShapeCounter::from(vec![O, O, I]), 2),
+        ]).unwrap();
+
+        let executor = PcLeftoverBulkExecutor::try_new(&move_rules, clipped_board, &pattern, false).unwrap();
+        let results = executor.execute();
+        assert_eq!(results.get(&ShapeSequence::new(vec![O, O])), Some([ShapeOrder::default()].as_slice()));
+        assert_eq!(results.get(&ShapeSequence::new(vec![O, I])), Some([].as_slice()));
+        assert_eq!(results.count_keys(), 3);
+    }
+}
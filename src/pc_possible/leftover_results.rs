@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+
+use crate::internals::SequenceTable;
+use crate::{ShapeOrder, ShapeSequence};
+
+/// Holds the leftovers reachable by Perfect Clears for each sequence.
+///
+/// A leftover is the order of the shapes not used for the PC: the first shape is the one in the hold (or the next if nothing is held),
+/// followed by the unused queue. With the hold, it does not matter whether the first is held, since either of the first two can be placed next.
+///
+/// The result of a sequence is `None` while pending, and an empty slice if PC is impossible.
+/// The iteration is in the order of the sequences passed to `new()`.
+#[derive(Clone, Eq, PartialEq, Default, Debug)]
+pub struct PcLeftoverResults {
+    leftovers: SequenceTable<Option<Vec<ShapeOrder>>>,
+}
+
+impl PcLeftoverResults {
+    #[inline]
+    pub fn new(sequences: &Vec<ShapeSequence>) -> Self {
+        Self { leftovers: SequenceTable::new(sequences.iter().cloned(), || None) }
+    }
+
+    /// Records the leftovers of the sequence. They are sorted and deduplicated. Returns `false` if the sequence is not a key.
+    pub fn accept_if_present(&mut self, sequence: &ShapeSequence, mut leftovers: Vec<ShapeOrder>) -> bool {
+        if let Some(recorded) = self.leftovers.get_mut(sequence) {
+            leftovers.sort();
+            leftovers.dedup();
+            *recorded = Some(leftovers);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the leftovers of the sequence in ascending order. Returns `None` if it's pending or not a key.
+    #[inline]
+    pub fn get(&self, sequence: &ShapeSequence) -> Option<&[ShapeOrder]> {
+        self.leftovers.get(sequence).and_then(|leftovers| leftovers.as_deref())
+    }
+
+    /// Returns the pair of shape sequence and leftovers in the order of the sequences passed to `new()`.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item=(&ShapeSequence, Option<&[ShapeOrder]>)> + '_ {
+        self.leftovers.iter().map(|(sequence, leftovers)| (sequence, leftovers.as_deref()))
+    }
+
+    /// Returns the count of sequences that can reach each leftover.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::pc_possible::PcLeftoverResults;
+    /// use Shape::*;
+    ///
+    /// let mut results = PcLeftoverResults::new(&vec![
+    ///     ShapeSequence::new(vec![O, O, T]),
+    ///     ShapeSequence::new(vec![O, T, O]),
+    ///     ShapeSequence::new(vec![T, T, T]),
+    /// ]);
+    /// results.accept_if_present(&ShapeSequence::new(vec![O, O, T]), vec![ShapeOrder::new(vec![T])]);
+    /// results.accept_if_present(&ShapeSequence::new(vec![O, T, O]), vec![ShapeOrder::new(vec![T])]);
+    /// results.accept_if_present(&ShapeSequence::new(vec![T, T, T]), vec![]);
+    ///
+    /// assert_eq!(results.count_by_leftover().get(&ShapeOrder::new(vec![T])), Some(&2));
+    /// assert_eq!(results.count_succeed(), 2);
+    /// assert_eq!(results.count_failed(), 1);
+    /// ```
+    pub fn count_by_leftover(&self) -> BTreeMap<ShapeOrder, u64> {
+        let mut counts = BTreeMap::new();
+        for leftover in self.leftovers.values().iter().flatten().flatten() {
+            *counts.entry(leftover.clone()).or_default() += 1;
+        }
+        counts
+    }
+
+    /// Returns the count of shape sequences for which PC is possible.
+    #[inline]
+    pub fn count_succeed(&self) -> u64 {
+        self.leftovers.values().iter().flatten().filter(|leftovers| !leftovers.is_empty()).count() as u64
+    }
+
+    /// Returns the count of shape sequences for which PC is impossible.
+    #[inline]
+    pub fn count_failed(&self) -> u64 {
+        self.leftovers.values().iter().flatten().filter(|leftovers| leftovers.is_empty()).count() as u64
+    }
+
+    /// Returns the count of shape sequences for which results are not yet found.
+    #[inline]
+    pub fn count_pending(&self) -> u64 {
+        self.leftovers.values().iter().filter(|leftovers| leftovers.is_none()).count() as u64
+    }
+
+    /// Return the count of all shape sequences independent of the result.
+    #[inline]
+    pub fn count_keys(&self) -> usize {
+        self.leftovers.len()
+    }
+}
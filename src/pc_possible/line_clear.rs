@@ -0,0 +1,237 @@
+use bitris::prelude::*;
+
+use crate::ClippedBoard;
+
+/// Constraints on line clears while building a PC.
+///
+/// Rows are counted from the bottom of the clipped board, starting at 0.
+/// Rows already filled on the initial board are not counted as cleared lines.
+/// The lines cleared by the last piece are always allowed, but the rows to be cleared together are still checked.
+/// ```
+/// use bitris_commands::pc_possible::LineClearConstraints;
+///
+/// // Like `no line clear until last piece` in solution-finder.
+/// let constraints = LineClearConstraints::no_line_clear_until_last();
+/// assert_eq!(constraints.max_lines_before_last(), Some(0));
+///
+/// // Up to two lines before the last piece, and the bottom two rows must be cleared at once.
+/// let constraints = LineClearConstraints::default()
+///     .with_max_lines_before_last(2)
+///     .with_rows_cleared_together(&[0, 1]);
+/// assert!(!constraints.is_unconstrained());
+/// ```
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default, Debug)]
+pub struct LineClearConstraints {
+    max_lines_before_last: Option<u32>,
+
+    // Each group is a bitmask of the rows.
+    rows_cleared_together: Vec<u64>,
+}
+
+impl LineClearConstraints {
+    /// Makes the constraints that forbid clearing lines until the last piece.
+    #[inline]
+    pub fn no_line_clear_until_last() -> Self {
+        Self::default().with_max_lines_before_last(0)
+    }
+
+    /// Limits the count of lines cleared before the last piece.
+    #[inline]
+    pub fn with_max_lines_before_last(mut self, max_lines: u32) -> Self {
+        self.max_lines_before_last = Some(max_lines);
+        self
+    }
+
+    /// Requires the rows to be cleared by the same piece. It can be called multiple times to add groups.
+    /// Panics if a row is out of the supported height (56).
+    pub fn with_rows_cleared_together(mut self, rows: &[u32]) -> Self {
+        let group = rows.iter().fold(0u64, |group, &row| {
+            assert!(row < 56, "The row is out of the supported height: {row}");
+            group | (1u64 << row)
+        });
+        if group.count_ones() > 1 {
+            self.rows_cleared_together.push(group);
+        }
+        self
+    }
+
+    #[inline]
+    pub fn max_lines_before_last(&self) -> Option<u32> {
+        self.max_lines_before_last
+    }
+
+    /// Returns the groups of the rows to be cleared together, as bitmasks.
+    #[inline]
+    pub(crate) fn rows_cleared_together(&self) -> &[u64] {
+        self.rows_cleared_together.as_slice()
+    }
+
+    /// Returns `true` if there is no constraint.
+    #[inline]
+    pub fn is_unconstrained(&self) -> bool {
+        self.max_lines_before_last.is_none() && self.rows_cleared_together.is_empty()
+    }
+
+    pub(crate) fn to_checker(&self, clipped_board: ClippedBoard) -> LineClearChecker {
+        let initial_rows = (1u64 << clipped_board.height()) - 1;
+        let filled_rows = clipped_board.board_ref().cols.iter().fold(initial_rows, |rows, &col| rows & col);
+        LineClearChecker { constraints: self.clone(), initial_rows, filled_rows }
+    }
+}
+
+/// Checks the constraints during the search.
+///
+/// The state is the rows of the initial board not yet cleared, as a bitmask.
+/// The rows on the current board correspond to the set bits in order from the bottom.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub(crate) struct LineClearChecker {
+    constraints: LineClearConstraints,
+    initial_rows: u64,
+    filled_rows: u64,
+}
+
+impl LineClearChecker {
+    #[inline]
+    pub(crate) fn constraints(&self) -> &LineClearConstraints {
+        &self.constraints
+    }
+
+    #[inline]
+    pub(crate) fn initial_rows(&self) -> u64 {
+        self.initial_rows
+    }
+
+    /// Returns the rows after the lines are cleared, or `None` if the constraints are violated.
+    #[inline]
+    pub(crate) fn clear(&self, rows: u64, lines_cleared: Lines, is_last: bool) -> Option<u64> {
+        if self.constraints.is_unconstrained() {
+            return Some(rows);
+        }
+
+        let cleared_rows = deposit(lines_cleared.key, rows);
+        let next_rows = rows & !cleared_rows;
+
+        if !is_last {
+            if let Some(max_lines) = self.constraints.max_lines_before_last {
+                let lines = (self.initial_rows & !self.filled_rows & !next_rows).count_ones();
+                if max_lines < lines {
+                    return None;
+                }
+            }
+        }
+
+        for &group in &self.constraints.rows_cleared_together {
+            let hit = cleared_rows & group;
+            if hit != 0 && hit != group {
+                return None;
+            }
+        }
+
+        Some(next_rows)
+    }
+
+    /// Returns the part of the rows that distinguishes searching states.
+    /// The count of cleared lines is determined by the height, so the rows are needed only to check the groups.
+    #[inline]
+    pub(crate) fn state_key(&self, rows: u64) -> u64 {
+        if self.constraints.rows_cleared_together.is_empty() { 0 } else { rows }
+    }
+}
+
+/// Deposits the low bits of `bits` to the positions of the set bits of `mask`, from the lowest.
+#[inline]
+fn deposit(mut bits: u64, mut mask: u64) -> u64 {
+    let mut result = 0;
+    while bits != 0 && mask != 0 {
+        let lowest = mask & mask.wrapping_neg();
+        if bits & 1 == 1 {
+            result |= lowest;
+        }
+        bits >>= 1;
+        mask &= mask - 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::pc_possible::line_clear::deposit;
+    use crate::pc_possible::{LineClearConstraints, PcPossibleBulkExecutor};
+    use crate::{ClippedBoard, Pattern, PatternElement, ShapeCounter, ShapeSequence};
+
+    #[test]
+    fn deposit_bits() {
+        assert_eq!(deposit(0b0000, 0b1010), 0);
+        assert_eq!(deposit(0b0001, 0b1010), 0b0010);
+        assert_eq!(deposit(0b0011, 0b1010), 0b1010);
+        assert_eq!(deposit(0b0010, 0b1110), 0b0100);
+    }
+
+    #[test]
+    fn intermediate_clears() {
+        use Shape::*;
+
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let board = Board64::from_str("
+            #....###..
+            ########..
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 2).unwrap();
+        let pattern = Pattern::try_from(vec![
+            PatternElement::Permutation(ShapeCounter::from(vec![O, I]), 2),
+        ]).unwrap();
+
+        // `OI` clears the bottom row by O before I. `IO` clears both rows by O.
+        let execute = |constraints: LineClearConstraints| {
+            PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, false).unwrap()
+                .with_line_clear_constraints(constraints)
+                .execute()
+        };
+        let (oi, io) = (ShapeSequence::new(vec![O, I]), ShapeSequence::new(vec![I, O]));
+
+        let results = execute(LineClearConstraints::default());
+        assert_eq!((results.get(&oi), results.get(&io)), (Some(true), Some(true)));
+
+        let results = execute(LineClearConstraints::no_line_clear_until_last());
+        assert_eq!((results.get(&oi), results.get(&io)), (Some(false), Some(true)));
+
+        let results = execute(LineClearConstraints::default().with_max_lines_before_last(1));
+        assert_eq!((results.get(&oi), results.get(&io)), (Some(true), Some(true)));
+
+        let results = execute(LineClearConstraints::default().with_rows_cleared_together(&[0, 1]));
+        assert_eq!((results.get(&oi), results.get(&io)), (Some(false), Some(true)));
+    }
+
+    #[test]
+    fn subset_of_unconstrained() {
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let board = Board64::from_str("
+            ####....##
+            ####...###
+            ####..####
+            ####...###
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let pattern = Pattern::try_from(vec![
+            PatternElement::Permutation(ShapeCounter::one_of_each(), 4),
+        ]).unwrap();
+
+        let executor = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap();
+        let unconstrained = executor.clone().execute();
+        let constrained = executor
+            .with_line_clear_constraints(LineClearConstraints::no_line_clear_until_last())
+            .execute();
+
+        assert!(0 < constrained.count_succeed());
+        assert!(constrained.count_succeed() < unconstrained.count_succeed());
+        for (sequence, &succeed) in constrained.iter() {
+            if succeed == Some(true) {
+                assert_eq!(unconstrained.get(sequence), Some(true));
+            }
+        }
+    }
+}
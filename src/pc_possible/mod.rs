@@ -2,12 +2,45 @@ pub use binder::*;
 pub(crate) use buffer::*;
 pub use bulk_binder::*;
 pub use bulk_executor::*;
+pub use checkpoint::*;
+pub use compact_results::*;
+pub use failures::*;
+pub use garbage_executor::*;
+pub use garbage_results::*;
+pub use height_executor::*;
+pub use height_results::*;
+pub use leftover_executor::*;
+pub use leftover_results::*;
+pub use line_clear::*;
 pub use pc_results::*;
+pub use progress::*;
+pub use region_executor::*;
+pub use regions::*;
+pub use stats::*;
+pub use success_tree::*;
+pub(crate) use transposition_table::*;
 pub(crate) use vertical_parity::*;
 
 mod binder;
 mod buffer;
 mod bulk_binder;
 mod bulk_executor;
+mod checkpoint;
+mod compact_results;
+mod export;
+mod failures;
+mod garbage_executor;
+mod garbage_results;
+mod height_executor;
+mod height_results;
+mod leftover_executor;
+mod leftover_results;
+mod line_clear;
 mod pc_results;
+mod progress;
+mod region_executor;
+mod regions;
+mod stats;
+mod success_tree;
+mod transposition_table;
 mod vertical_parity;
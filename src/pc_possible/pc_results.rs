@@ -1,8 +1,47 @@
-use std::collections::hash_map::{IntoIter, Iter};
+use itertools::Itertools;
+use thiserror::Error;
 
-use fxhash::FxHashMap;
+use crate::internals::SequenceTable;
+use crate::{Mirror, ShapeSequence};
 
-use crate::ShapeSequence;
+/// A collection of errors that occur when merging the results.
+#[derive(Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum PcResultsMergeError {
+    /// Holds the sequences on which the results disagree, in lexicographic order.
+    #[error("The results disagree on {} sequences.", .0.len())]
+    Conflict(Vec<ShapeSequence>),
+
+    /// One was searched with the hold and the other without, so their results are not comparable.
+    #[error("The results disagree on whether the hold is allowed.")]
+    HoldMismatch,
+}
+
+/// The differences between two results. See `PcResults::compare()`.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct PcResultsComparison<'a> {
+    /// The sequences succeed in the left but failed in the right, in lexicographic order.
+    pub succeed_only_in_left: Vec<&'a ShapeSequence>,
+
+    /// The sequences succeed in the right but failed in the left, in lexicographic order.
+    pub succeed_only_in_right: Vec<&'a ShapeSequence>,
+
+    /// The count of sequences succeed in both.
+    pub count_both_succeed: u64,
+
+    /// The count of sequences failed in both.
+    pub count_both_failed: u64,
+
+    /// The count of sequences that cannot be compared because they are pending or missing in either.
+    pub count_incomparable: u64,
+}
+
+impl PcResultsComparison<'_> {
+    /// Returns `true` if no sequence has different results.
+    #[inline]
+    pub fn is_equivalent(&self) -> bool {
+        self.succeed_only_in_left.is_empty() && self.succeed_only_in_right.is_empty()
+    }
+}
 
 /// Holds the results of Perfect Clears.
 ///
@@ -12,26 +51,68 @@ use crate::ShapeSequence;
 /// * Pending: `None`
 ///
 /// Therefore, the shape sequences to be searched (key) are established at `new()`.
-#[derive(Clone, PartialEq, Default, Debug)]
+///
+/// The iteration is in the order of the sequences passed to `new()`, such as the enumeration order of the pattern,
+/// so it's deterministic. Use `iter_sorted()` for lexicographic order.
+/// The equality does not depend on the order.
+#[derive(Clone, Default, Debug)]
 pub struct PcResults {
-    succeed: FxHashMap<ShapeSequence, Option<bool>>,
+    table: SequenceTable<Option<bool>>,
+
+    // Whether the hold was allowed in the search. It does not take part in the equality, which compares the results only.
+    allows_hold: bool,
+
+    // The counts are kept up to date so that they can be polled cheaply during execution.
+    count_succeed: u64,
+    count_failed: u64,
 }
 
 impl PcResults {
     #[inline]
     pub fn new(sequences: &Vec<ShapeSequence>) -> Self {
-        let mut succeed = FxHashMap::<ShapeSequence, Option<bool>>::default();
-        succeed.reserve(sequences.len());
-        for order in sequences {
-            succeed.insert(order.clone(), None);
+        Self {
+            table: SequenceTable::new(sequences.iter().cloned(), || None),
+            allows_hold: false,
+            count_succeed: 0,
+            count_failed: 0,
+        }
+    }
+
+    /// Records whether the hold was allowed in the search. Set by the executors.
+    #[inline]
+    pub(crate) fn with_allows_hold(mut self, allows_hold: bool) -> Self {
+        self.allows_hold = allows_hold;
+        self
+    }
+
+    /// Returns `true` if the results were searched with the hold.
+    /// The results made by `new()` are regarded as searched without the hold.
+    #[inline]
+    pub fn allows_hold(&self) -> bool {
+        self.allows_hold
+    }
+
+    /// Adds the sequence as pending if it's not present.
+    #[inline]
+    fn insert_pending(&mut self, sequence: &ShapeSequence) {
+        if !self.table.contains_key(sequence) {
+            self.table.insert_if_absent(sequence.clone(), || None);
         }
-        Self { succeed }
     }
 
     #[inline]
     pub fn accept_if_present(&mut self, sequence: &ShapeSequence, succeed: bool) -> bool {
-        if let Some(_) = self.succeed.get(&sequence) {
-            self.succeed.insert(sequence.clone(), Some(succeed));
+        if let Some(result) = self.table.get_mut(sequence) {
+            match result.replace(succeed) {
+                Some(true) => self.count_succeed -= 1,
+                Some(false) => self.count_failed -= 1,
+                None => {}
+            }
+            if succeed {
+                self.count_succeed += 1;
+            } else {
+                self.count_failed += 1;
+            }
             true
         } else {
             false
@@ -41,7 +122,7 @@ impl PcResults {
     #[inline]
     #[allow(dead_code)]
     pub(crate) fn contains_key(&self, order: &ShapeSequence) -> bool {
-        self.succeed.contains_key(order)
+        self.table.contains_key(order)
     }
 
     /// Returns the result of a shape sequence.
@@ -65,10 +146,10 @@ impl PcResults {
     /// ```
     #[inline]
     pub fn get(&self, sequence: &ShapeSequence) -> Option<bool> {
-        self.succeed.get(sequence).map(|it| *it).unwrap_or(None)
+        self.table.get(sequence).copied().flatten()
     }
 
-    /// Returns accepted shape sequence. The order of the shape sequences is undefined.
+    /// Returns accepted shape sequence in the order of `iter()`.
     /// ```
     /// use itertools::Itertools;
     /// use bitris_commands::prelude::*;
@@ -85,8 +166,7 @@ impl PcResults {
     /// result.accept_if_present(&ShapeSequence::new(vec!(I, T, Z)), false);
     ///
     /// assert_eq!(
-    ///     // The order of the shape sequences is undefined.
-    ///     result.accepted_shape_sequences().into_iter().sorted().collect_vec(),
+    ///     result.accepted_shape_sequences(),
     ///     vec![
     ///         &ShapeSequence::new(vec!(I, T, S)),
     ///         &ShapeSequence::new(vec!(I, T, Z)),
@@ -95,13 +175,13 @@ impl PcResults {
     /// ```
     #[inline]
     pub fn accepted_shape_sequences(&self) -> Vec<&ShapeSequence> {
-        self.succeed.iter()
+        self.iter()
             .filter(|(_, value)| value.is_some())
             .map(|(key, _)| key)
             .collect()
     }
 
-    /// Returns the pair of shape sequence and result. The order of the shape sequences is undefined.
+    /// Returns the pair of shape sequence and result in the order of the sequences passed to `new()`.
     /// ```
     /// use itertools::Itertools;
     /// use bitris_commands::prelude::*;
@@ -118,21 +198,48 @@ impl PcResults {
     /// result.accept_if_present(&ShapeSequence::new(vec!(I, T, Z)), false);
     ///
     /// assert_eq!(
-    ///     // The order of the shape sequences is undefined.
-    ///     result.iter().sorted().collect_vec(),
+    ///     result.iter().collect_vec(),
     ///     vec![
-    ///         (&ShapeSequence::new(vec!(I, T, O)), &None),
     ///         (&ShapeSequence::new(vec!(I, T, S)), &Some(true)),
     ///         (&ShapeSequence::new(vec!(I, T, Z)), &Some(false)),
+    ///         (&ShapeSequence::new(vec!(I, T, O)), &None),
     ///     ],
     /// );
     /// ```
     #[inline]
-    pub fn iter(&self) -> Iter<'_, ShapeSequence, Option<bool>> {
-        self.succeed.iter()
+    pub fn iter(&self) -> impl Iterator<Item=(&ShapeSequence, &Option<bool>)> + '_ {
+        self.table.iter()
     }
 
-    /// Returns the pair of shape sequence and result. The order of the shape sequences is undefined.
+    /// Returns the pair of shape sequence and result in lexicographic order of the shapes (T, I, O, L, J, S, Z).
+    /// ```
+    /// use itertools::Itertools;
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::pc_possible::PcResults;
+    /// use Shape::*;
+    ///
+    /// let mut result = PcResults::new(&vec![
+    ///     ShapeSequence::new(vec!(O, T)),
+    ///     ShapeSequence::new(vec!(I, T)),
+    ///     ShapeSequence::new(vec!(T, O)),
+    /// ]);
+    ///
+    /// result.accept_if_present(&ShapeSequence::new(vec!(I, T)), true);
+    ///
+    /// assert_eq!(
+    ///     result.iter_sorted().collect_vec(),
+    ///     vec![
+    ///         (&ShapeSequence::new(vec!(T, O)), &None),
+    ///         (&ShapeSequence::new(vec!(I, T)), &Some(true)),
+    ///         (&ShapeSequence::new(vec!(O, T)), &None),
+    ///     ],
+    /// );
+    /// ```
+    pub fn iter_sorted(&self) -> impl Iterator<Item=(&ShapeSequence, &Option<bool>)> + '_ {
+        self.iter().sorted_by(|(left, _), (right, _)| left.cmp(right))
+    }
+
+    /// Returns the pair of shape sequence and result in the order of the sequences passed to `new()`.
     /// ```
     /// use itertools::Itertools;
     /// use bitris_commands::prelude::*;
@@ -149,18 +256,17 @@ impl PcResults {
     /// result.accept_if_present(&ShapeSequence::new(vec!(I, T, Z)), false);
     ///
     /// assert_eq!(
-    ///     // The order of the shape sequences is undefined.
-    ///     result.into_iter().sorted().collect_vec(),
+    ///     result.into_iter().collect_vec(),
     ///     vec![
-    ///         (ShapeSequence::new(vec!(I, T, O)), None),
     ///         (ShapeSequence::new(vec!(I, T, S)), Some(true)),
     ///         (ShapeSequence::new(vec!(I, T, Z)), Some(false)),
+    ///         (ShapeSequence::new(vec!(I, T, O)), None),
     ///     ],
     /// );
     /// ```
     #[inline]
-    pub fn into_iter(self) -> IntoIter<ShapeSequence, Option<bool>> {
-        self.succeed.into_iter()
+    pub fn into_iter(self) -> impl Iterator<Item=(ShapeSequence, Option<bool>)> {
+        self.table.into_iter()
     }
 
     /// Returns the count of shape sequences found to be succeed.
@@ -182,9 +288,7 @@ impl PcResults {
     /// ```
     #[inline]
     pub fn count_succeed(&self) -> u64 {
-        self.succeed.values()
-            .filter(|value| value.unwrap_or(false))
-            .count() as u64
+        self.count_succeed
     }
 
     /// Returns the count of shape sequences found to be failed.
@@ -206,9 +310,7 @@ impl PcResults {
     /// ```
     #[inline]
     pub fn count_failed(&self) -> u64 {
-        self.succeed.values()
-            .filter(|value| value.map(|flag| !flag).unwrap_or(false))
-            .count() as u64
+        self.count_failed
     }
 
     /// Returns the count of shape sequences for which results were found.
@@ -232,9 +334,7 @@ impl PcResults {
     /// ```
     #[inline]
     pub fn count_accepted(&self) -> u64 {
-        self.succeed.values()
-            .filter(|value| value.is_some())
-            .count() as u64
+        self.count_succeed + self.count_failed
     }
 
     /// Returns the count of shape sequences for which results are not yet found.
@@ -258,9 +358,120 @@ impl PcResults {
     /// ```
     #[inline]
     pub fn count_pending(&self) -> u64 {
-        self.succeed.values()
-            .filter(|value| value.is_none())
-            .count() as u64
+        self.table.len() as u64 - self.count_accepted()
+    }
+
+    /// Merges two results, such as shards searched on different machines.
+    ///
+    /// The keys are the union of both. A result found in either is adopted, so a sequence is pending only if it is pending in both.
+    /// Returns `Err(PcResultsMergeError::Conflict)` if one succeed and the other failed on the same sequence.
+    /// Returns `Err(PcResultsMergeError::HoldMismatch)` if one allows the hold and the other does not.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::pc_possible::{PcResults, PcResultsMergeError};
+    /// use Shape::*;
+    ///
+    /// let sequences = vec![
+    ///     ShapeSequence::new(vec!(I, T)),
+    ///     ShapeSequence::new(vec!(I, O)),
+    /// ];
+    ///
+    /// let mut left = PcResults::new(&sequences);
+    /// left.accept_if_present(&ShapeSequence::new(vec!(I, T)), true);
+    ///
+    /// let mut right = PcResults::new(&sequences);
+    /// right.accept_if_present(&ShapeSequence::new(vec!(I, O)), false);
+    ///
+    /// let merged = left.try_merge(&right).unwrap();
+    /// assert_eq!(merged.count_succeed(), 1);
+    /// assert_eq!(merged.count_failed(), 1);
+    /// assert_eq!(merged.count_pending(), 0);
+    ///
+    /// right.accept_if_present(&ShapeSequence::new(vec!(I, T)), false);
+    /// assert_eq!(
+    ///     left.try_merge(&right),
+    ///     Err(PcResultsMergeError::Conflict(vec![ShapeSequence::new(vec!(I, T))])),
+    /// );
+    /// ```
+    pub fn try_merge(&self, other: &PcResults) -> Result<PcResults, PcResultsMergeError> {
+        if self.allows_hold != other.allows_hold {
+            return Err(PcResultsMergeError::HoldMismatch);
+        }
+
+        let conflicts = self.iter()
+            .filter(|(sequence, result)| {
+                matches!((**result, other.get(sequence)), (Some(left), Some(right)) if left != right)
+            })
+            .map(|(sequence, _)| sequence.clone())
+            .sorted()
+            .collect_vec();
+        if !conflicts.is_empty() {
+            return Err(PcResultsMergeError::Conflict(conflicts));
+        }
+
+        let mut merged = self.clone();
+        for (sequence, &result) in other.iter() {
+            merged.insert_pending(sequence);
+            if let (None, Some(result)) = (merged.get(sequence), result) {
+                merged.accept_if_present(sequence, result);
+            }
+        }
+        Ok(merged)
+    }
+
+    /// Compares the results, such as those of two setups on the same pattern.
+    /// `self` is the left and `other` is the right.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::pc_possible::PcResults;
+    /// use Shape::*;
+    ///
+    /// let sequences = vec![
+    ///     ShapeSequence::new(vec!(I, T)),
+    ///     ShapeSequence::new(vec!(I, O)),
+    ///     ShapeSequence::new(vec!(I, S)),
+    /// ];
+    ///
+    /// let mut left = PcResults::new(&sequences);
+    /// left.accept_if_present(&ShapeSequence::new(vec!(I, T)), true);
+    /// left.accept_if_present(&ShapeSequence::new(vec!(I, O)), true);
+    /// left.accept_if_present(&ShapeSequence::new(vec!(I, S)), true);
+    ///
+    /// let mut right = PcResults::new(&sequences);
+    /// right.accept_if_present(&ShapeSequence::new(vec!(I, T)), true);
+    /// right.accept_if_present(&ShapeSequence::new(vec!(I, O)), false);
+    ///
+    /// let comparison = left.compare(&right);
+    /// assert_eq!(comparison.succeed_only_in_left, vec![&ShapeSequence::new(vec!(I, O))]);
+    /// assert!(comparison.succeed_only_in_right.is_empty());
+    /// assert_eq!(comparison.count_both_succeed, 1);
+    /// assert_eq!(comparison.count_incomparable, 1);
+    /// ```
+    pub fn compare<'a>(&'a self, other: &'a PcResults) -> PcResultsComparison<'a> {
+        let mut comparison = PcResultsComparison {
+            succeed_only_in_left: Vec::new(),
+            succeed_only_in_right: Vec::new(),
+            count_both_succeed: 0,
+            count_both_failed: 0,
+            count_incomparable: 0,
+        };
+
+        for (sequence, &result) in self.iter() {
+            match (result, other.get(sequence)) {
+                (Some(true), Some(true)) => comparison.count_both_succeed += 1,
+                (Some(false), Some(false)) => comparison.count_both_failed += 1,
+                (Some(true), Some(false)) => comparison.succeed_only_in_left.push(sequence),
+                (Some(false), Some(true)) => comparison.succeed_only_in_right.push(sequence),
+                _ => comparison.count_incomparable += 1,
+            }
+        }
+        comparison.count_incomparable += other.table.sequences().iter()
+            .filter(|sequence| !self.table.contains_key(sequence))
+            .count() as u64;
+
+        comparison.succeed_only_in_left.sort();
+        comparison.succeed_only_in_right.sort();
+        comparison
     }
 
     /// Return the count of all shape sequences independent of the result.
@@ -280,7 +491,29 @@ impl PcResults {
     /// ```
     #[inline]
     pub fn count_keys(&self) -> usize {
-        self.succeed.len()
+        self.table.len()
+    }
+}
+
+impl Mirror for PcResults {
+    /// Returns the results of the mirrored sequences, which are the results on the mirrored board.
+    /// The order of `iter()` is kept.
+    fn mirror(&self) -> Self {
+        let sequences: Vec<ShapeSequence> = self.table.sequences().iter().map(|sequence| sequence.mirror()).collect();
+        let mut mirrored = PcResults::new(&sequences).with_allows_hold(self.allows_hold);
+        for (sequence, &result) in sequences.iter().zip(self.table.values().iter()) {
+            if let Some(succeed) = result {
+                mirrored.accept_if_present(sequence, succeed);
+            }
+        }
+        mirrored
+    }
+}
+
+impl PartialEq for PcResults {
+    fn eq(&self, other: &Self) -> bool {
+        // The counts follow from the table.
+        self.table == other.table
     }
 }
 
@@ -288,9 +521,11 @@ impl PcResults {
 #[cfg(test)]
 mod tests {
     use bitris::prelude::*;
+    use itertools::Itertools;
 
-    use crate::pc_possible::PcResults;
-    use crate::ShapeSequence;
+    use crate::pc_possible::{PcResults, PcResultsMergeError};
+    use crate::internals::SequenceTable;
+    use crate::{Mirror, ShapeSequence};
 
     #[test]
     fn pc_rate_result() {
@@ -335,6 +570,16 @@ mod tests {
         assert_eq!(result.count_pending(), 1);
         assert_eq!(result.count_keys(), 3);
 
+        // Overwrites the result.
+        assert!(result.accept_if_present(&ShapeSequence::new(vec!(I, T, Z)), true));
+
+        assert_eq!(result.count_succeed(), 2);
+        assert_eq!(result.count_failed(), 0);
+        assert_eq!(result.count_accepted(), 2);
+        assert_eq!(result.count_pending(), 1);
+
+        assert!(result.accept_if_present(&ShapeSequence::new(vec!(I, T, Z)), false));
+
         {
             let sequence = ShapeSequence::new(vec!(I, T, S));
             assert!(result.contains_key(&sequence));
@@ -359,4 +604,149 @@ mod tests {
             assert_eq!(result.get(&sequence), None);
         }
     }
+
+    #[test]
+    fn deterministic_order() {
+        use Shape::*;
+        let sequences = [T, I, O, S].into_iter()
+            .permutations(3)
+            .map(ShapeSequence::new)
+            .collect_vec();
+        let reversed = sequences.iter().rev().cloned().collect_vec();
+
+        let mut results = PcResults::new(&sequences);
+        let mut reversed_results = PcResults::new(&reversed);
+        for sequence in &sequences {
+            results.accept_if_present(sequence, sequence.shapes()[0] == T);
+            reversed_results.accept_if_present(sequence, sequence.shapes()[0] == T);
+        }
+
+        assert_eq!(results.iter().map(|(sequence, _)| sequence.clone()).collect_vec(), sequences);
+        assert_eq!(reversed_results.iter().map(|(sequence, _)| sequence.clone()).collect_vec(), reversed);
+        assert_eq!(results, reversed_results);
+        assert_eq!(results.iter_sorted().collect_vec(), reversed_results.iter_sorted().collect_vec());
+        assert_eq!(results.clone().into_iter().map(|(sequence, _)| sequence).collect_vec(), sequences);
+
+        // Duplicates are ignored.
+        let duplicated = PcResults::new(&[sequences.clone(), sequences.clone()].concat());
+        assert_eq!(duplicated.count_keys(), sequences.len());
+        assert_eq!(duplicated, PcResults::new(&sequences));
+    }
+
+    #[test]
+    fn merge_shards_with_different_keys() {
+        use Shape::*;
+        let mut left = PcResults::new(&vec![
+            ShapeSequence::new(vec!(T, I)),
+            ShapeSequence::new(vec!(T, O)),
+        ]);
+        left.accept_if_present(&ShapeSequence::new(vec!(T, I)), true);
+
+        let mut right = PcResults::new(&vec![
+            ShapeSequence::new(vec!(T, O)),
+            ShapeSequence::new(vec!(I, T)),
+            ShapeSequence::new(vec!(I, O)),
+        ]);
+        right.accept_if_present(&ShapeSequence::new(vec!(T, O)), false);
+        right.accept_if_present(&ShapeSequence::new(vec!(I, T)), true);
+
+        let merged = left.try_merge(&right).unwrap();
+        assert_eq!(merged, right.try_merge(&left).unwrap());
+        assert_eq!(merged.count_keys(), 4);
+        assert_eq!(merged.count_succeed(), 2);
+        assert_eq!(merged.count_failed(), 1);
+        assert_eq!(merged.count_pending(), 1);
+        assert_eq!(merged.get(&ShapeSequence::new(vec!(T, O))), Some(false));
+
+        // Merging the same results changes nothing.
+        assert_eq!(merged.try_merge(&merged).unwrap(), merged);
+
+        let mut conflicting = right.clone();
+        conflicting.accept_if_present(&ShapeSequence::new(vec!(T, O)), true);
+        conflicting.accept_if_present(&ShapeSequence::new(vec!(I, O)), false);
+        assert_eq!(
+            merged.try_merge(&conflicting),
+            Err(PcResultsMergeError::Conflict(vec![ShapeSequence::new(vec!(T, O))])),
+        );
+    }
+
+    #[test]
+    fn merge_with_different_hold() {
+        use Shape::*;
+        let sequences = vec![
+            ShapeSequence::new(vec!(T, I)),
+            ShapeSequence::new(vec!(T, O)),
+        ];
+        let mut with_hold = PcResults::new(&sequences).with_allows_hold(true);
+        with_hold.accept_if_present(&ShapeSequence::new(vec!(T, I)), true);
+        let mut without_hold = PcResults::new(&sequences);
+        without_hold.accept_if_present(&ShapeSequence::new(vec!(T, O)), false);
+
+        assert_eq!(with_hold.try_merge(&without_hold), Err(PcResultsMergeError::HoldMismatch));
+        assert_eq!(without_hold.try_merge(&with_hold), Err(PcResultsMergeError::HoldMismatch));
+
+        // The merged results keep the hold.
+        let merged = with_hold.try_merge(&without_hold.clone().with_allows_hold(true)).unwrap();
+        assert!(merged.allows_hold());
+        assert_eq!(merged.count_accepted(), 2);
+    }
+
+    #[test]
+    fn compare_with_different_keys() {
+        use Shape::*;
+        let mut left = PcResults::new(&vec![
+            ShapeSequence::new(vec!(T, I)),
+            ShapeSequence::new(vec!(T, O)),
+            ShapeSequence::new(vec!(T, S)),
+        ]);
+        left.accept_if_present(&ShapeSequence::new(vec!(T, I)), false);
+        left.accept_if_present(&ShapeSequence::new(vec!(T, O)), false);
+        left.accept_if_present(&ShapeSequence::new(vec!(T, S)), true);
+
+        let mut right = PcResults::new(&vec![
+            ShapeSequence::new(vec!(T, I)),
+            ShapeSequence::new(vec!(T, O)),
+            ShapeSequence::new(vec!(I, T)),
+        ]);
+        right.accept_if_present(&ShapeSequence::new(vec!(T, I)), true);
+        right.accept_if_present(&ShapeSequence::new(vec!(T, O)), false);
+        right.accept_if_present(&ShapeSequence::new(vec!(I, T)), true);
+
+        let comparison = left.compare(&right);
+        assert!(!comparison.is_equivalent());
+        assert!(comparison.succeed_only_in_left.is_empty());
+        assert_eq!(comparison.succeed_only_in_right, vec![&ShapeSequence::new(vec!(T, I))]);
+        assert_eq!(comparison.count_both_succeed, 0);
+        assert_eq!(comparison.count_both_failed, 1);
+        assert_eq!(comparison.count_incomparable, 2);
+
+        let reversed = right.compare(&left);
+        assert_eq!(reversed.succeed_only_in_left, comparison.succeed_only_in_right);
+        assert!(left.compare(&left).is_equivalent());
+    }
+
+    #[test]
+    fn mirror() {
+        use Shape::*;
+        let mut results = PcResults::new(&vec![
+            ShapeSequence::new(vec!(L, T, S)),
+            ShapeSequence::new(vec!(I, J, O)),
+            ShapeSequence::new(vec!(T, T, Z)),
+        ]);
+        results.accept_if_present(&ShapeSequence::new(vec!(L, T, S)), true);
+        results.accept_if_present(&ShapeSequence::new(vec!(I, J, O)), false);
+
+        let mirrored = results.mirror();
+        assert_eq!(
+            mirrored.iter().map(|(sequence, &result)| (sequence.clone(), result)).collect_vec(),
+            vec![
+                (ShapeSequence::new(vec!(J, T, Z)), Some(true)),
+                (ShapeSequence::new(vec!(I, L, O)), Some(false)),
+                (ShapeSequence::new(vec!(T, T, S)), None),
+            ],
+        );
+        assert_eq!(mirrored.count_succeed(), 1);
+        assert_eq!(mirrored.count_failed(), 1);
+        assert_eq!(mirrored.mirror(), results);
+    }
 }
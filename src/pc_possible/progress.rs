@@ -0,0 +1,184 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::pc_possible::PcResults;
+
+/// A snapshot of the progress of bulk execution.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct ExecuteProgress {
+    /// The count of sequences for which results were found, including those inferred by the hold.
+    pub count_accepted: u64,
+
+    /// The count of sequences found to be succeed.
+    pub count_succeed: u64,
+
+    /// The count of sequences for which results are not yet found.
+    pub count_pending: u64,
+
+    /// The time elapsed since the execution started.
+    pub elapsed: Duration,
+}
+
+impl ExecuteProgress {
+    #[inline]
+    pub(crate) fn new(results: &PcResults, elapsed: Duration) -> Self {
+        Self {
+            count_accepted: results.count_accepted(),
+            count_succeed: results.count_succeed(),
+            count_pending: results.count_pending(),
+            elapsed,
+        }
+    }
+
+    /// Returns the count of sequences accepted per second.
+    /// ```
+    /// use std::time::Duration;
+    /// use bitris_commands::pc_possible::ExecuteProgress;
+    ///
+    /// let progress = ExecuteProgress {
+    ///     count_accepted: 300,
+    ///     count_succeed: 200,
+    ///     count_pending: 600,
+    ///     elapsed: Duration::from_secs(2),
+    /// };
+    /// assert_eq!(progress.sequences_per_second(), 150.0);
+    /// assert_eq!(progress.estimated_remaining(), Some(Duration::from_secs(4)));
+    /// ```
+    #[inline]
+    pub fn sequences_per_second(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds <= 0.0 {
+            return 0.0;
+        }
+        self.count_accepted as f64 / seconds
+    }
+
+    /// Returns the estimated time until all sequences are accepted, assuming the current pace continues.
+    /// Returns `None` if nothing has been accepted yet.
+    #[inline]
+    pub fn estimated_remaining(&self) -> Option<Duration> {
+        if self.count_pending == 0 {
+            return Some(Duration::ZERO);
+        }
+        let sequences_per_second = self.sequences_per_second();
+        if sequences_per_second <= 0.0 {
+            return None;
+        }
+        Some(Duration::from_secs_f64(self.count_pending as f64 / sequences_per_second))
+    }
+}
+
+/// A token to cancel the execution from another thread.
+/// Clones share the same state, so cancelling one cancels all.
+///
+/// The cancellation is checked each time a sequence is accepted.
+#[derive(Clone, Default, Debug)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// The built-in conditions to stop the execution.
+/// The execution stops when any one of them is satisfied. The sequences not yet searched remain pending.
+#[derive(Clone, Default, Debug)]
+pub struct StopConditions {
+    /// Stops when the wall-clock time reaches the deadline.
+    pub deadline: Option<Instant>,
+
+    /// Stops when the token is cancelled.
+    pub cancellation: Option<CancellationToken>,
+}
+
+impl StopConditions {
+    /// Makes the conditions to stop after the time budget has elapsed from now.
+    #[inline]
+    pub fn with_time_budget(budget: Duration) -> Self {
+        Self {
+            deadline: Some(Instant::now() + budget),
+            cancellation: None,
+        }
+    }
+
+    /// Makes the conditions to stop when the token is cancelled.
+    #[inline]
+    pub fn with_cancellation(token: CancellationToken) -> Self {
+        Self {
+            deadline: None,
+            cancellation: Some(token),
+        }
+    }
+
+    #[inline]
+    pub fn is_satisfied(&self) -> bool {
+        if let Some(deadline) = self.deadline {
+            if deadline <= Instant::now() {
+                return true;
+            }
+        }
+        if let Some(token) = &self.cancellation {
+            if token.is_cancelled() {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::pc_possible::{CancellationToken, ExecuteProgress, StopConditions};
+
+    #[test]
+    fn stop_conditions() {
+        assert!(!StopConditions::default().is_satisfied());
+
+        assert!(StopConditions::with_time_budget(Duration::ZERO).is_satisfied());
+        assert!(!StopConditions::with_time_budget(Duration::from_secs(3600)).is_satisfied());
+
+        let token = CancellationToken::new();
+        let conditions = StopConditions::with_cancellation(token.clone());
+        assert!(!conditions.is_satisfied());
+        token.clone().cancel();
+        assert!(conditions.is_satisfied());
+
+        let conditions = StopConditions {
+            deadline: Some(Instant::now() + Duration::from_secs(3600)),
+            cancellation: Some(CancellationToken::new()),
+        };
+        assert!(!conditions.is_satisfied());
+    }
+
+    #[test]
+    fn progress_before_accepting() {
+        let progress = ExecuteProgress {
+            count_accepted: 0,
+            count_succeed: 0,
+            count_pending: 10,
+            elapsed: Duration::ZERO,
+        };
+        assert_eq!(progress.sequences_per_second(), 0.0);
+        assert_eq!(progress.estimated_remaining(), None);
+
+        let progress = ExecuteProgress { count_pending: 0, ..progress };
+        assert_eq!(progress.estimated_remaining(), Some(Duration::ZERO));
+    }
+}
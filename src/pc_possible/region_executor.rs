@@ -0,0 +1,363 @@
+use bitris::prelude::*;
+use thiserror::Error;
+
+use crate::pc_possible::{LineClearConstraints, PcPossibleBulkExecutor, PcPossibleExecutorBulkCreationError, PcRegion, PcResults, RegionPruner};
+use crate::{ClippedBoard, Pattern, PatternElement, RotationSystem180, SpawnRule, TSpinKind};
+
+/// A collection of errors that occur when making the executor.
+#[derive(Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum PcRegionExecutorCreationError {
+    #[error("A row is already filled within a region, so the regions are not independent.")]
+    RowIsFilledInRegion,
+    #[error(transparent)]
+    ExecutorCreation(#[from] PcPossibleExecutorBulkCreationError),
+}
+
+/// The executor to find PC possibles on boards divided by walls, pruning the boards on which a region is never filled.
+///
+/// Each region is searched once for the states made by placing the shapes of the pattern in it, to find the states that cannot fill it.
+/// The search itself is `PcPossibleBulkExecutor` on the whole board, so the moves, including the kicks near the walls,
+/// and the settings are the same as it. The boards are only pruned when a region is never filled.
+/// So the results are the same as `PcPossibleBulkExecutor` with the same settings.
+///
+/// If the board is not divided, it searches in the same way as `PcPossibleBulkExecutor`.
+#[derive(Clone, PartialEq, Hash, Debug)]
+pub struct PcRegionBulkExecutor<'a, T: RotationSystem180> {
+    regions: Vec<PcRegion>,
+    executor: PcPossibleBulkExecutor<'a, T>,
+}
+
+impl<'a, T: RotationSystem180> PcRegionBulkExecutor<'a, T> {
+    /// Make PcRegionBulkExecutor. The arguments are the same as `PcPossibleBulkExecutor::try_new()`.
+    ///
+    /// Returns `Err()` if the setting is incorrect or restricted, or the regions are not independent.
+    /// See `PcRegionExecutorCreationError` for error patterns.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris::prelude::*;
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::pc_possible::{PcPossibleBulkExecutor, PcRegionBulkExecutor};
+    ///
+    /// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    ///
+    /// // Two 4x2 regions next to the filled block
+    /// let board = Board64::from_str("
+    ///     ....XX....
+    ///     ....XX....
+    /// ").expect("Failed to create a board");
+    /// let clipped_board = ClippedBoard::try_new(board, 2).expect("Failed to clip");
+    ///
+    /// let pattern = Pattern::try_from(vec![
+    ///     PatternElement::Wildcard,
+    ///     PatternElement::Wildcard,
+    ///     PatternElement::Wildcard,
+    ///     PatternElement::Wildcard,
+    /// ]).expect("Failed to create a pattern");
+    ///
+    /// let executor = PcRegionBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true)
+    ///     .expect("Failed to create an executor");
+    /// assert_eq!(executor.regions().len(), 2);
+    ///
+    /// // The bottom row is cleared by the second I, before the last piece.
+    /// let results = executor.execute();
+    /// assert_eq!(results.get(&ShapeSequence::new(vec![Shape::I, Shape::I, Shape::I, Shape::I])), Some(true));
+    ///
+    /// let expected = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true)
+    ///     .expect("Failed to create an executor")
+    ///     .execute();
+    /// assert_eq!(results, expected);
+    /// ```
+    pub fn try_new(
+        move_rules: &'a MoveRules<T>,
+        clipped_board: ClippedBoard,
+        pattern: &'a Pattern,
+        allows_hold: bool,
+    ) -> Result<Self, PcRegionExecutorCreationError> {
+        let mut executor = PcPossibleBulkExecutor::try_new(move_rules, clipped_board, pattern, allows_hold)?;
+
+        let regions = PcRegion::decompose(clipped_board);
+        if regions.iter().any(|region| region.has_filled_row()) {
+            return Err(PcRegionExecutorCreationError::RowIsFilledInRegion);
+        }
+
+        // A single region is the whole board, so there is nothing to prune.
+        if 1 < regions.len() {
+            let pruner = RegionPruner::new(regions.clone(), shapes_in(pattern), clipped_board.height());
+            executor = executor.with_region_pruner(pruner);
+        }
+
+        Ok(Self { regions, executor })
+    }
+
+    /// Sets the maximum count of states memorized across sequences.
+    /// See `PcPossibleBulkExecutor::with_transposition_capacity()` for more details.
+    #[inline]
+    pub fn with_transposition_capacity(mut self, capacity: usize) -> Self {
+        self.executor = self.executor.with_transposition_capacity(capacity);
+        self
+    }
+
+    /// Sets the constraints on line clears. See `PcPossibleBulkExecutor::with_line_clear_constraints()` for more details.
+    #[inline]
+    pub fn with_line_clear_constraints(mut self, constraints: LineClearConstraints) -> Self {
+        self.executor = self.executor.with_line_clear_constraints(constraints);
+        self
+    }
+
+    /// Requires one of the T-spins in the PC. See `PcPossibleBulkExecutor::with_required_t_spins()` for more details.
+    #[inline]
+    pub fn with_required_t_spins(mut self, kinds: &[TSpinKind]) -> Self {
+        self.executor = self.executor.with_required_t_spins(kinds);
+        self
+    }
+
+    /// Sets the placements where pieces spawn. See `PcPossibleBulkExecutor::try_with_spawn_rule()` for more details.
+    pub fn try_with_spawn_rule(mut self, spawn_rule: SpawnRule) -> Result<Self, PcRegionExecutorCreationError> {
+        self.executor = self.executor.try_with_spawn_rule(spawn_rule)?;
+        Ok(self)
+    }
+
+    /// Limits each placement to the count of inputs. See `PcPossibleBulkExecutor::with_max_inputs()` for more details.
+    #[inline]
+    pub fn with_max_inputs(mut self, max_inputs: Option<u32>) -> Self {
+        self.executor = self.executor.with_max_inputs(max_inputs);
+        self
+    }
+
+    /// Enables the mirror symmetry reduction. See `PcPossibleBulkExecutor::with_mirror_reduction()` for more details.
+    #[inline]
+    pub fn with_mirror_reduction(mut self, enabled: bool) -> Self {
+        self.executor = self.executor.with_mirror_reduction(enabled);
+        self
+    }
+
+    /// Returns the regions, from the left.
+    #[inline]
+    pub fn regions(&self) -> &[PcRegion] {
+        self.regions.as_slice()
+    }
+
+    /// Returns the executor that searches with the regions.
+    /// Use it for the other ways of execution, such as `execute_in_parallel()` and the checkpoints.
+    #[inline]
+    pub fn executor(&self) -> &PcPossibleBulkExecutor<'a, T> {
+        &self.executor
+    }
+
+    /// Start the search for PC possible in bulk. See `PcPossibleBulkExecutor::execute()` for more details.
+    #[inline]
+    pub fn execute(&self) -> PcResults {
+        self.executor.execute()
+    }
+}
+
+/// Returns the shapes that appear in the pattern.
+fn shapes_in(pattern: &Pattern) -> Vec<Shape> {
+    let mut shapes: Vec<Shape> = pattern.elements().iter()
+        .flat_map(|element| match *element {
+            PatternElement::One(shape) => vec![shape],
+            PatternElement::Fixed(shapes) => shapes.to_vec(),
+            PatternElement::Wildcard => Shape::all_iter().collect(),
+            PatternElement::Permutation(counter, _) | PatternElement::Factorial(counter) => {
+                counter.to_pairs().into_iter().map(|(shape, _)| shape).collect()
+            }
+        })
+        .collect();
+    shapes.sort();
+    shapes.dedup();
+    shapes
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::pc_possible::{LineClearConstraints, PcPossibleBulkExecutor, PcPossibleExecutorBulkCreationError, PcRegionBulkExecutor, PcRegionExecutorCreationError};
+    use crate::{ClippedBoard, Pattern, PatternElement, ShapeCounter, ShapeSequence, SpawnRow, SpawnRule, TSpinKind};
+
+    #[test]
+    fn same_as_whole_search() {
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let board = Board64::from_str("
+            ...XXX....
+            ...XX.....
+            ...XXX....
+            ...XXXX...
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+
+        for allows_hold in [true, false] {
+            let pattern = Pattern::try_from(vec![
+                PatternElement::Factorial(ShapeCounter::one_of_each()),
+            ]).unwrap();
+
+            let results = PcRegionBulkExecutor::try_new(&move_rules, clipped_board, &pattern, allows_hold).unwrap().execute();
+            let expected = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, allows_hold).unwrap().execute();
+
+            assert_eq!(results, expected);
+            assert!(0 < results.count_succeed());
+        }
+    }
+
+    #[test]
+    fn kicks_near_wall() {
+        use Shape::*;
+
+        // On the left, the T and then the Z are kicked into the slots under the overhangs next to the wall.
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let board = Board64::from_str("
+            ..XXXX....
+            X..XXX....
+            ...XXX....
+            X.XXXX....
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let pattern = Pattern::try_from(vec![
+            PatternElement::Fixed(vec![T, Z].try_into().unwrap()),
+            PatternElement::Permutation(ShapeCounter::one_of_each(), 4),
+        ]).unwrap();
+
+        for allows_hold in [true, false] {
+            let executor = PcRegionBulkExecutor::try_new(&move_rules, clipped_board, &pattern, allows_hold).unwrap();
+            assert_eq!(executor.regions().len(), 2);
+
+            let expected = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, allows_hold).unwrap().execute();
+            assert_eq!(executor.execute(), expected);
+        }
+    }
+
+    #[test]
+    fn settings() {
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let board = Board64::from_str("
+            ...XXX....
+            ...XX.....
+            ...XXX....
+            ...XXXX...
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let pattern = Pattern::try_from(vec![
+            PatternElement::Permutation(ShapeCounter::one_of_each(), 5),
+        ]).unwrap();
+
+        let results = PcRegionBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap()
+            .with_transposition_capacity(0)
+            .with_required_t_spins(&[TSpinKind::Mini, TSpinKind::Single, TSpinKind::Double])
+            .with_max_inputs(Some(4))
+            .execute();
+        let expected = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap()
+            .with_transposition_capacity(0)
+            .with_required_t_spins(&[TSpinKind::Mini, TSpinKind::Single, TSpinKind::Double])
+            .with_max_inputs(Some(4))
+            .execute();
+        assert_eq!(results, expected);
+
+        let results = PcRegionBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap()
+            .with_line_clear_constraints(LineClearConstraints::no_line_clear_until_last())
+            .execute();
+        let expected = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap()
+            .with_line_clear_constraints(LineClearConstraints::no_line_clear_until_last())
+            .execute();
+        assert_eq!(results, expected);
+    }
+
+    #[test]
+    fn single_region() {
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let clipped_board = ClippedBoard::try_new(Board64::blank(), 4).unwrap();
+        let pattern = Pattern::try_from(vec![
+            PatternElement::Factorial(ShapeCounter::one_of_each()),
+            PatternElement::Permutation(ShapeCounter::one_of_each(), 3),
+        ]).unwrap();
+
+        // Nothing to prune, so it's the same executor.
+        let executor = PcRegionBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap();
+        assert_eq!(executor.regions().len(), 1);
+        assert_eq!(executor.executor(), &PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap());
+    }
+
+    #[test]
+    fn line_clear_across_regions() {
+        use Shape::*;
+
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let board = Board64::from_str("
+            ....XX....
+            ....XX....
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 2).unwrap();
+        let pattern = Pattern::try_from(vec![PatternElement::Wildcard; 4]).unwrap();
+
+        for allows_hold in [true, false] {
+            let results = PcRegionBulkExecutor::try_new(&move_rules, clipped_board, &pattern, allows_hold).unwrap().execute();
+            let expected = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, allows_hold).unwrap().execute();
+            assert_eq!(results, expected);
+
+            // The bottom row is always filled in both regions before the last I, and cleared.
+            assert_eq!(results.get(&ShapeSequence::new(vec![I, I, I, I])), Some(true));
+
+            let without_line_clears = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, allows_hold).unwrap()
+                .with_line_clear_constraints(LineClearConstraints::no_line_clear_until_last())
+                .execute();
+            assert_eq!(without_line_clears.get(&ShapeSequence::new(vec![I, I, I, I])), Some(false));
+            assert!(without_line_clears.count_succeed() < results.count_succeed());
+        }
+    }
+
+    #[test]
+    fn spawn_rule() {
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let board = Board64::from_str("
+            ...XXX....
+            ...XX.....
+            ...XXX....
+            ...XXXX...
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let pattern = Pattern::try_from(vec![
+            PatternElement::Permutation(ShapeCounter::one_of_each(), 5),
+        ]).unwrap();
+
+        for spawn_rule in [
+            SpawnRule::default().with_columns(0).with_orientation(Shape::I, Orientation::East),
+            SpawnRule::default().with_row(SpawnRow::Fixed(4)),
+            SpawnRule::default().with_row(SpawnRow::Fixed(3)).with_columns(6),
+        ] {
+            let results = PcRegionBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap()
+                .try_with_spawn_rule(spawn_rule).unwrap()
+                .execute();
+            let expected = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap()
+                .try_with_spawn_rule(spawn_rule).unwrap()
+                .execute();
+            assert_eq!(results, expected);
+        }
+
+        // Blocked by the stack
+        let spawn_rule = SpawnRule::default().with_row(SpawnRow::Fixed(0)).with_columns(3);
+        assert_eq!(
+            PcRegionBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap()
+                .try_with_spawn_rule(spawn_rule).unwrap_err(),
+            PcRegionExecutorCreationError::ExecutorCreation(PcPossibleExecutorBulkCreationError::SpawnIsBlocked),
+        );
+    }
+
+    #[test]
+    fn filled_row_in_region() {
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let board = Board64::from_str("
+            ....XX....
+            XXXXXX....
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 2).unwrap();
+        let pattern = Pattern::try_from(vec![
+            PatternElement::Factorial(ShapeCounter::one_of_each()),
+        ]).unwrap();
+
+        assert_eq!(
+            PcRegionBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap_err(),
+            PcRegionExecutorCreationError::RowIsFilledInRegion,
+        );
+    }
+}
@@ -0,0 +1,314 @@
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+
+use bitris::prelude::*;
+use fxhash::FxHashMap;
+
+use crate::pc_possible::validate_board;
+use crate::ClippedBoard;
+
+/// An area of the board divided by walls, where pieces cannot cross.
+///
+/// A wall is a pair of adjacent columns without a row where both are free, as `validate_board` detects.
+/// No piece can be placed across a wall, so the pieces fill each region separately.
+/// ```
+/// use std::str::FromStr;
+/// use bitris::prelude::*;
+/// use bitris_commands::prelude::*;
+/// use bitris_commands::pc_possible::PcRegion;
+///
+/// let board = Board64::from_str("
+///     ...XXX....
+///     ...XX.....
+///     ...XXX....
+///     ...XXXX...
+/// ").unwrap();
+/// let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+///
+/// let regions = PcRegion::decompose(clipped_board);
+/// assert_eq!(regions.len(), 2);
+/// assert_eq!(regions[0].columns(), 0..3);
+/// assert_eq!(regions[1].columns(), 5..10);
+/// assert_eq!(regions[0].count_pieces(), 3);
+/// ```
+#[derive(Clone, PartialEq, Hash, Debug)]
+pub struct PcRegion {
+    columns: Range<usize>,
+
+    // The columns outside the region are filled.
+    clipped_board: ClippedBoard,
+}
+
+impl PcRegion {
+    /// Divides the board into the regions, from the left. Regions without spaces are excluded.
+    pub fn decompose(clipped_board: ClippedBoard) -> Vec<PcRegion> {
+        let wall = (1u64 << clipped_board.height()) - 1;
+        let cols = clipped_board.board_ref().cols;
+
+        let mut boundaries = vec![0];
+        boundaries.extend((1..10).filter(|&x| (cols[x - 1] | cols[x]) & wall == wall));
+        boundaries.push(10);
+
+        boundaries.windows(2)
+            .map(|pair| pair[0]..pair[1])
+            .filter(|columns| columns.clone().any(|x| cols[x] & wall != wall))
+            .map(|columns| {
+                let mut board = clipped_board.board();
+                for x in (0..10).filter(|x| !columns.contains(x)) {
+                    board.cols[x] |= wall;
+                }
+                PcRegion { columns, clipped_board: ClippedBoard::new_unsafe(board, clipped_board.height()) }
+            })
+            .collect()
+    }
+
+    /// Returns the columns of the region, from the left.
+    #[inline]
+    pub fn columns(&self) -> Range<usize> {
+        self.columns.clone()
+    }
+
+    /// Returns the board in which the columns outside the region are filled.
+    #[inline]
+    pub fn clipped_board(&self) -> ClippedBoard {
+        self.clipped_board
+    }
+
+    /// Returns the count of pieces to fill the region.
+    #[inline]
+    pub fn count_pieces(&self) -> usize {
+        self.clipped_board.spaces() as usize / 4
+    }
+
+    /// Returns `true` if a row is already filled within the region.
+    #[inline]
+    pub(crate) fn has_filled_row(&self) -> bool {
+        let wall = (1u64 << self.clipped_board.height()) - 1;
+        let filled_rows = self.clipped_board.board_ref().cols.iter().fold(wall, |rows, &col| rows & col);
+        filled_rows != 0
+    }
+
+    /// Searches all states of the region made by placing the shapes without line clears.
+    ///
+    /// A piece can be placed at any position where it's grounded, whether it's reachable or not.
+    /// So the states are a superset of the ones made by the actual moves, and a state that cannot fill the region here
+    /// is never filled by the actual moves either, unless a line is cleared across the regions.
+    pub(crate) fn search_graph(&self, shapes: &[Shape]) -> RegionGraph {
+        let height = self.clipped_board.height();
+        let all_rows = (1u64 << height) - 1;
+        let outside = (0..10).filter(|x| !self.columns.contains(x)).collect();
+        let mut graph = RegionGraph { outside, all_rows, nodes: FxHashMap::default() };
+
+        // The cells of the pieces from the bottom-left. Orientations with the same cells are merged.
+        let mut offsets: Vec<[(i32, i32); 4]> = shapes.iter()
+            .flat_map(|&shape| {
+                [Orientation::North, Orientation::East, Orientation::South, Orientation::West].map(|orientation| {
+                    let mut cells = shape.with(orientation).with(bl(0, 0)).locations().map(|location| (location.x, location.y));
+                    cells.sort();
+                    cells
+                })
+            })
+            .collect();
+        offsets.sort();
+        offsets.dedup();
+
+        self.search_nodes(self.clipped_board.board(), &offsets, &mut graph);
+        graph
+    }
+
+    /// Adds the node of the board and the states made from it, and returns the node.
+    fn search_nodes(&self, board: Board64, offsets: &[[(i32, i32); 4]], graph: &mut RegionGraph) -> RegionNode {
+        if let Some(&node) = graph.nodes.get(&board) {
+            return node;
+        }
+
+        let height = self.clipped_board.height();
+        let filled_rows = board.cols.iter().fold(graph.all_rows, |rows, &col| rows & col);
+        let mut node = RegionNode { reach_rows: filled_rows, can_fill: filled_rows == graph.all_rows };
+
+        if !node.can_fill {
+            let is_free = |x: i32, y: i32| board.cols[x as usize] & (1 << y) == 0;
+            for cells in offsets {
+                for x in self.columns.clone() {
+                    for y in 0..height as i32 {
+                        let locations = cells.map(|(dx, dy)| (x as i32 + dx, y + dy));
+                        let fits = locations.iter()
+                            .all(|&(lx, ly)| self.columns.contains(&(lx as usize)) && ly < height as i32 && is_free(lx, ly));
+                        if !fits || !locations.iter().any(|&(lx, ly)| ly == 0 || !is_free(lx, ly - 1)) {
+                            continue;
+                        }
+
+                        // Rows filled in the region are not cleared until the other regions also fill them.
+                        // A wall made in the region remains after line clears, so such a board is never filled.
+                        let mut next_board = board;
+                        for (x, y) in locations {
+                            next_board.cols[x as usize] |= 1 << y;
+                        }
+                        if !validate_board(&ClippedBoard::new_unsafe(next_board, height)) {
+                            continue;
+                        }
+
+                        let next = self.search_nodes(next_board, offsets, graph);
+                        node.reach_rows |= next.reach_rows;
+                        node.can_fill |= next.can_fill;
+                    }
+                }
+            }
+        }
+
+        graph.nodes.insert(board, node);
+        node
+    }
+}
+
+/// The states of a region made without line clears. See `PcRegion::search_graph()`.
+#[derive(Clone, Debug)]
+pub(crate) struct RegionGraph {
+    // The columns outside the region, which are filled in the boards of the states.
+    outside: Vec<usize>,
+    all_rows: u64,
+    nodes: FxHashMap<Board64, RegionNode>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct RegionNode {
+    // The rows that can be filled from this state, including the filled ones.
+    reach_rows: u64,
+    can_fill: bool,
+}
+
+impl RegionGraph {
+    /// Returns the state of the region on the whole board. Returns `None` if it's not a state of the graph.
+    #[inline]
+    fn node(&self, board: &Board64) -> Option<&RegionNode> {
+        let mut region_board = *board;
+        for &x in &self.outside {
+            region_board.cols[x] |= self.all_rows;
+        }
+        self.nodes.get(&region_board)
+    }
+}
+
+/// Prunes the boards on which a region is never filled, for the search on the whole board.
+///
+/// A region that cannot be filled by itself is still filled if a line is cleared across the regions,
+/// so it's pruned only if no row can be filled in all regions.
+/// Once a line is cleared, the regions are no longer independent, so nothing is pruned.
+#[derive(Clone, Debug)]
+pub(crate) struct RegionPruner {
+    regions: Vec<PcRegion>,
+    shapes: Vec<Shape>,
+    height: u32,
+    graphs: Vec<RegionGraph>,
+}
+
+impl RegionPruner {
+    /// Makes the pruner of the regions for the shapes to be placed.
+    pub(crate) fn new(regions: Vec<PcRegion>, shapes: Vec<Shape>, height: u32) -> Self {
+        let graphs = regions.iter().map(|region| region.search_graph(&shapes)).collect();
+        Self { regions, shapes, height, graphs }
+    }
+
+    /// Returns `true` if PC is impossible on the board because a region is never filled.
+    pub(crate) fn prunes(&self, clipped_board: &ClippedBoard) -> bool {
+        if clipped_board.height() != self.height {
+            return false;
+        }
+
+        let mut cannot_fill = false;
+        let mut common_reach_rows = u64::MAX;
+        for graph in &self.graphs {
+            let Some(node) = graph.node(clipped_board.board_ref()) else {
+                return false;
+            };
+            cannot_fill |= !node.can_fill;
+            common_reach_rows &= node.reach_rows;
+        }
+        cannot_fill && common_reach_rows == 0
+    }
+}
+
+// The graphs are made from the other fields, so they are compared by them.
+impl PartialEq for RegionPruner {
+    fn eq(&self, other: &Self) -> bool {
+        self.regions == other.regions && self.shapes == other.shapes && self.height == other.height
+    }
+}
+
+impl PartialOrd for RegionPruner {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (self == other).then_some(Ordering::Equal)
+    }
+}
+
+impl Hash for RegionPruner {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.regions.hash(state);
+        self.shapes.hash(state);
+        self.height.hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::pc_possible::PcRegion;
+    use crate::ClippedBoard;
+
+    #[test]
+    fn decompose() {
+        let board = Board64::from_str("
+            XXXX......
+            XXXX......
+            XXXX......
+            XXXX......
+        ").unwrap();
+        let regions = PcRegion::decompose(ClippedBoard::try_new(board, 4).unwrap());
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].columns(), 4..10);
+
+        // Without walls
+        let regions = PcRegion::decompose(ClippedBoard::try_new(Board64::blank(), 4).unwrap());
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].columns(), 0..10);
+        assert_eq!(regions[0].count_pieces(), 10);
+    }
+
+    #[test]
+    fn search_graph() {
+        let board = Board64::from_str("
+            XX....XXXX
+            XX....XXXX
+        ").unwrap();
+        let regions = PcRegion::decompose(ClippedBoard::try_new(board, 2).unwrap());
+        assert_eq!(regions.len(), 1);
+
+        let graph = regions[0].search_graph(&Shape::all_iter().collect::<Vec<_>>());
+        let node = |board: &str| graph.node(&Board64::from_str(board).unwrap()).copied();
+
+        let root = node("
+            XX....XXXX
+            XX....XXXX
+        ").unwrap();
+        assert!(root.can_fill);
+        assert_eq!(root.reach_rows, 0b11);
+
+        // An I on the bottom fills the row.
+        let i_on_bottom = node("
+            XX....XXXX
+            XXXXXXXXXX
+        ").unwrap();
+        assert!(i_on_bottom.can_fill);
+        assert_eq!(i_on_bottom.reach_rows, 0b11);
+
+        // A T on the bottom leaves an isolated cell, so it's not a state.
+        assert!(node("
+            XX.X..XXXX
+            XXXXX.XXXX
+        ").is_none());
+    }
+}
@@ -0,0 +1,41 @@
+use std::ops::AddAssign;
+
+/// `true` if the statistics are counted, that is, the `stats` feature is enabled.
+/// The counting is skipped at compile time otherwise.
+pub(crate) const COUNTS_STATS: bool = cfg!(feature = "stats");
+
+/// The counters of the PC possible search, to see where the time goes.
+///
+/// They are counted only if the `stats` feature is enabled. See `PcPossibleBulkExecutor::execute_with_stats()`.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Default, Debug)]
+pub struct SearchStats {
+    /// The count of states expanded, that is, searched for the placements of the next piece.
+    /// The states proven by the transposition table are not expanded.
+    pub nodes_expanded: u64,
+
+    /// The count of placements generated by the move rules.
+    pub placements_generated: u64,
+
+    /// The count of states skipped because they were already visited while searching the same sequence.
+    pub states_deduplicated: u64,
+
+    /// The count of boards pruned because an area divided by walls cannot be filled.
+    pub pruned_by_board: u64,
+
+    /// The count of boards pruned because the vertical parity cannot be resolved by the remaining pieces.
+    pub pruned_by_parity: u64,
+
+    /// The count of sequences found to be succeed without searching, by inferring from the solution of another with the hold.
+    pub reused_by_hold: u64,
+}
+
+impl AddAssign for SearchStats {
+    fn add_assign(&mut self, other: Self) {
+        self.nodes_expanded += other.nodes_expanded;
+        self.placements_generated += other.placements_generated;
+        self.states_deduplicated += other.states_deduplicated;
+        self.pruned_by_board += other.pruned_by_board;
+        self.pruned_by_parity += other.pruned_by_parity;
+        self.reused_by_hold += other.reused_by_hold;
+    }
+}
@@ -0,0 +1,286 @@
+use std::fmt;
+
+use bitris::prelude::*;
+use fxhash::FxHashMap;
+
+use crate::pc_possible::PcResults;
+
+/// A collection of orders to sort the children in `SuccessTree`.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default, Debug)]
+pub enum SuccessTreeOrder {
+    /// In the order of the shapes (T, I, O, L, J, S, Z).
+    #[default]
+    Shape,
+
+    /// The highest success rate first. Ties are in the order of the shapes.
+    RateDescending,
+
+    /// The lowest success rate first. Ties are in the order of the shapes.
+    RateAscending,
+}
+
+/// Aggregates the results of the sequences starting with `prefix`.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct SuccessTreeNode {
+    /// The head shapes shared by the sequences. It's empty for the root.
+    pub prefix: Vec<Shape>,
+
+    /// The count of succeed sequences starting with the prefix.
+    pub count_succeed: u64,
+
+    /// The count of accepted sequences starting with the prefix. Pending sequences are not counted.
+    pub count_accepted: u64,
+
+    /// The nodes whose prefix is one shape longer.
+    pub children: Vec<SuccessTreeNode>,
+}
+
+impl SuccessTreeNode {
+    /// Returns the success rate in the range 0.0 to 1.0. Returns 0.0 if no sequences are accepted.
+    #[inline]
+    pub fn success_rate(&self) -> f64 {
+        if self.count_accepted == 0 {
+            return 0.0;
+        }
+        self.count_succeed as f64 / self.count_accepted as f64
+    }
+
+    /// Returns the count of failed sequences starting with the prefix.
+    #[inline]
+    pub fn count_failed(&self) -> u64 {
+        self.count_accepted - self.count_succeed
+    }
+
+    /// Returns the node with the prefix, searching the descendants.
+    pub fn find(&self, prefix: &[Shape]) -> Option<&SuccessTreeNode> {
+        if !prefix.starts_with(&self.prefix) {
+            return None;
+        }
+        if prefix.len() == self.prefix.len() {
+            return Some(self);
+        }
+        self.children.iter()
+            .find(|child| child.prefix[self.prefix.len()] == prefix[self.prefix.len()])
+            .and_then(|child| child.find(prefix))
+    }
+
+    fn sort(&mut self, order: SuccessTreeOrder) {
+        match order {
+            SuccessTreeOrder::Shape => self.children.sort_by(|left, right| left.prefix.cmp(&right.prefix)),
+            SuccessTreeOrder::RateDescending => self.children.sort_by(|left, right| {
+                right.success_rate().total_cmp(&left.success_rate()).then(left.prefix.cmp(&right.prefix))
+            }),
+            SuccessTreeOrder::RateAscending => self.children.sort_by(|left, right| {
+                left.success_rate().total_cmp(&right.success_rate()).then(left.prefix.cmp(&right.prefix))
+            }),
+        }
+        for child in &mut self.children {
+            child.sort(order);
+        }
+    }
+
+    fn write_lines(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = if self.prefix.is_empty() {
+            "*".to_string()
+        } else {
+            self.prefix.iter().map(|shape| format!("{:?}", shape)).collect::<String>()
+        };
+        let indent = "  ".repeat(self.prefix.len());
+        writeln!(
+            f,
+            "{}{} -> {:.2} % [{}/{}]",
+            indent, label, self.success_rate() * 100.0, self.count_succeed, self.count_accepted,
+        )?;
+        for child in &self.children {
+            child.write_lines(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// The success rates broken down by the head shapes of the sequences.
+/// It's known as the tree of `percent` in solution-finder.
+///
+/// The text representation is available by `to_string()`. Each line is `<prefix> -> <rate> % [<succeed>/<accepted>]`,
+/// and indented by the length of the prefix.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct SuccessTree {
+    root: SuccessTreeNode,
+}
+
+impl SuccessTree {
+    /// Returns the root node, which aggregates all accepted sequences.
+    #[inline]
+    pub fn root(&self) -> &SuccessTreeNode {
+        &self.root
+    }
+
+    /// Returns the node with the prefix. Returns `None` if no accepted sequences start with it or it's deeper than the tree.
+    #[inline]
+    pub fn find(&self, prefix: &[Shape]) -> Option<&SuccessTreeNode> {
+        self.root.find(prefix)
+    }
+}
+
+impl fmt::Display for SuccessTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.root.write_lines(f)
+    }
+}
+
+/// The builder to aggregate `PcResults` into `SuccessTree`.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct SuccessTreeBuilder {
+    /// The maximum length of the prefixes. If it's longer than the sequences, it's limited to their length.
+    pub depth: usize,
+
+    /// The order to sort the children of each node.
+    pub order: SuccessTreeOrder,
+}
+
+impl Default for SuccessTreeBuilder {
+    /// The default values are as follows:
+    ///   + depth: 3
+    ///   + order: the order of the shapes
+    fn default() -> Self {
+        Self { depth: 3, order: SuccessTreeOrder::Shape }
+    }
+}
+
+impl SuccessTreeBuilder {
+    /// Aggregates the accepted sequences. Pending sequences are ignored.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::pc_possible::{PcResults, SuccessTreeBuilder, SuccessTreeOrder};
+    /// use Shape::*;
+    ///
+    /// let mut results = PcResults::new(&vec![
+    ///     ShapeSequence::new(vec![T, I]),
+    ///     ShapeSequence::new(vec![T, O]),
+    ///     ShapeSequence::new(vec![I, T]),
+    ///     ShapeSequence::new(vec![I, O]),
+    /// ]);
+    /// results.accept_if_present(&ShapeSequence::new(vec![T, I]), true);
+    /// results.accept_if_present(&ShapeSequence::new(vec![T, O]), false);
+    /// results.accept_if_present(&ShapeSequence::new(vec![I, T]), true);
+    /// results.accept_if_present(&ShapeSequence::new(vec![I, O]), true);
+    ///
+    /// let builder = SuccessTreeBuilder { depth: 1, order: SuccessTreeOrder::RateDescending };
+    /// let tree = builder.build(&results);
+    ///
+    /// let node = tree.find(&[T]).unwrap();
+    /// assert_eq!(node.count_succeed, 1);
+    /// assert_eq!(node.count_accepted, 2);
+    /// assert_eq!(node.success_rate(), 0.5);
+    ///
+    /// assert_eq!(tree.to_string(), "\
+    /// * -> 75.00 % [3/4]
+    ///   I -> 100.00 % [2/2]
+    ///   T -> 50.00 % [1/2]
+    /// ");
+    /// ```
+    pub fn build(&self, results: &PcResults) -> SuccessTree {
+        // The counts of (succeed, accepted) for each prefix.
+        let mut counts = FxHashMap::<Vec<Shape>, (u64, u64)>::default();
+        for (sequence, result) in results.iter() {
+            let Some(succeed) = *result else {
+                continue;
+            };
+            let shapes = sequence.shapes();
+            for len in 0..=self.depth.min(shapes.len()) {
+                let count = counts.entry(shapes[..len].to_vec()).or_default();
+                if succeed {
+                    count.0 += 1;
+                }
+                count.1 += 1;
+            }
+        }
+
+        let mut children = FxHashMap::<Vec<Shape>, Vec<Vec<Shape>>>::default();
+        for prefix in counts.keys() {
+            if let Some((_, parent)) = prefix.split_last() {
+                children.entry(parent.to_vec()).or_default().push(prefix.clone());
+            }
+        }
+
+        fn make_node(
+            prefix: Vec<Shape>,
+            counts: &FxHashMap<Vec<Shape>, (u64, u64)>,
+            children: &mut FxHashMap<Vec<Shape>, Vec<Vec<Shape>>>,
+        ) -> SuccessTreeNode {
+            let (count_succeed, count_accepted) = counts.get(&prefix).copied().unwrap_or_default();
+            let child_prefixes = children.remove(&prefix).unwrap_or_default();
+            SuccessTreeNode {
+                prefix,
+                count_succeed,
+                count_accepted,
+                children: child_prefixes.into_iter()
+                    .map(|child| make_node(child, counts, children))
+                    .collect(),
+            }
+        }
+
+        let mut root = make_node(Vec::new(), &counts, &mut children);
+        root.sort(self.order);
+        SuccessTree { root }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitris::prelude::*;
+    use itertools::Itertools;
+
+    use crate::pc_possible::{PcResults, SuccessTreeBuilder, SuccessTreeOrder};
+    use crate::ShapeSequence;
+
+    #[test]
+    fn aggregate_by_prefix() {
+        use Shape::*;
+
+        let sequences = [T, I, O].into_iter()
+            .permutations(3)
+            .map(ShapeSequence::new)
+            .collect_vec();
+        let mut results = PcResults::new(&sequences);
+        for sequence in &sequences {
+            // Fails only if it starts with O. Leaves `TOI` pending.
+            if sequence.shapes() == [T, O, I] {
+                continue;
+            }
+            results.accept_if_present(sequence, sequence.shapes()[0] != O);
+        }
+
+        let tree = SuccessTreeBuilder { depth: 2, order: SuccessTreeOrder::RateAscending }.build(&results);
+
+        let root = tree.root();
+        assert_eq!((root.count_succeed, root.count_accepted), (3, 5));
+        assert_eq!(root.children.iter().map(|child| child.prefix.clone()).collect_vec(), vec![
+            vec![O], vec![T], vec![I],
+        ]);
+
+        let node = tree.find(&[T]).unwrap();
+        assert_eq!((node.count_succeed, node.count_accepted), (1, 1));
+        assert_eq!(node.children.len(), 1);
+        assert_eq!(node.children[0].prefix, vec![T, I]);
+        assert!(node.children[0].children.is_empty());
+
+        assert_eq!(tree.find(&[O, I]).unwrap().count_failed(), 1);
+        assert!(tree.find(&[T, O]).is_none());
+        assert!(tree.find(&[T, I, O]).is_none());
+
+        // The depth is limited to the length of the sequences.
+        let tree = SuccessTreeBuilder { depth: 5, order: SuccessTreeOrder::Shape }.build(&results);
+        assert_eq!(tree.find(&[I, T, O]).unwrap().count_accepted, 1);
+        assert_eq!(tree.to_string().lines().count(), 1 + 3 + 5 + 5);
+    }
+
+    #[test]
+    fn empty_results() {
+        let results = PcResults::new(&vec![ShapeSequence::new(vec![Shape::T])]);
+        let tree = SuccessTreeBuilder::default().build(&results);
+        assert_eq!(tree.root().count_accepted, 0);
+        assert_eq!(tree.root().success_rate(), 0.0);
+        assert_eq!(tree.to_string(), "* -> 0.00 % [0/0]\n");
+    }
+}
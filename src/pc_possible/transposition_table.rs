@@ -0,0 +1,169 @@
+use std::mem;
+
+use bitris::prelude::*;
+use fxhash::FxHashMap;
+
+use crate::OrderCursor;
+
+/// Up to 42 shapes can be packed into the key, 3 bits each.
+const MAX_ORDER_LEN: usize = 42;
+
+/// Identifies a searching state independent of the sequence it came from.
+/// The result of the subtree is determined by the board, the height, the progress toward the constraints, and the order of the remaining shapes.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+struct TranspositionKey {
+    // The board does not include filled rows.
+    board: Board64,
+
+    height: u32,
+
+    // The progress toward the constraints of the search. 0 if unconstrained.
+    progress: u64,
+
+    // The remaining shapes packed from the head. Each shape is stored as `shape + 1` so that `0` terminates.
+    order: u128,
+}
+
+impl TranspositionKey {
+    #[inline]
+    fn new(board: Board64, height: u32, progress: u64, cursor: &OrderCursor) -> Option<Self> {
+        if MAX_ORDER_LEN < cursor.len_unused() {
+            return None;
+        }
+
+        let order = cursor.unused_shapes().shapes().iter()
+            .rev()
+            .fold(0u128, |order, &shape| (order << 3) | (shape as u128 + 1));
+        Some(Self { board, height, progress, order })
+    }
+}
+
+/// The proven result of a subtree.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub(crate) enum Proof {
+    Failed,
+
+    /// Holds the shapes placed from the state to take a PC, in the order they are placed.
+    Succeeded(Vec<Shape>),
+}
+
+/// Memorizes the proven results of subtrees across sequences.
+///
+/// The memory is bounded by `capacity` entries. The entries are kept in two generations:
+/// when the current generation is full, it becomes the previous one and the old previous one is dropped.
+/// Entries found in the previous generation are moved back to the current one, so recently used entries survive.
+#[derive(Clone, Debug)]
+pub(crate) struct TranspositionTable {
+    capacity_per_generation: usize,
+    current: FxHashMap<TranspositionKey, Proof>,
+    previous: FxHashMap<TranspositionKey, Proof>,
+}
+
+impl TranspositionTable {
+    /// If the capacity is 0, nothing is memorized.
+    #[inline]
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity_per_generation: capacity / 2,
+            current: FxHashMap::default(),
+            previous: FxHashMap::default(),
+        }
+    }
+
+    #[inline]
+    fn is_enabled(&self) -> bool {
+        0 < self.capacity_per_generation
+    }
+
+    pub(crate) fn get(&mut self, board: Board64, height: u32, progress: u64, cursor: &OrderCursor) -> Option<&Proof> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        let key = TranspositionKey::new(board, height, progress, cursor)?;
+        if !self.current.contains_key(&key) {
+            let proof = self.previous.remove(&key)?;
+            self.insert_key(key, proof);
+        }
+        self.current.get(&key)
+    }
+
+    pub(crate) fn insert(&mut self, board: Board64, height: u32, progress: u64, cursor: &OrderCursor, proof: Proof) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        if let Some(key) = TranspositionKey::new(board, height, progress, cursor) {
+            self.insert_key(key, proof);
+        }
+    }
+
+    fn insert_key(&mut self, key: TranspositionKey, proof: Proof) {
+        if self.capacity_per_generation <= self.current.len() {
+            self.previous = mem::take(&mut self.current);
+        }
+        self.current.insert(key, proof);
+    }
+
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.current.len() + self.previous.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitris::prelude::*;
+
+    use crate::pc_possible::{Proof, TranspositionTable};
+    use crate::{PopOp, ShapeOrder};
+
+    #[test]
+    fn keys_depend_on_remaining_order() {
+        use Shape::*;
+
+        let mut table = TranspositionTable::with_capacity(16);
+        let board = Board64::blank();
+
+        let order = ShapeOrder::new(vec![T, I, O]);
+        let cursor = order.new_cursor();
+        table.insert(board, 4, 0, &cursor, Proof::Succeeded(vec![T, I, O]));
+        assert_eq!(table.get(board, 4, 0, &cursor), Some(&Proof::Succeeded(vec![T, I, O])));
+        assert_eq!(table.get(board, 3, 0, &cursor), None);
+        assert_eq!(table.get(board, 4, 0b11, &cursor), None);
+
+        // The same remaining shapes reached from another order.
+        let other_order = ShapeOrder::new(vec![S, T, I, O]);
+        let other_cursor = other_order.new_cursor();
+        let (_, popped_cursor) = other_cursor.pop(PopOp::First);
+        assert_eq!(table.get(board, 4, 0, &popped_cursor), Some(&Proof::Succeeded(vec![T, I, O])));
+
+        // The hold changes the remaining order.
+        let (_, held_cursor) = other_cursor.pop(PopOp::Second);
+        assert_eq!(table.get(board, 4, 0, &held_cursor), None);
+    }
+
+    #[test]
+    fn bounded_memory() {
+        use Shape::*;
+
+        let mut table = TranspositionTable::with_capacity(4);
+        let board = Board64::blank();
+        let orders = [T, I, O, L, J].map(|shape| ShapeOrder::new(vec![shape]));
+
+        for order in &orders {
+            table.insert(board, 4, 0, &order.new_cursor(), Proof::Failed);
+            assert!(table.len() <= 4);
+        }
+
+        // The oldest ones are dropped.
+        assert_eq!(table.get(board, 4, 0, &orders[0].new_cursor()), None);
+        assert_eq!(table.get(board, 4, 0, &orders[4].new_cursor()), Some(&Proof::Failed));
+
+        // Disabled.
+        let mut table = TranspositionTable::with_capacity(0);
+        table.insert(board, 4, 0, &orders[0].new_cursor(), Proof::Failed);
+        assert_eq!(table.get(board, 4, 0, &orders[0].new_cursor()), None);
+        assert_eq!(table.len(), 0);
+    }
+}
@@ -3,6 +3,8 @@ use std::ops;
 use bitris::pieces::Shape;
 use derive_more::Constructor;
 
+use crate::Mirror;
+
 /// Holds the count of each shape. Each shape can hold up to 255 items.
 /// ```
 /// use bitris_commands::prelude::*;
@@ -84,6 +86,16 @@ impl ops::Index<Shape> for ShapeCounter {
     }
 }
 
+impl Mirror for ShapeCounter {
+    fn mirror(&self) -> Self {
+        let mut counters: [u8; 7] = [0; 7];
+        for shape in Shape::all_iter() {
+            counters[shape.mirror() as usize] = self.counters[shape as usize];
+        }
+        ShapeCounter::new(counters)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use bitris::pieces::Shape;
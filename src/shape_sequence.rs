@@ -2,7 +2,7 @@ use bitris::pieces::Shape;
 
 use crate::internal_macros::forward_impl_from;
 use crate::internals::{FuzzyShape, FuzzyShapeOrder};
-use crate::{BitShapes, ForEachVisitor, ShapeOrder};
+use crate::{BitShapes, ForEachVisitor, Mirror, ShapeOrder};
 
 /// Represents a sequence of shapes.
 /// "Sequence" means that it is not affected by the hold operation.
@@ -114,6 +114,13 @@ impl From<&BitShapes> for ShapeSequence {
 
 forward_impl_from!(ShapeSequence, from BitShapes);
 
+impl Mirror for ShapeSequence {
+    #[inline]
+    fn mirror(&self) -> Self {
+        Self::new(self.shapes.iter().map(|shape| shape.mirror()).collect())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::internals::{FuzzyShape, FuzzyShapeOrder};
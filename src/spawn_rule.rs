@@ -0,0 +1,133 @@
+use bitris::prelude::*;
+
+/// The row where pieces spawn, as the bottom of the piece.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default, Debug)]
+pub enum SpawnRow {
+    /// Spawns 4 rows above the clipped height, so that pieces are never stuck even if harddrop only.
+    #[default]
+    AboveHeight,
+
+    /// Spawns at the fixed row counted from the bottom of the board, regardless of the clipped height.
+    /// If the stack blocks the spawn, the piece cannot be placed.
+    Fixed(i32),
+}
+
+/// Defines the placement where each shape spawns.
+///
+/// The default spawns every shape facing north with its left side at column 5, above the clipped height.
+///
+/// With 20G, pieces drop onto the stack at spawn and after every move and rotation, so they never move in the air.
+/// Combined with `SpawnRow::Fixed`, pieces cannot climb over the stack higher than the spawn row.
+/// ```
+/// use bitris::prelude::*;
+/// use bitris_commands::{SpawnRow, SpawnRule};
+///
+/// // Spawns at row 21 (0-indexed: 20) in the middle, and I faces east.
+/// let spawn_rule = SpawnRule::default()
+///     .with_row(SpawnRow::Fixed(20))
+///     .with_columns(3)
+///     .with_orientation(Shape::I, Orientation::East);
+///
+/// assert_eq!(spawn_rule.placement(Shape::T, 4), Shape::T.with(Orientation::North).with(bl(3, 20)));
+/// assert_eq!(spawn_rule.placement(Shape::I, 4), Shape::I.with(Orientation::East).with(bl(3, 20)));
+///
+/// // Above the height
+/// let spawn_rule = SpawnRule::default();
+/// assert_eq!(spawn_rule.placement(Shape::T, 4), Shape::T.with(Orientation::North).with(bl(5, 8)));
+/// assert!(!spawn_rule.is_20g());
+///
+/// // 20G
+/// let spawn_rule = SpawnRule::default().with_row(SpawnRow::Fixed(20)).with_20g(true);
+/// assert!(spawn_rule.is_20g());
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct SpawnRule {
+    // Indexed by shapes.
+    orientations: [Orientation; 7],
+    columns: [i32; 7],
+    row: SpawnRow,
+    is_20g: bool,
+}
+
+impl Default for SpawnRule {
+    fn default() -> Self {
+        Self {
+            orientations: [Orientation::North; 7],
+            columns: [5; 7],
+            row: SpawnRow::AboveHeight,
+            is_20g: false,
+        }
+    }
+}
+
+impl SpawnRule {
+    /// Sets the orientation the shape spawns with.
+    #[inline]
+    pub fn with_orientation(mut self, shape: Shape, orientation: Orientation) -> Self {
+        self.orientations[shape as usize] = orientation;
+        self
+    }
+
+    /// Sets the leftmost column of the shape at spawn.
+    #[inline]
+    pub fn with_column(mut self, shape: Shape, lx: i32) -> Self {
+        self.columns[shape as usize] = lx;
+        self
+    }
+
+    /// Sets the leftmost column of all shapes at spawn.
+    #[inline]
+    pub fn with_columns(mut self, lx: i32) -> Self {
+        self.columns = [lx; 7];
+        self
+    }
+
+    #[inline]
+    pub fn with_row(mut self, row: SpawnRow) -> Self {
+        self.row = row;
+        self
+    }
+
+    /// Sets whether pieces drop onto the stack at spawn and after every move and rotation.
+    #[inline]
+    pub fn with_20g(mut self, enabled: bool) -> Self {
+        self.is_20g = enabled;
+        self
+    }
+
+    #[inline]
+    pub fn orientation(&self, shape: Shape) -> Orientation {
+        self.orientations[shape as usize]
+    }
+
+    #[inline]
+    pub fn column(&self, shape: Shape) -> i32 {
+        self.columns[shape as usize]
+    }
+
+    #[inline]
+    pub fn row(&self) -> SpawnRow {
+        self.row
+    }
+
+    #[inline]
+    pub fn is_20g(&self) -> bool {
+        self.is_20g
+    }
+
+    /// Returns the placement where the shape spawns on the board clipped at the height.
+    #[inline]
+    pub fn placement(&self, shape: Shape, height: u32) -> BlPlacement {
+        let by = match self.row {
+            SpawnRow::AboveHeight => height as i32 + 4,
+            SpawnRow::Fixed(row) => row,
+        };
+        shape.with(self.orientation(shape)).with(bl(self.column(shape), by))
+    }
+
+    /// Returns `true` if the spawn of the shape overlaps the blocks or is out of the board.
+    #[inline]
+    pub fn is_blocked(&self, shape: Shape, board: &Board64, height: u32) -> bool {
+        !self.placement(shape, height).is_in_free_space(board)
+    }
+}
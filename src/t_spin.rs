@@ -0,0 +1,251 @@
+use std::collections::VecDeque;
+
+use bitris::prelude::*;
+use fxhash::FxHashSet;
+
+use crate::input_moves::drop_to_bottom;
+use crate::RotationSystem180;
+
+/// A collection of T-spins that clear lines.
+/// T-spins that clear no lines are not distinguished from other placements, since they have no attack.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum TSpinKind {
+    /// T-spin mini that clears one or two lines.
+    Mini,
+    Single,
+    Double,
+    Triple,
+}
+
+/// Detects T-spins of the placements on a board.
+///
+/// A T placement is a T-spin if it can be reached with a rotation as the last move, and three or more corners around the center are occupied.
+/// It's a mini unless both corners on the pointing side are occupied or the rotation used the `(±1, ±2)` kick.
+/// The 180° rotation is also counted if the rotation system defines it.
+/// The positions reachable from the spawn are searched once when it's made, so it can be reused for all placements on the same board.
+/// Use `new_in_20g()` if the pieces drop after every move and rotation. See `SpawnRule::with_20g()`.
+/// ```
+/// use std::str::FromStr;
+/// use bitris::prelude::*;
+/// use bitris_commands::{TSpinDetector, TSpinKind};
+///
+/// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+/// let board = Board64::from_str("
+///     XXXX....XX
+///     XXX...XXXX
+///     XXXX.XXXXX
+/// ").unwrap();
+/// let spawn = Shape::T.with(Orientation::North).with(bl(4, 20));
+/// let detector = TSpinDetector::new(&move_rules, board, spawn);
+///
+/// // T-spin double under the overhang
+/// assert_eq!(detector.detect(Shape::T.with(Orientation::South).with(cc(4, 1))), Some(TSpinKind::Double));
+///
+/// // Not a T-spin since the last move cannot be a rotation, even though three corners are occupied and two lines are cleared.
+/// let board = Board64::from_str("
+///     X.........
+///     X..XXXXXXX
+///     X.XXXXXXXX
+/// ").unwrap();
+/// let detector = TSpinDetector::new(&move_rules, board, spawn);
+/// assert_eq!(detector.detect(Shape::T.with(Orientation::East).with(cc(1, 1))), None);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TSpinDetector<'a, T: RotationSystem180> {
+    move_rules: &'a MoveRules<'a, T>,
+    board: Board64,
+    reachable: FxHashSet<CcPlacement>,
+}
+
+impl<'a, T: RotationSystem180> TSpinDetector<'a, T> {
+    /// Makes the detector for T pieces spawned at the placement.
+    pub fn new(move_rules: &'a MoveRules<'a, T>, board: Board64, spawn: BlPlacement) -> Self {
+        Self::build(move_rules, board, spawn, false)
+    }
+
+    /// Makes the detector for T pieces spawned at the placement in 20G, where the piece drops at spawn and after every move and rotation.
+    pub fn new_in_20g(move_rules: &'a MoveRules<'a, T>, board: Board64, spawn: BlPlacement) -> Self {
+        Self::build(move_rules, board, spawn, true)
+    }
+
+    fn build(move_rules: &'a MoveRules<'a, T>, board: Board64, spawn: BlPlacement, is_20g: bool) -> Self {
+        debug_assert_eq!(spawn.piece.shape, Shape::T);
+        let reachable = Self::search_reachable(move_rules, &board, spawn.to_cc_placement(), is_20g);
+        Self { move_rules, board, reachable }
+    }
+
+    /// Returns the kind of T-spin of the placement. Returns `None` if it is not a T-spin or clears no lines.
+    /// The placement is expected to be landed on the board.
+    pub fn detect(&self, placement: CcPlacement) -> Option<TSpinKind> {
+        if placement.piece.shape != Shape::T || !placement.is_in_free_space(&self.board) {
+            return None;
+        }
+
+        let center = placement.position;
+        let is_occupied = |dx: i32, dy: i32| {
+            let (x, y) = (center.cx + dx, center.cy + dy);
+            !(0..10).contains(&x) || y < 0 || self.board.is_occupied_at(xy(x, y))
+        };
+
+        let corners = [(-1, 1), (1, 1), (1, -1), (-1, -1)];
+        if corners.iter().filter(|&&(dx, dy)| is_occupied(dx, dy)).count() < 3 {
+            return None;
+        }
+
+        // The corners on the pointing side.
+        let front = match placement.piece.orientation {
+            Orientation::North => [corners[0], corners[1]],
+            Orientation::East => [corners[1], corners[2]],
+            Orientation::South => [corners[2], corners[3]],
+            Orientation::West => [corners[3], corners[0]],
+        };
+        let front_occupied = front.iter().all(|&(dx, dy)| is_occupied(dx, dy));
+
+        // The pieces before the rotation, and the kicks to the placement.
+        let rotation_system = self.move_rules.rotation_system;
+        let from_ccw = placement.piece.rotate(Rotation::Ccw);
+        let from_cw = placement.piece.rotate(Rotation::Cw);
+        let from_180 = from_cw.rotate(Rotation::Cw);
+        let last_rotations = [
+            (from_ccw, rotation_system.iter_kicks(from_ccw, Rotation::Cw).as_slice()),
+            (from_cw, rotation_system.iter_kicks(from_cw, Rotation::Ccw).as_slice()),
+            (from_180, rotation_system.iter_kicks_180(from_180).as_slice()),
+        ];
+
+        let mut is_mini = None;
+        for (from_piece, kicks) in last_rotations {
+            for kick in self.last_kicks(placement, from_piece, kicks) {
+                let regular = front_occupied || (kick.offset.dx.abs() == 1 && kick.offset.dy.abs() == 2);
+                is_mini = Some(is_mini.unwrap_or(true) && !regular);
+            }
+        }
+        let is_mini = is_mini?;
+
+        let mut board = self.board;
+        let lines = placement.to_bl_placement().place_on_and_clear_lines(&mut board).ok()?.count();
+        match (is_mini, lines) {
+            (_, 0) => None,
+            (true, _) => Some(TSpinKind::Mini),
+            (false, 1) => Some(TSpinKind::Single),
+            (false, 2) => Some(TSpinKind::Double),
+            (false, _) => Some(TSpinKind::Triple),
+        }
+    }
+
+    /// Returns the kicks with which the rotation from a reachable placement of the piece lands on the placement.
+    fn last_kicks(&self, placement: CcPlacement, from_piece: Piece, kicks: &[Kick]) -> Vec<Kick> {
+        kicks.iter().enumerate()
+            .filter_map(|(index, kick)| {
+                let from = CcPlacement::new(from_piece, cc(
+                    placement.position.cx - kick.offset.dx,
+                    placement.position.cy - kick.offset.dy,
+                ));
+                if !self.reachable.contains(&from) {
+                    return None;
+                }
+
+                // The earlier kicks take priority.
+                let is_prior_free = kicks[..index].iter().any(|prior| {
+                    let to = CcPlacement::new(placement.piece, cc(
+                        from.position.cx + prior.offset.dx,
+                        from.position.cy + prior.offset.dy,
+                    ));
+                    to.is_in_free_space(&self.board)
+                });
+                (!is_prior_free).then_some(*kick)
+            })
+            .collect()
+    }
+
+    /// Returns all positions reachable from the spawn, including the floating ones unless in 20G.
+    fn search_reachable(move_rules: &MoveRules<T>, board: &Board64, spawn: CcPlacement, is_20g: bool) -> FxHashSet<CcPlacement> {
+        let mut reachable = FxHashSet::default();
+        if !spawn.is_in_free_space(board) {
+            return reachable;
+        }
+
+        let fall = |placement: CcPlacement| if is_20g { drop_to_bottom(board, placement) } else { placement };
+        let spawn = fall(spawn);
+
+        let mut queue = VecDeque::new();
+        reachable.insert(spawn);
+        queue.push_back(spawn);
+
+        while let Some(current) = queue.pop_front() {
+            let mut nexts = Vec::with_capacity(6);
+            for dx in [-1, 1] {
+                nexts.push(CcPlacement::new(current.piece, cc(current.position.cx + dx, current.position.cy)));
+            }
+            if move_rules.allow_move == AllowMove::Softdrop {
+                nexts.push(CcPlacement::new(current.piece, cc(current.position.cx, current.position.cy - 1)));
+            }
+
+            let rotations = [
+                (current.piece.rotate(Rotation::Cw), move_rules.rotation_system.iter_kicks(current.piece, Rotation::Cw)),
+                (current.piece.rotate(Rotation::Ccw), move_rules.rotation_system.iter_kicks(current.piece, Rotation::Ccw)),
+                (current.piece.rotate(Rotation::Cw).rotate(Rotation::Cw), move_rules.rotation_system.iter_kicks_180(current.piece)),
+            ];
+            for (piece, kicks) in rotations {
+                let rotated = kicks
+                    .map(|kick| CcPlacement::new(piece, cc(
+                        current.position.cx + kick.offset.dx,
+                        current.position.cy + kick.offset.dy,
+                    )))
+                    .find(|placement| placement.is_in_free_space(board));
+                nexts.extend(rotated);
+            }
+
+            for next in nexts {
+                if !next.is_in_free_space(board) {
+                    continue;
+                }
+                let next = fall(next);
+                if reachable.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        reachable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::{TSpinDetector, TSpinKind};
+
+    #[test]
+    fn t_spin_kinds() {
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+
+        // TSS by the right wall
+        let board = Board64::from_str("
+            XXXXXXXX..
+            XXXXXX....
+            XXXXXXXX.X
+        ").unwrap();
+        let detector = TSpinDetector::new(&move_rules, board, Shape::T.with(Orientation::North).with(bl(4, 20)));
+        assert_eq!(detector.detect(Shape::T.with(Orientation::South).with(cc(8, 1))), Some(TSpinKind::Single));
+
+        // Mini: the pointing side is open.
+        let board = Board64::from_str("
+            ...XXXXXXX
+            .XXXXXXXXX
+        ").unwrap();
+        let detector = TSpinDetector::new(&move_rules, board, Shape::T.with(Orientation::North).with(bl(4, 20)));
+        assert_eq!(detector.detect(Shape::T.with(Orientation::East).with(cc(0, 1))), Some(TSpinKind::Mini));
+
+        // Harddrop cannot rotate after landing.
+        let move_rules = MoveRules::srs(AllowMove::Harddrop);
+        let detector = TSpinDetector::new(&move_rules, board, Shape::T.with(Orientation::North).with(bl(4, 20)));
+        assert_eq!(detector.detect(Shape::T.with(Orientation::East).with(cc(0, 1))), None);
+
+        // In 20G, the piece is always on the stack, so it can rotate after landing even without softdrop.
+        let detector = TSpinDetector::new_in_20g(&move_rules, board, Shape::T.with(Orientation::North).with(bl(4, 20)));
+        assert_eq!(detector.detect(Shape::T.with(Orientation::East).with(cc(0, 1))), Some(TSpinKind::Mini));
+    }
+}
@@ -1,4 +1,38 @@
+use bitris::pieces::Shape;
+
 /// This trait for direct processing without creating vec.
 pub trait ForEachVisitor<T: ?Sized> {
     fn visit(&mut self, arg: &T);
 }
+
+/// Flips horizontally. The shapes are swapped L↔J and S↔Z, and the others stay the same.
+///
+/// A board mirrored with the shapes mirrored has the same PC results.
+/// ```
+/// use bitris_commands::prelude::*;
+/// use Shape::*;
+///
+/// assert_eq!(L.mirror(), J);
+/// assert_eq!(S.mirror(), Z);
+/// assert_eq!(T.mirror(), T);
+///
+/// let sequence = ShapeSequence::new(vec![T, L, S, I]);
+/// assert_eq!(sequence.mirror(), ShapeSequence::new(vec![T, J, Z, I]));
+/// assert_eq!(sequence.mirror().mirror(), sequence);
+/// ```
+pub trait Mirror {
+    fn mirror(&self) -> Self;
+}
+
+impl Mirror for Shape {
+    #[inline]
+    fn mirror(&self) -> Self {
+        match *self {
+            Shape::L => Shape::J,
+            Shape::J => Shape::L,
+            Shape::S => Shape::Z,
+            Shape::Z => Shape::S,
+            shape => shape,
+        }
+    }
+}